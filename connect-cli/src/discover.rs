@@ -0,0 +1,577 @@
+use anyhow::{Context, Result, bail};
+use cargo_metadata::{MetadataCommand, Package, Target as CargoTarget, TargetKind};
+use quint_connect_spec::{SpecSources, test_name};
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+use syn::{Attribute, Item, LitStr, Macro, Token, meta::ParseNestedMeta};
+
+/// Macros generating model-based tests.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Kind {
+    Run,
+    Test,
+    Tests,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Kind::Run => write!(f, "quint_run"),
+            Kind::Test => write!(f, "quint_test"),
+            Kind::Tests => write!(f, "quint_tests"),
+        }
+    }
+}
+
+/// The cargo target a test is compiled into.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Target {
+    Lib,
+    Bin(String),
+    Test(String),
+    Example(String),
+}
+
+impl Target {
+    /// The `cargo test` arguments selecting this target.
+    pub fn cargo_args(&self) -> Vec<&str> {
+        match self {
+            Target::Lib => vec!["--lib"],
+            Target::Bin(name) => vec!["--bin", name],
+            Target::Test(name) => vec!["--test", name],
+            Target::Example(name) => vec!["--example", name],
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Target::Lib => write!(f, "lib"),
+            Target::Bin(name) => write!(f, "bin {}", name),
+            Target::Test(name) => write!(f, "test {}", name),
+            Target::Example(name) => write!(f, "example {}", name),
+        }
+    }
+}
+
+/// A test generated by the `quint_run`, `quint_test` or `quint_tests` macros.
+#[derive(Clone, Debug)]
+pub struct QuintTest {
+    /// The test path within its target, e.g. `tests::simulation`.
+    pub path: String,
+    pub kind: Kind,
+    /// The spec path, relative to the package root.
+    pub spec: String,
+    /// The Quint test name, for `quint_test` and `quint_tests`.
+    pub test: Option<String>,
+    /// Whether the test expands into a module of tests, one per matrix case.
+    pub matrix: bool,
+    pub package: String,
+    pub manifest_path: PathBuf,
+    pub target: Target,
+}
+
+impl QuintTest {
+    /// The function name of the test.
+    pub fn name(&self) -> &str {
+        self.path.rsplit("::").next().unwrap_or(&self.path)
+    }
+
+    /// The spec path, resolved against the package root.
+    pub fn spec_path(&self) -> PathBuf {
+        let root = self.manifest_path.parent().unwrap_or(Path::new("."));
+        root.join(&self.spec)
+    }
+
+    /// Whether this test is selected by the given name, which may either be its
+    /// function name or its full path.
+    pub fn matches(&self, name: &str) -> bool {
+        self.path == name || self.name() == name
+    }
+}
+
+/// Finds all Quint Connect tests in the workspace of the given manifest.
+pub fn discover(manifest_path: Option<&Path>, package: Option<&str>) -> Result<Vec<QuintTest>> {
+    let mut cmd = MetadataCommand::new();
+    cmd.no_deps();
+    if let Some(path) = manifest_path {
+        cmd.manifest_path(path);
+    }
+    let metadata = cmd.exec().context("Failed to read cargo metadata")?;
+
+    let mut tests = Vec::new();
+    for pkg in metadata.workspace_packages() {
+        if package.is_some_and(|name| name != pkg.name.as_str()) {
+            continue;
+        }
+        for target in &pkg.targets {
+            if let Some(kind) = target_kind(target) {
+                scan_target(pkg, target, kind, &mut tests)?;
+            }
+        }
+    }
+
+    if let Some(name) = package
+        && !metadata
+            .workspace_packages()
+            .iter()
+            .any(|pkg| pkg.name.as_str() == name)
+    {
+        bail!("Package `{}` is not a member of the workspace", name)
+    }
+
+    Ok(tests)
+}
+
+/// Finds the single test selected by the given name.
+pub fn select<'a>(tests: &'a [QuintTest], name: &str) -> Result<&'a QuintTest> {
+    let matches: Vec<_> = tests.iter().filter(|test| test.matches(name)).collect();
+    match matches.as_slice() {
+        [test] => Ok(test),
+        [] => bail!(
+            "No Quint Connect test named `{}` was found.\n\
+             Run `cargo quint-connect list` to see all available tests.",
+            name
+        ),
+        _ => {
+            let candidates: Vec<_> = matches
+                .iter()
+                .map(|test| format!("  {} ({}, {})", test.path, test.package, test.target))
+                .collect();
+            bail!(
+                "Test name `{}` is ambiguous. Please use one of:\n{}",
+                name,
+                candidates.join("\n")
+            )
+        }
+    }
+}
+
+fn target_kind(target: &CargoTarget) -> Option<Target> {
+    let name = target.name.clone();
+    target.kind.iter().find_map(|kind| match kind {
+        TargetKind::Lib | TargetKind::RLib | TargetKind::ProcMacro => Some(Target::Lib),
+        TargetKind::Bin => Some(Target::Bin(name.clone())),
+        TargetKind::Test => Some(Target::Test(name.clone())),
+        TargetKind::Example => Some(Target::Example(name.clone())),
+        _ => None,
+    })
+}
+
+fn scan_target(
+    pkg: &Package,
+    target: &CargoTarget,
+    kind: Target,
+    tests: &mut Vec<QuintTest>,
+) -> Result<()> {
+    let root = target.src_path.as_std_path();
+    let dir = root.parent().unwrap_or(Path::new("."));
+    let mut found = Vec::new();
+    scan_file(root, dir, &mut Vec::new(), &mut found)?;
+
+    let root = pkg
+        .manifest_path
+        .parent()
+        .map_or(Path::new("."), |dir| dir.as_std_path());
+    for found in found {
+        if found.kind == Kind::Tests {
+            let spec = root.join(&found.spec);
+            let runs = list_runs(&spec, found.main.as_deref()).with_context(|| {
+                format!(
+                    "Failed to list the runs of `quint_tests!` in `{}`",
+                    found.path
+                )
+            })?;
+            for run in runs {
+                let name = test_name(&run);
+                let path = match found.path.as_str() {
+                    "" => name,
+                    module => format!("{}::{}", module, name),
+                };
+                tests.push(QuintTest {
+                    path,
+                    kind: found.kind,
+                    spec: found.spec.clone(),
+                    test: Some(run),
+                    matrix: false,
+                    package: pkg.name.to_string(),
+                    manifest_path: pkg.manifest_path.clone().into_std_path_buf(),
+                    target: kind.clone(),
+                });
+            }
+            continue;
+        }
+        tests.push(QuintTest {
+            path: found.path,
+            kind: found.kind,
+            spec: found.spec,
+            test: found.test,
+            matrix: found.matrix,
+            package: pkg.name.to_string(),
+            manifest_path: pkg.manifest_path.clone().into_std_path_buf(),
+            target: kind.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// A test found in a source file, not yet attached to a package. For
+/// `quint_tests!`, the path is the module the macro is invoked in, and the tests
+/// are listed from the spec once its package is known.
+#[derive(PartialEq, Debug)]
+struct Found {
+    path: String,
+    kind: Kind,
+    spec: String,
+    test: Option<String>,
+    main: Option<String>,
+    matrix: bool,
+}
+
+/// Scans a source file, following `mod` declarations. The `dir` argument is where
+/// child module files are looked up.
+fn scan_file(
+    file: &Path,
+    dir: &Path,
+    module: &mut Vec<String>,
+    out: &mut Vec<Found>,
+) -> Result<()> {
+    let source = fs::read_to_string(file)
+        .with_context(|| format!("Failed to read source file at: {}", file.display()))?;
+    let ast = syn::parse_file(&source)
+        .with_context(|| format!("Failed to parse source file at: {}", file.display()))?;
+    scan_items(&ast.items, dir, module, out)
+}
+
+fn scan_items(
+    items: &[Item],
+    dir: &Path,
+    module: &mut Vec<String>,
+    out: &mut Vec<Found>,
+) -> Result<()> {
+    for item in items {
+        match item {
+            Item::Fn(func) => {
+                if let Some(found) = parse_test(&func.attrs, &func.sig.ident.to_string(), module)? {
+                    out.push(found);
+                }
+            }
+            Item::Macro(item)
+                if item
+                    .mac
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|seg| seg.ident == "quint_tests") =>
+            {
+                if let Some(found) = parse_tests(&item.mac, module)? {
+                    out.push(found);
+                }
+            }
+            Item::Mod(item) => {
+                let name = item.ident.to_string();
+                let child_dir = dir.join(&name);
+                module.push(name.clone());
+                match &item.content {
+                    Some((_, items)) => scan_items(items, &child_dir, module, out)?,
+                    None => {
+                        // Modules that can't be found are likely behind a `#[path]` or
+                        // `#[cfg]` attribute; they are skipped rather than failing.
+                        let file = dir.join(format!("{}.rs", name));
+                        let mod_file = child_dir.join("mod.rs");
+                        if file.is_file() {
+                            scan_file(&file, &child_dir, module, out)?;
+                        } else if mod_file.is_file() {
+                            scan_file(&mod_file, &child_dir, module, out)?;
+                        }
+                    }
+                }
+                module.pop();
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn parse_test(attrs: &[Attribute], name: &str, module: &[String]) -> Result<Option<Found>> {
+    for attr in attrs {
+        let kind = match attr.path().segments.last() {
+            Some(seg) if seg.ident == "quint_run" => Kind::Run,
+            Some(seg) if seg.ident == "quint_test" => Kind::Test,
+            _ => continue,
+        };
+
+        let mut spec = None;
+        let mut test = None;
+        let mut main = None;
+        let mut matrix = false;
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("spec") {
+                spec = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("test") {
+                test = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("main") {
+                main = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else {
+                matrix |= meta.path.is_ident("matrix");
+                skip_meta(&meta)?;
+            }
+            Ok(())
+        })
+        .with_context(|| format!("Failed to parse `{}` attribute on `{}`", kind, name))?;
+
+        let path = module
+            .iter()
+            .map(String::as_str)
+            .chain([name])
+            .collect::<Vec<_>>()
+            .join("::");
+
+        return Ok(spec.map(|spec| Found {
+            path,
+            kind,
+            spec,
+            test,
+            main,
+            matrix,
+        }));
+    }
+    Ok(None)
+}
+
+fn parse_tests(mac: &Macro, module: &[String]) -> Result<Option<Found>> {
+    let mut spec = None;
+    let mut main = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("spec") {
+            spec = Some(meta.value()?.parse::<LitStr>()?.value());
+        } else if meta.path.is_ident("main") {
+            main = Some(meta.value()?.parse::<LitStr>()?.value());
+        } else {
+            skip_meta(&meta)?;
+        }
+        Ok(())
+    });
+    mac.parse_body_with(parser)
+        .with_context(|| format!("Failed to parse `quint_tests!` in `{}`", module.join("::")))?;
+
+    Ok(spec.map(|spec| Found {
+        path: module.join("::"),
+        kind: Kind::Tests,
+        spec,
+        test: None,
+        main,
+        matrix: false,
+    }))
+}
+
+/// Lists the `run` definitions of the spec's main module and of the modules it
+/// imports, read with the same scanner as `quint_tests!`.
+fn list_runs(spec: &Path, main: Option<&str>) -> Result<Vec<String>> {
+    let sources = SpecSources::read(spec)
+        .with_context(|| format!("Failed to read spec file at: {}", spec.display()))?;
+    visible_runs(&sources, main)
+}
+
+fn visible_runs(sources: &SpecSources, main: Option<&str>) -> Result<Vec<String>> {
+    let main = sources.main_name(main);
+    match sources.module(&main) {
+        Some(module) => Ok(sources.visible_names(module, "run")),
+        None => bail!("Module `{}` not found in spec", main),
+    }
+}
+
+/// Skips attribute arguments this tool doesn't care about.
+fn skip_meta(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+        meta.input.step(|cursor| match cursor.token_tree() {
+            Some((_, rest)) => Ok(((), rest)),
+            None => Err(cursor.error("expected attribute arguments")),
+        })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(source: &str) -> Vec<Found> {
+        let ast = syn::parse_file(source).unwrap();
+        let mut out = Vec::new();
+        scan_items(&ast.items, Path::new("."), &mut Vec::new(), &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_scan_tests() {
+        let found = scan(
+            r#"
+            use quint_connect::*;
+
+            #[quint_run(spec = "spec.qnt", max_samples = 10, seed = "0x42")]
+            fn simulation() -> impl Driver { MyDriver::default() }
+
+            mod tests {
+                #[quint_connect::quint_test(spec = "spec.qnt", test = "happyPath")]
+                fn happy_path() -> impl Driver { MyDriver::default() }
+
+                #[test]
+                fn unit() {}
+            }
+            "#,
+        );
+
+        assert_eq!(
+            found,
+            vec![
+                Found {
+                    path: "simulation".to_string(),
+                    kind: Kind::Run,
+                    spec: "spec.qnt".to_string(),
+                    test: None,
+                    main: None,
+                    matrix: false,
+                },
+                Found {
+                    path: "tests::happy_path".to_string(),
+                    kind: Kind::Test,
+                    spec: "spec.qnt".to_string(),
+                    test: Some("happyPath".to_string()),
+                    main: None,
+                    matrix: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_skip_unknown_arguments() {
+        let found = scan(
+            r#"
+            #[quint_run(max_steps = 1 + 1, future(a = 1), spec = "spec.qnt")]
+            fn simulation() -> impl Driver { MyDriver::default() }
+            "#,
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].spec, "spec.qnt");
+    }
+
+    #[test]
+    fn test_scan_matrix() {
+        let found = scan(
+            r#"
+            #[quint_run(spec = "spec.qnt", matrix(max_steps = [5, 20], main = ["a", "b"]))]
+            fn simulation() -> impl Driver { MyDriver::default() }
+            "#,
+        );
+        assert_eq!(found.len(), 1);
+        assert!(found[0].matrix);
+    }
+
+    #[test]
+    fn test_scan_quint_tests() {
+        let found = scan(
+            r#"
+            mod tests {
+                quint_connect::quint_tests!(
+                    spec = "spec.qnt",
+                    main = "counter",
+                    driver = CounterDriver::default(),
+                );
+            }
+            "#,
+        );
+        assert_eq!(
+            found,
+            vec![Found {
+                path: "tests".to_string(),
+                kind: Kind::Tests,
+                spec: "spec.qnt".to_string(),
+                test: None,
+                main: Some("counter".to_string()),
+                matrix: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_visible_runs() {
+        let sources = SpecSources::parse(
+            "/pkg/spec/counter.qnt",
+            r#"
+            module choreo {
+              run choreoTest = true
+            }
+
+            module counter {
+              import choreo as choreo
+              run incTest = true
+            }
+            "#,
+        );
+        assert_eq!(
+            visible_runs(&sources, None).unwrap(),
+            ["incTest", "choreo::choreoTest"]
+        );
+        assert_eq!(
+            visible_runs(&sources, Some("choreo")).unwrap(),
+            ["choreoTest"]
+        );
+        assert_eq!(
+            visible_runs(&sources, Some("missing"))
+                .unwrap_err()
+                .to_string(),
+            "Module `missing` not found in spec"
+        );
+    }
+
+    fn test(path: &str, package: &str) -> QuintTest {
+        QuintTest {
+            path: path.to_string(),
+            kind: Kind::Run,
+            spec: "spec.qnt".to_string(),
+            test: None,
+            matrix: false,
+            package: package.to_string(),
+            manifest_path: PathBuf::from("/pkg/Cargo.toml"),
+            target: Target::Lib,
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let tests = vec![
+            test("simulation", "a"),
+            test("tests::simulation", "b"),
+            test("tests::happy_path", "b"),
+        ];
+
+        assert_eq!(
+            select(&tests, "happy_path").unwrap().path,
+            "tests::happy_path"
+        );
+        assert_eq!(select(&tests, "tests::simulation").unwrap().package, "b");
+        assert!(
+            select(&tests, "simulation")
+                .unwrap_err()
+                .to_string()
+                .contains("ambiguous")
+        );
+        assert!(select(&tests, "missing").is_err());
+    }
+
+    #[test]
+    fn test_spec_path() {
+        assert_eq!(
+            test("simulation", "a").spec_path(),
+            PathBuf::from("/pkg/spec.qnt")
+        );
+    }
+}
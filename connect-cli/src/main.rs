@@ -0,0 +1,173 @@
+mod discover;
+mod run;
+mod show;
+mod watch;
+
+use crate::{discover::discover, run::Overrides};
+use anyhow::Result;
+use clap::{Args, Parser, Subcommand};
+use colored::Colorize;
+use std::{path::PathBuf, process::ExitCode};
+
+#[derive(Parser)]
+#[command(name = "cargo", bin_name = "cargo")]
+enum Cargo {
+    #[command(name = "quint-connect", version, about)]
+    QuintConnect(Cli),
+}
+
+/// List, run, replay and watch Quint Connect model-based tests.
+#[derive(Args)]
+struct Cli {
+    /// Path to the workspace or package `Cargo.toml`
+    #[arg(long, global = true)]
+    manifest_path: Option<PathBuf>,
+
+    /// Only consider tests from the given package
+    #[arg(short, long, global = true)]
+    package: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List `quint_run`, `quint_test` and `quint_tests!` tests and their specs
+    List,
+
+    /// Run a single test
+    Run {
+        /// The test function name or its full path, e.g. `tests::simulation`
+        test: String,
+
+        #[command(flatten)]
+        overrides: Overrides,
+    },
+
+    /// Replay a saved ITF trace against a test
+    Replay {
+        /// The test function name or its full path, e.g. `tests::simulation`
+        test: String,
+
+        /// The ITF trace file to replay
+        trace: PathBuf,
+
+        /// Open the trace in the interactive debugger (requires the `debugger`
+        /// feature of `quint-connect`)
+        #[arg(long)]
+        debug: bool,
+
+        #[command(flatten)]
+        overrides: Overrides,
+    },
+
+    /// Pretty-print an ITF trace in Quint syntax
+    Show {
+        /// The ITF trace file to print
+        trace: PathBuf,
+    },
+
+    /// Watch spec files and rerun the tests affected by changes
+    Watch {
+        /// Only watch the given tests (defaults to all tests)
+        tests: Vec<String>,
+
+        #[command(flatten)]
+        overrides: Overrides,
+    },
+}
+
+fn main() -> ExitCode {
+    let Cargo::QuintConnect(cli) = Cargo::parse();
+    match run(cli) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("{} {:?}", "error:".bold().red(), err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the given command, returning whether it succeeded.
+fn run(cli: Cli) -> Result<bool> {
+    let manifest_path = cli.manifest_path.as_deref();
+    let package = cli.package.as_deref();
+
+    match cli.command {
+        Command::List => {
+            let tests = discover(manifest_path, package)?;
+            run::list(&tests);
+            Ok(true)
+        }
+        Command::Run { test, overrides } => {
+            let tests = discover(manifest_path, package)?;
+            let test = discover::select(&tests, &test)?;
+            run::run(test, &overrides.envs())
+        }
+        Command::Replay {
+            test,
+            trace,
+            debug,
+            overrides,
+        } => {
+            let tests = discover(manifest_path, package)?;
+            let test = discover::select(&tests, &test)?;
+            run::replay(test, &trace, debug, &overrides)
+        }
+        Command::Show { trace } => {
+            show::show(&trace)?;
+            Ok(true)
+        }
+        Command::Watch { tests, overrides } => {
+            let all = discover(manifest_path, package)?;
+            let selected = if tests.is_empty() {
+                all
+            } else {
+                tests
+                    .iter()
+                    .map(|name| discover::select(&all, name).cloned())
+                    .collect::<Result<_>>()?
+            };
+            watch::watch(&selected, &overrides.envs())?;
+            Ok(true)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli() {
+        Cargo::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_run() {
+        let Cargo::QuintConnect(cli) = Cargo::parse_from([
+            "cargo",
+            "quint-connect",
+            "run",
+            "simulation",
+            "--seed",
+            "0x42",
+            "--max-steps",
+            "10",
+        ]);
+        let Command::Run { test, overrides } = cli.command else {
+            panic!("expected the run command");
+        };
+        assert_eq!(test, "simulation");
+        assert_eq!(
+            overrides.envs(),
+            vec![
+                ("QUINT_SEED", "0x42".to_string()),
+                ("QUINT_MAX_STEPS", "10".to_string())
+            ]
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use crate::discover::{Kind, QuintTest};
+use anyhow::{Context, Result};
+use clap::Args;
+use colored::Colorize;
+use std::{env, path::Path, process::Command};
+
+/// Overrides for the test configuration, passed to tests as environment variables.
+#[derive(Args, Default)]
+pub struct Overrides {
+    /// The random seed used to generate traces, unless the test pins its own
+    #[arg(long)]
+    seed: Option<String>,
+
+    /// The maximum number of traces to generate
+    #[arg(long)]
+    max_samples: Option<usize>,
+
+    /// The maximum number of steps per trace
+    #[arg(long)]
+    max_steps: Option<usize>,
+
+    /// The output verbosity, from 0 to 2
+    #[arg(short, long)]
+    verbose: Option<u8>,
+
+    /// Comma-separated failure report formats, e.g. `html,itf`
+    #[arg(long)]
+    report: Option<String>,
+}
+
+impl Overrides {
+    pub fn envs(&self) -> Vec<(&'static str, String)> {
+        let mut envs = Vec::new();
+        let mut push = |name, value: Option<String>| {
+            if let Some(value) = value {
+                envs.push((name, value));
+            }
+        };
+        push("QUINT_SEED", self.seed.clone());
+        push("QUINT_MAX_SAMPLES", self.max_samples.map(|n| n.to_string()));
+        push("QUINT_MAX_STEPS", self.max_steps.map(|n| n.to_string()));
+        push("QUINT_VERBOSE", self.verbose.map(|n| n.to_string()));
+        push("QUINT_REPORT", self.report.clone());
+        envs
+    }
+}
+
+/// Prints the given tests grouped by package and target.
+pub fn list(tests: &[QuintTest]) {
+    if tests.is_empty() {
+        println!("No Quint Connect tests found.");
+        return;
+    }
+
+    let mut group = None;
+    for test in tests {
+        let current = Some((&test.package, &test.target));
+        if group != current {
+            println!("{} ({})", test.package.bold(), test.target);
+            group = current;
+        }
+        let spec = match (&test.kind, &test.test) {
+            (Kind::Test | Kind::Tests, Some(name)) => format!("{} [{}]", test.spec, name),
+            _ => test.spec.clone(),
+        };
+        println!("    {:<40} {:<11} {}", test.path, test.kind, spec.dimmed());
+    }
+}
+
+/// Runs a single test with `cargo test`, returning whether it passed.
+pub fn run(test: &QuintTest, envs: &[(&str, String)]) -> Result<bool> {
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let mut cmd = Command::new(cargo);
+    cmd.arg("test")
+        .arg("--manifest-path")
+        .arg(&test.manifest_path)
+        .args(test.target.cargo_args())
+        .arg("--");
+    if test.matrix {
+        // Matrix tests expand into a module with one test per case.
+        cmd.arg(format!("{}::", test.path));
+    } else {
+        cmd.arg("--exact").arg(&test.path);
+    }
+    cmd.arg("--nocapture")
+        .envs(envs.iter().map(|(name, value)| (name, value)));
+
+    let status = cmd
+        .status()
+        .with_context(|| format!("Failed to run `cargo test` for `{}`", test.path))?;
+    Ok(status.success())
+}
+
+/// Replays an ITF trace against a test instead of generating new traces.
+pub fn replay(test: &QuintTest, trace: &Path, debug: bool, overrides: &Overrides) -> Result<bool> {
+    // Tests run from their package root, so the trace path must be absolute.
+    let trace = trace
+        .canonicalize()
+        .with_context(|| format!("Can't find trace file at: {}", trace.display()))?;
+
+    let var = if debug { "QUINT_DEBUG" } else { "QUINT_REPLAY" };
+    let mut envs = overrides.envs();
+    envs.push((var, trace.display().to_string()));
+    run(test, &envs)
+}
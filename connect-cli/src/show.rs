@@ -0,0 +1,89 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use itf::Value;
+use quint_connect::ValueDisplay;
+use std::{fmt::Write, fs::File, io::BufReader, path::Path};
+
+type Trace = itf::Trace<Value>;
+
+/// Prints an ITF trace with values in Quint syntax.
+pub fn show(path: &Path) -> Result<()> {
+    let file = File::open(path)
+        .with_context(|| format!("Can't open trace file at: {}", path.display()))?;
+    let trace: Trace = serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("Failed to parse JSON trace file at: {}", path.display()))?;
+    print!("{}", render(&trace));
+    Ok(())
+}
+
+fn render(trace: &Trace) -> String {
+    let mut out = String::new();
+    for (index, state) in trace.states.iter().enumerate() {
+        let header = match action_taken(&state.value) {
+            Some(action) => format!("[State {}] {}", index, action),
+            None => format!("[State {}]", index),
+        };
+        let _ = writeln!(out, "{}", header.bold());
+
+        match &state.value {
+            Value::Record(rec) => {
+                for (name, value) in rec.iter() {
+                    let _ = writeln!(out, "  {}: {}", name, value.display());
+                }
+            }
+            other => {
+                let _ = writeln!(out, "  {}", other.display());
+            }
+        }
+    }
+    out
+}
+
+fn action_taken(state: &Value) -> Option<&str> {
+    match state {
+        Value::Record(rec) => match rec.get("mbt::actionTaken") {
+            Some(Value::String(action)) if !action.is_empty() => Some(action),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        colored::control::set_override(false);
+        let trace: Trace = serde_json::from_str(
+            r##"{
+                "#meta": {},
+                "vars": ["mbt::actionTaken", "board"],
+                "states": [
+                    {
+                        "#meta": { "index": 0 },
+                        "mbt::actionTaken": "init",
+                        "board": { "#map": [[{ "#bigint": "1" }, "X"]] }
+                    },
+                    {
+                        "#meta": { "index": 1 },
+                        "mbt::actionTaken": "",
+                        "board": { "#map": [] }
+                    }
+                ]
+            }"##,
+        )
+        .unwrap();
+
+        assert_eq!(
+            render(&trace),
+            "[State 0] init\n  \
+               board: Map(1 -> \"X\")\n  \
+               mbt::actionTaken: \"init\"\n\
+             [State 1]\n  \
+               board: Map()\n  \
+               mbt::actionTaken: \"\"\n"
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{discover::QuintTest, run};
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+/// How long to wait for more changes before rerunning tests, since editors often
+/// write files in several steps.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Watches the spec files of the given tests, rerunning the tests affected by
+/// each change until interrupted.
+pub fn watch(tests: &[QuintTest], envs: &[(&str, String)]) -> Result<()> {
+    if tests.is_empty() {
+        bail!("No Quint Connect tests to watch")
+    }
+
+    let dirs: BTreeSet<_> = tests
+        .iter()
+        .filter_map(|test| spec_path(test).parent().map(Path::to_path_buf))
+        .collect();
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).context("Failed to create file watcher")?;
+    for dir in &dirs {
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("Failed to watch directory: {}", dir.display()))?;
+    }
+
+    println!(
+        "Watching {} spec directories for {} tests. Press Ctrl-C to stop.",
+        dirs.len(),
+        tests.len()
+    );
+
+    while let Ok(event) = rx.recv() {
+        let mut changed = BTreeSet::new();
+        collect_changes(event, &mut changed);
+        while let Ok(event) = rx.recv_timeout(DEBOUNCE) {
+            collect_changes(event, &mut changed);
+        }
+
+        let changed: Vec<_> = changed.into_iter().collect();
+        let affected = affected(tests, &changed);
+        if affected.is_empty() {
+            continue;
+        }
+
+        for path in &changed {
+            println!("{} {}", "Changed:".bold(), path.display());
+        }
+        for test in affected {
+            let passed = run::run(test, envs)?;
+            let status = if passed { "ok".green() } else { "FAILED".red() };
+            println!("{} {} ... {}", "Rerun:".bold(), test.path, status);
+        }
+    }
+
+    Ok(())
+}
+
+fn collect_changes(event: notify::Result<notify::Event>, changed: &mut BTreeSet<PathBuf>) {
+    let Ok(event) = event else {
+        return;
+    };
+    if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+        return;
+    }
+    for path in event.paths {
+        if path.extension().is_some_and(|ext| ext == "qnt") {
+            changed.insert(canonical(&path));
+        }
+    }
+}
+
+/// Finds the tests affected by the changed files. Tests whose spec changed are
+/// affected directly. Other changed files are assumed to be modules imported by
+/// the specs in the same directory.
+fn affected<'a>(tests: &'a [QuintTest], changed: &[PathBuf]) -> Vec<&'a QuintTest> {
+    tests
+        .iter()
+        .filter(|test| {
+            let spec = spec_path(test);
+            changed.iter().any(|path| {
+                *path == spec
+                    || (!tests.iter().any(|other| spec_path(other) == *path)
+                        && path.parent() == spec.parent())
+            })
+        })
+        .collect()
+}
+
+fn spec_path(test: &QuintTest) -> PathBuf {
+    canonical(&test.spec_path())
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discover::{Kind, Target};
+
+    fn test(path: &str, spec: &str) -> QuintTest {
+        QuintTest {
+            path: path.to_string(),
+            kind: Kind::Run,
+            spec: spec.to_string(),
+            test: None,
+            matrix: false,
+            package: "pkg".to_string(),
+            manifest_path: PathBuf::from("/pkg/Cargo.toml"),
+            target: Target::Lib,
+        }
+    }
+
+    fn names(tests: Vec<&QuintTest>) -> Vec<&str> {
+        tests.iter().map(|test| test.path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_affected() {
+        let tests = vec![
+            test("a", "spec/a.qnt"),
+            test("b", "spec/b.qnt"),
+            test("c", "other/c.qnt"),
+        ];
+
+        let changed = vec![PathBuf::from("/pkg/spec/a.qnt")];
+        assert_eq!(names(affected(&tests, &changed)), vec!["a"]);
+
+        let changed = vec![PathBuf::from("/pkg/spec/common.qnt")];
+        assert_eq!(names(affected(&tests, &changed)), vec!["a", "b"]);
+
+        let changed = vec![PathBuf::from("/pkg/unrelated/d.qnt")];
+        assert!(affected(&tests, &changed).is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+use crate::internal::state::{FieldAttrs, adapter, parse_rename};
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quint_connect_spec::case::to_camel_case;
+use quote::quote;
+use syn::{
+    Data, DeriveInput, Error, Fields, GenericArgument, PathArguments, Type, Variant, ext::IdentExt,
+    parse::Result, parse_macro_input,
+};
+
+pub(crate) fn expand(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Implements `Action` by matching the step's action against the names of the
+/// variants and decoding their fields from the nondet picks.
+fn derive(input: &DeriveInput) -> Result<TokenStream2> {
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "QuintAction can only be derived for enums",
+        ));
+    };
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "QuintAction doesn't support generic types",
+        ));
+    }
+
+    let name = &input.ident;
+    let arms = data
+        .variants
+        .iter()
+        .map(quote_variant)
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        impl quint_connect::Action for #name {
+            fn from_step(step: &quint_connect::Step) -> quint_connect::Result<Self> {
+                match step.action_taken.as_str() {
+                    #(#arms,)*
+                    _ => quint_connect::runner::unknown_action(step),
+                }
+            }
+        }
+    })
+}
+
+/// A match arm decoding the variant from the actions it's named after.
+fn quote_variant(variant: &Variant) -> Result<TokenStream2> {
+    let ident = &variant.ident;
+    let actions = match parse_rename(&variant.attrs)? {
+        Some(rename) => vec![rename.value()],
+        None => action_names(&ident.unraw().to_string()),
+    };
+
+    let value = match &variant.fields {
+        Fields::Named(named) => {
+            let mut fields = Vec::new();
+            for field in &named.named {
+                let ident = &field.ident;
+                let attrs = FieldAttrs::parse(&field.attrs)?;
+                let pick = attrs
+                    .rename
+                    .map(|rename| rename.value())
+                    .unwrap_or_else(|| {
+                        let ident = ident.as_ref().expect("named field");
+                        to_camel_case(&ident.unraw().to_string())
+                    });
+                // The adapter of an optional pick decodes its value.
+                let decode = match option_inner(&field.ty) {
+                    Some(inner) => {
+                        let adapter = quote_adapter(inner, &attrs.adapter);
+                        quote! { quint_connect::runner::optional_pick::<_, #adapter>(step, #pick)? }
+                    }
+                    None => {
+                        let adapter = quote_adapter(&field.ty, &attrs.adapter);
+                        quote! { quint_connect::runner::pick::<_, #adapter>(step, #pick)? }
+                    }
+                };
+                fields.push(quote! { #ident: #decode });
+            }
+            quote! { Self::#ident { #(#fields),* } }
+        }
+        Fields::Unit => quote! { Self::#ident },
+        Fields::Unnamed(_) => {
+            return Err(Error::new_spanned(
+                &variant.fields,
+                "QuintAction variants must have named fields, one per nondet pick",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        #(#actions)|* => ::core::result::Result::Ok(#value)
+    })
+}
+
+/// The action names a variant matches: its own name, and the name starting with
+/// a lowercase letter, e.g. `Init` for `init`.
+fn action_names(variant: &str) -> Vec<String> {
+    let mut chars = variant.chars();
+    let lower: String = chars
+        .next()
+        .into_iter()
+        .flat_map(char::to_lowercase)
+        .chain(chars)
+        .collect();
+    if lower == variant {
+        vec![lower]
+    } else {
+        vec![variant.to_string(), lower]
+    }
+}
+
+/// The `T` in an `Option<T>` field, which is decoded from an optional pick.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(inner) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+fn quote_adapter(ty: &Type, custom: &Option<Type>) -> TokenStream2 {
+    match custom {
+        Some(custom) => quote!(#custom),
+        None => adapter(ty).unwrap_or_else(|| quote!(quint_connect::runner::Same)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_names() {
+        assert_eq!(action_names("MoveX"), ["MoveX", "moveX"]);
+        assert_eq!(action_names("init"), ["init"]);
+    }
+}
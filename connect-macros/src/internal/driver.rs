@@ -0,0 +1,214 @@
+use crate::internal::{
+    ir::Actions,
+    spec::SpecFile,
+    switch::{Alternative, Case, Switch},
+    utils::{did_you_mean, parse_str},
+};
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::{
+    Error, ItemImpl, LitStr, Macro, meta::ParseNestedMeta, parse::Result, parse_macro_input,
+    visit::Visit,
+};
+
+#[derive(Default)]
+struct DriverAttrs {
+    pub spec: Option<LitStr>,
+    pub main: Option<LitStr>,
+    pub init: Option<LitStr>,
+    pub step: Option<LitStr>,
+}
+
+impl DriverAttrs {
+    fn parse(&mut self, meta: ParseNestedMeta) -> Result<()> {
+        if meta.path.is_ident("spec") {
+            self.spec = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("main") {
+            self.main = Some(parse_str(&meta)?);
+        } else if meta.path.is_ident("init") {
+            self.init = Some(parse_str(&meta)?);
+        } else if meta.path.is_ident("step") {
+            self.step = Some(parse_str(&meta)?);
+        } else {
+            return Err(meta.error("Invalid attribute"));
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn expand(args: TokenStream, item: TokenStream) -> TokenStream {
+    let mut attrs = DriverAttrs::default();
+    let parser = syn::meta::parser(|meta| attrs.parse(meta));
+    parse_macro_input!(args with parser);
+
+    let item_impl = parse_macro_input!(item as ItemImpl);
+
+    let Some(spec_lit) = attrs.spec.as_ref().filter(|spec| !spec.value().is_empty()) else {
+        return quote! {
+            compile_error!("Missing required attribute `spec`");
+            #item_impl
+        }
+        .into();
+    };
+
+    // Emit the impl block as is, even on errors, so that they don't cascade into
+    // missing trait implementations.
+    match check_switches(spec_lit, &attrs, &item_impl) {
+        Ok((spec_file, uncovered)) => {
+            let dependencies = spec_file.quote_dependencies();
+            let warnings = uncovered
+                .iter()
+                .map(|action| quote_warning(spec_lit, action));
+            quote! {
+                #item_impl
+                #dependencies
+                #(#warnings)*
+            }
+        }
+        Err(err) => {
+            let err = err.to_compile_error();
+            quote! {
+                #err
+                #item_impl
+            }
+        }
+    }
+    .into()
+}
+
+/// Collects the `switch!` invocations in the impl block.
+#[derive(Default)]
+struct Switches(Vec<Switch>);
+
+impl<'ast> Visit<'ast> for Switches {
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        let is_switch = mac
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "switch");
+        // Malformed invocations are reported by `switch!` itself.
+        if is_switch && let Ok(switch) = mac.parse_body::<Switch>() {
+            self.0.push(switch);
+        }
+    }
+}
+
+/// Checks the actions and nondet picks of every `switch!` case against the spec.
+/// Returns the actions the spec can take but no case handles, unless a `_` case
+/// handles them all.
+///
+/// Nondet picks and the actions the spec can take are read from the spec as
+/// compiled by Quint. Where Quint isn't available, only action names are checked.
+fn check_switches(
+    spec_lit: &LitStr,
+    attrs: &DriverAttrs,
+    item_impl: &ItemImpl,
+) -> Result<(SpecFile, Vec<String>)> {
+    let spec_file = SpecFile::read(spec_lit)?;
+    let main = spec_file.main_module(attrs.main.as_ref())?;
+    for action in [&attrs.init, &attrs.step].into_iter().flatten() {
+        spec_file.check_decl(main, "action", action)?;
+    }
+
+    let mut switches = Switches::default();
+    switches.visit_item_impl(item_impl);
+
+    let alternatives: Vec<&Alternative> = switches
+        .0
+        .iter()
+        .flat_map(|switch| &switch.cases)
+        .flat_map(|case| &case.alternatives)
+        .collect();
+
+    let actions = spec_file.compiled_actions(main)?;
+    let mut errors: Option<Error> = None;
+    for alternative in &alternatives {
+        let action = &alternative.action;
+        let name = action.to_string();
+        let result = spec_file
+            .check_name(main, "action", &name, action.span())
+            .and_then(|()| match &actions {
+                Some(actions) => check_picks(actions, &name, alternative),
+                None => Ok(()),
+            });
+        if let Err(err) = result {
+            match &mut errors {
+                Some(errors) => errors.combine(err),
+                None => errors = Some(err),
+            }
+        }
+    }
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    let has_catch_all = switches
+        .0
+        .iter()
+        .flat_map(|switch| &switch.cases)
+        .any(Case::matches_all);
+    let uncovered = match &actions {
+        Some(actions) if !has_catch_all => taken_actions(actions, attrs)
+            .into_iter()
+            .filter(|action| !alternatives.iter().any(|alt| alt.action == action))
+            .collect(),
+        _ => Vec::new(),
+    };
+    Ok((spec_file, uncovered))
+}
+
+fn check_picks(actions: &Actions, action: &str, alternative: &Alternative) -> Result<()> {
+    let nondets = actions.nondets(action);
+    // Fields of `..` picks depend on the struct's definition, which isn't known here.
+    for pick in alternative
+        .nondet_picks
+        .iter()
+        .filter(|pick| !pick.is_record)
+    {
+        let name = &pick.key;
+        if !nondets.contains(&name.as_str()) {
+            let hint = did_you_mean(name, nondets.iter().copied());
+            return Err(Error::new(
+                pick.name.span(),
+                format!("Nondet `{}` not found in action `{}`{}", name, action, hint),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The init action followed by the actions the step action can take.
+fn taken_actions(actions: &Actions, attrs: &DriverAttrs) -> Vec<String> {
+    let init = attrs
+        .init
+        .as_ref()
+        .map_or("init".to_string(), LitStr::value);
+    let step = attrs
+        .step
+        .as_ref()
+        .map_or("step".to_string(), LitStr::value);
+
+    let mut taken = actions.taken(&init);
+    for action in actions.taken(&step) {
+        if !taken.contains(&action) {
+            taken.push(action);
+        }
+    }
+    taken
+}
+
+/// A use of a deprecated constant, so that the compiler warns about the missing
+/// case at the spec attribute.
+fn quote_warning(spec_lit: &LitStr, action: &str) -> TokenStream2 {
+    let note = format!("Action `{}` has no case in `switch!`", action);
+    let usage = quote_spanned!(spec_lit.span()=> MISSING_CASE);
+    quote! {
+        const _: () = {
+            #[deprecated(note = #note)]
+            const MISSING_CASE: () = ();
+            #usage
+        };
+    }
+}
@@ -0,0 +1,209 @@
+//! The actions of a spec as compiled by Quint, read from the JSON intermediate
+//! representation printed by `quint compile`.
+
+use serde_json::Value;
+
+/// The action definitions of a module, after Quint flattened its imports,
+/// exports and instances.
+pub(crate) struct Actions(Vec<(String, Value)>);
+
+impl Actions {
+    /// Reads the actions of the given module from Quint's output.
+    pub fn from_ir(ir: &Value, name: &str) -> Option<Self> {
+        let module = ir["modules"]
+            .as_array()?
+            .iter()
+            .find(|module| module["name"] == name)?;
+        let actions = module["declarations"]
+            .as_array()?
+            .iter()
+            .filter(|decl| decl["kind"] == "def" && decl["qualifier"] == "action")
+            .filter_map(|decl| Some((decl["name"].as_str()?.to_string(), decl["expr"].clone())))
+            .collect();
+        Some(Self(actions))
+    }
+
+    /// Names picked with `nondet` when taking the given action, including in the
+    /// actions it calls.
+    pub fn nondets<'a>(&'a self, action: &'a str) -> Vec<&'a str> {
+        let mut nondets = Vec::new();
+        let mut visited = vec![action];
+        let mut i = 0;
+        while i < visited.len() {
+            if let Some(expr) = self.expr(visited[i]) {
+                walk(expr, &mut |node| {
+                    if node["kind"] == "let"
+                        && node["opdef"]["qualifier"] == "nondet"
+                        && let Some(name) = node["opdef"]["name"].as_str()
+                    {
+                        nondets.push(name);
+                    }
+                    if let Some(called) = self.called(node)
+                        && !visited.contains(&called)
+                    {
+                        visited.push(called);
+                    }
+                });
+            }
+            i += 1;
+        }
+        nondets.sort();
+        nondets.dedup();
+        nondets
+    }
+
+    /// Actions that can be recorded as taken by the given step action: the
+    /// actions called as branches of its `any` blocks, transitively, or the
+    /// action itself.
+    pub fn taken(&self, step: &str) -> Vec<String> {
+        let mut taken = Vec::new();
+        let mut pending: Vec<&str> = self.called_name(step).into_iter().collect();
+        let mut visited = pending.clone();
+        while let Some(action) = pending.pop() {
+            let mut branches = Vec::new();
+            if let Some(expr) = self.expr(action) {
+                walk(expr, &mut |node| {
+                    if node["kind"] == "app" && node["opcode"] == "actionAny" {
+                        let args = node["args"].as_array().into_iter().flatten();
+                        branches.extend(args.filter_map(|arg| self.called(arg)));
+                    }
+                });
+            }
+            if branches.is_empty() {
+                taken.push(action.to_string());
+            }
+            for branch in branches.into_iter().rev() {
+                if !visited.contains(&branch) {
+                    visited.push(branch);
+                    pending.push(branch);
+                }
+            }
+        }
+        taken
+    }
+
+    fn expr(&self, action: &str) -> Option<&Value> {
+        self.0
+            .iter()
+            .find(|(name, _)| name == action)
+            .map(|(_, expr)| expr)
+    }
+
+    /// The action referred to by an expression, either by name or by applying it.
+    fn called(&self, node: &Value) -> Option<&str> {
+        let name = match node["kind"].as_str()? {
+            "name" => node["name"].as_str()?,
+            "app" => node["opcode"].as_str()?,
+            _ => return None,
+        };
+        self.called_name(name)
+    }
+
+    fn called_name(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(action, _)| action == name)
+            .map(|(action, _)| action.as_str())
+    }
+}
+
+/// Calls `f` on every object in the expression tree.
+fn walk<'a>(value: &'a Value, f: &mut impl FnMut(&'a Value)) {
+    match value {
+        Value::Object(fields) => {
+            f(value);
+            fields.values().for_each(|field| walk(field, f));
+        }
+        Value::Array(items) => items.iter().for_each(|item| walk(item, f)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn name(name: &str) -> Value {
+        json!({ "kind": "name", "name": name })
+    }
+
+    fn app(opcode: &str, args: Vec<Value>) -> Value {
+        json!({ "kind": "app", "opcode": opcode, "args": args })
+    }
+
+    fn nondet(name: &str, body: Value) -> Value {
+        json!({
+            "kind": "let",
+            "opdef": { "kind": "def", "name": name, "qualifier": "nondet", "expr": app("oneOf", vec![]) },
+            "expr": body,
+        })
+    }
+
+    fn action(name: &str, expr: Value) -> Value {
+        json!({ "kind": "def", "name": name, "qualifier": "action", "expr": expr })
+    }
+
+    /// The output of `quint compile` for:
+    ///
+    /// ```quint
+    /// module game {
+    ///   action Move(c) = board' = board + c
+    ///   action StartInCorner = { nondet corner = ...; Move(corner) }
+    ///   action MoveAnywhere = { nondet coordinate = ...; Move(coordinate) }
+    ///   action MoveX = if (board == 0) StartInCorner else MoveAnywhere
+    ///   action MoveO = MoveAnywhere
+    ///   action Turn = any { MoveX, MoveO }
+    ///   action step = any { Turn, all { board == 9, board' = board }, stuttered }
+    ///   action stuttered = board' = board
+    /// }
+    /// ```
+    fn game() -> Actions {
+        let assign = app("assign", vec![name("board"), name("board")]);
+        let ir = json!({
+            "modules": [{
+                "name": "game",
+                "declarations": [
+                    { "kind": "var", "name": "board" },
+                    action("Move", json!({ "kind": "lambda", "params": [{ "name": "c" }], "expr": assign })),
+                    action("StartInCorner", nondet("corner", app("Move", vec![name("corner")]))),
+                    action("MoveAnywhere", nondet("coordinate", app("Move", vec![name("coordinate")]))),
+                    action("MoveX", app("ite", vec![name("isEmpty"), name("StartInCorner"), name("MoveAnywhere")])),
+                    action("MoveO", name("MoveAnywhere")),
+                    action("Turn", app("actionAny", vec![name("MoveX"), name("MoveO")])),
+                    action("step", app("actionAny", vec![
+                        name("Turn"),
+                        app("actionAll", vec![assign.clone()]),
+                        name("stuttered"),
+                    ])),
+                    action("stuttered", assign),
+                    { "kind": "def", "name": "isEmpty", "qualifier": "val", "expr": name("board") },
+                ],
+            }],
+        });
+        Actions::from_ir(&ir, "game").unwrap()
+    }
+
+    #[test]
+    fn test_nondets() {
+        let actions = game();
+        assert_eq!(actions.nondets("MoveX"), ["coordinate", "corner"]);
+        assert_eq!(actions.nondets("MoveO"), ["coordinate"]);
+        assert!(actions.nondets("stuttered").is_empty());
+        assert!(actions.nondets("missing").is_empty());
+    }
+
+    #[test]
+    fn test_taken() {
+        let actions = game();
+        assert_eq!(actions.taken("step"), ["MoveX", "MoveO", "stuttered"]);
+        assert_eq!(actions.taken("MoveO"), ["MoveO"]);
+        assert!(actions.taken("missing").is_empty());
+    }
+
+    #[test]
+    fn test_missing_module() {
+        assert!(Actions::from_ir(&json!({ "modules": [] }), "game").is_none());
+        assert!(Actions::from_ir(&json!({}), "game").is_none());
+    }
+}
@@ -0,0 +1,154 @@
+use crate::internal::utils::parse_const;
+use proc_macro2::{Span, TokenStream};
+use syn::{
+    Error, Ident, LitInt, LitStr, Token, bracketed,
+    meta::ParseNestedMeta,
+    parenthesized,
+    parse::{Parser, Result},
+};
+
+/// The kind of values an attribute takes.
+#[derive(Clone, Copy)]
+pub(crate) enum Kind {
+    Str,
+    Num,
+    Consts,
+}
+
+#[derive(Clone)]
+pub(crate) enum Value {
+    Str(LitStr),
+    Num(usize),
+    Consts(Vec<(String, String)>),
+}
+
+/// An attribute of the matrix along with the values it takes.
+#[derive(Clone)]
+pub(crate) struct Axis {
+    pub key: Ident,
+    pub values: Vec<Value>,
+}
+
+/// A combination of one value per axis.
+pub(crate) type Case<'a> = Vec<(&'a Ident, &'a Value)>;
+
+/// Parses `matrix(key = [value, ...], ...)`, where keys must be one of the given
+/// attributes. Constants are given as `consts = [(N = 1), (N = 2, M = 3)]`.
+pub(crate) fn parse_matrix(meta: &ParseNestedMeta, keys: &[(&str, Kind)]) -> Result<Vec<Axis>> {
+    let mut axes: Vec<Axis> = Vec::new();
+    meta.parse_nested_meta(|meta| {
+        let Some(key) = meta.path.get_ident().cloned() else {
+            return Err(meta.error("Invalid matrix attribute"));
+        };
+        let Some((_, kind)) = keys.iter().find(|(name, _)| key == name) else {
+            return Err(meta.error("Invalid matrix attribute"));
+        };
+        if axes.iter().any(|axis| axis.key == key) {
+            return Err(meta.error(format!("Duplicated matrix attribute `{}`", key)));
+        }
+
+        let input = meta.value()?;
+        let content;
+        bracketed!(content in input);
+
+        let mut values = Vec::new();
+        while !content.is_empty() {
+            values.push(match kind {
+                Kind::Str => Value::Str(content.parse()?),
+                Kind::Num => Value::Num(content.parse::<LitInt>()?.base10_parse()?),
+                Kind::Consts => {
+                    let consts;
+                    parenthesized!(consts in content);
+                    let consts: TokenStream = consts.parse()?;
+                    let mut parsed = Vec::new();
+                    syn::meta::parser(|meta| parse_const(meta, &mut parsed)).parse2(consts)?;
+                    Value::Consts(parsed)
+                }
+            });
+            if !content.is_empty() {
+                content.parse::<Token![,]>()?;
+            }
+        }
+
+        if values.is_empty() {
+            return Err(meta.error(format!("Expected at least one value for `{}`", key)));
+        }
+        axes.push(Axis { key, values });
+        Ok(())
+    })?;
+    Ok(axes)
+}
+
+/// All combinations of values in the matrix, in declaration order, along with
+/// their test names. Fails if two combinations end up with the same name.
+pub(crate) fn cases(axes: &[Axis]) -> Result<Vec<(Ident, Case<'_>)>> {
+    let mut named: Vec<(Ident, Case)> = Vec::new();
+    for case in combinations(axes) {
+        let ident = case_ident(axes, &case);
+        if named.iter().any(|(other, _)| *other == ident) {
+            return Err(Error::new(
+                Span::call_site(),
+                format!("Duplicated matrix case `{}`", ident),
+            ));
+        }
+        named.push((ident, case));
+    }
+    Ok(named)
+}
+
+fn combinations(axes: &[Axis]) -> Vec<Case<'_>> {
+    let mut cases = vec![Vec::new()];
+    for axis in axes {
+        cases = cases
+            .into_iter()
+            .flat_map(|case| {
+                axis.values.iter().map(move |value| {
+                    let mut case = case.clone();
+                    case.push((&axis.key, value));
+                    case
+                })
+            })
+            .collect();
+    }
+    cases
+}
+
+/// A test name describing the case, e.g. `max_steps_5_main_small`. Constants are
+/// named after their position in the matrix, e.g. `consts_0`.
+fn case_ident(axes: &[Axis], case: &Case) -> Ident {
+    let parts: Vec<_> = case
+        .iter()
+        .zip(axes)
+        .map(|((key, value), axis)| match value {
+            Value::Str(value) => format!("{}_{}", key, sanitize(&value.value())),
+            Value::Num(value) => format!("{}_{}", key, value),
+            Value::Consts(_) => {
+                let index = axis
+                    .values
+                    .iter()
+                    .position(|other| std::ptr::eq(other, *value))
+                    .unwrap_or_default();
+                format!("{}_{}", key, index)
+            }
+        })
+        .collect();
+    Ident::new(&parts.join("_"), Span::call_site())
+}
+
+fn sanitize(value: &str) -> String {
+    let name: String = value
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.is_empty() {
+        "_".to_string()
+    } else {
+        name
+    }
+}
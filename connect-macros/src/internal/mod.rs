@@ -1,5 +1,12 @@
+mod ir;
+mod matrix;
+mod spec;
 mod utils;
 
+pub mod action;
+pub mod driver;
 pub mod run;
+pub mod state;
 pub mod switch;
 pub mod test;
+pub mod tests;
@@ -0,0 +1,221 @@
+//! Specs given to macros, inspected at compile time without requiring Quint to be
+//! installed where the tests are built.
+//!
+//! Names are read with the scanner shared with `cargo quint-connect`, so that both
+//! agree on the tests a spec expands into. Where Quint is installed,
+//! [`SpecFile::compiled_actions`] asks it about the bodies of actions.
+
+use crate::internal::{ir::Actions, utils::did_you_mean};
+use proc_macro2::{Span, TokenStream};
+use quint_connect_spec::{Module, SpecSources, config};
+use quote::quote;
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use syn::{Error, LitStr, parse::Result};
+
+/// A spec file given to a macro, read at compile time along with the files it
+/// imports.
+pub(crate) struct SpecFile {
+    pub lit: LitStr,
+    pub sources: SpecSources,
+}
+
+impl SpecFile {
+    /// Reads the spec at the given path, relative to the crate being compiled.
+    pub fn read(lit: &LitStr) -> Result<Self> {
+        let spec = lit.value();
+        let path = resolve(&spec, lit.span());
+        if !path.exists() {
+            return Err(Error::new(
+                lit.span(),
+                format!("Can't find spec file at: {}", spec),
+            ));
+        }
+        let sources = SpecSources::read(&path).map_err(|err| {
+            Error::new(
+                lit.span(),
+                format!("Failed to read spec file at: {}: {}", spec, err),
+            )
+        })?;
+        Ok(Self {
+            lit: lit.clone(),
+            sources,
+        })
+    }
+
+    /// The absolute path of the spec, as passed to Quint at runtime.
+    pub fn path_str(&self) -> String {
+        self.sources.path.to_string_lossy().to_string()
+    }
+
+    /// Finds the main module, which Quint defaults to the one named after the
+    /// spec file.
+    pub fn main_module(&self, main: Option<&LitStr>) -> Result<&Module> {
+        let name = self.sources.main_name(main.map(LitStr::value).as_deref());
+        self.sources.module(&name).ok_or_else(|| {
+            let span = main.map_or(self.lit.span(), LitStr::span);
+            let names = self
+                .sources
+                .spec
+                .modules
+                .iter()
+                .map(|module| module.name.as_str());
+            let hint = did_you_mean(&name, names);
+            Error::new(span, format!("Module `{}` not found in spec{}", name, hint))
+        })
+    }
+
+    /// Checks that the attribute names a declaration of the given kind, either in
+    /// the main module or in a module it imports.
+    pub fn check_decl(&self, main: &Module, kind: &str, lit: &LitStr) -> Result<()> {
+        self.check_name(main, kind, &lit.value(), lit.span())
+    }
+
+    pub fn check_name(&self, main: &Module, kind: &str, name: &str, span: Span) -> Result<()> {
+        let names = self.sources.visible_names(main, kind);
+        if names.iter().any(|visible| visible == name) {
+            return Ok(());
+        }
+        let hint = did_you_mean(name, names.iter().map(String::as_str));
+        Err(Error::new(
+            span,
+            format!(
+                "{} `{}` not found in module `{}`{}",
+                capitalize(kind),
+                name,
+                main.name,
+                hint
+            ),
+        ))
+    }
+
+    /// The actions of the main module as compiled by Quint, or `None` if Quint
+    /// can't be run or fails to compile the spec.
+    pub fn compiled_actions(&self, main: &Module) -> Result<Option<Actions>> {
+        let ir = self.compile(main)?;
+        Ok(ir.and_then(|ir| Actions::from_ir(&ir, &main.name)))
+    }
+
+    /// Runs `quint compile`, returning its JSON output.
+    ///
+    /// Quint is found as it is for type generation and at runtime: from
+    /// `QUINT_BIN`, then `quint-connect.toml`, looked up from the crate's root.
+    fn compile(&self, main: &Module) -> Result<Option<serde_json::Value>> {
+        let dir = env::var_os("CARGO_MANIFEST_DIR").map_or(PathBuf::from("."), PathBuf::from);
+        let quint = config::quint_bin(&dir).map_err(|err| Error::new(self.lit.span(), err))?;
+        let output = Command::new(quint)
+            .args(["compile", "--target", "json", "--main", &main.name])
+            .arg(&self.sources.path)
+            .output()
+            .ok()
+            .filter(|output| output.status.success());
+        Ok(output.and_then(|output| serde_json::from_slice(&output.stdout).ok()))
+    }
+
+    /// Makes the crate depend on the spec and its imports, so that it gets
+    /// rebuilt when they change.
+    pub fn quote_dependencies(&self) -> TokenStream {
+        let deps = self
+            .sources
+            .dependencies()
+            .map(|path| path.to_string_lossy().to_string());
+        quote! {
+            #(const _: &[u8] = include_bytes!(#deps);)*
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Resolves a spec path against the root of the crate being compiled or, if no
+/// spec is found there, against the directory of the file invoking the macro, as
+/// `include_str!` does.
+fn resolve(spec: &str, span: Span) -> PathBuf {
+    let path = match env::var_os("CARGO_MANIFEST_DIR") {
+        Some(dir) => Path::new(&dir).join(spec),
+        None => PathBuf::from(spec),
+    };
+    if path.exists() || !proc_macro::is_available() {
+        return path;
+    }
+    span.unwrap()
+        .local_file()
+        .and_then(|file| Some(file.parent()?.join(spec)))
+        .filter(|path| path.exists())
+        .unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r#"
+        module counter {
+          action init = n' = 0
+          action step = n' = n + 1
+          run incTest = init.then(step)
+        }
+
+        module other {
+          run otherTest = true
+        }
+    "#;
+
+    fn lit(value: &str) -> LitStr {
+        LitStr::new(value, proc_macro2::Span::call_site())
+    }
+
+    fn counter() -> SpecFile {
+        SpecFile {
+            lit: lit("specs/counter.qnt"),
+            sources: SpecSources::parse("/crate/specs/counter.qnt", SPEC),
+        }
+    }
+
+    #[test]
+    fn test_main_module() {
+        let file = counter();
+        assert_eq!(file.main_module(None).unwrap().name, "counter");
+        assert_eq!(file.main_module(Some(&lit("other"))).unwrap().name, "other");
+        assert_eq!(
+            file.main_module(Some(&lit("missing")))
+                .unwrap_err()
+                .to_string(),
+            "Module `missing` not found in spec"
+        );
+        assert_eq!(
+            file.main_module(Some(&lit("counte")))
+                .unwrap_err()
+                .to_string(),
+            "Module `counte` not found in spec, did you mean `counter`?"
+        );
+    }
+
+    #[test]
+    fn test_check_decl() {
+        let file = counter();
+        let main = file.main_module(None).unwrap();
+        assert!(file.check_decl(main, "action", &lit("step")).is_ok());
+        assert_eq!(
+            file.check_decl(main, "action", &lit("stpe"))
+                .unwrap_err()
+                .to_string(),
+            "Action `stpe` not found in module `counter`, did you mean `step`?"
+        );
+        assert_eq!(
+            file.check_decl(main, "run", &lit("otherTest"))
+                .unwrap_err()
+                .to_string(),
+            "Run `otherTest` not found in module `counter`"
+        );
+    }
+}
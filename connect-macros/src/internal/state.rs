@@ -0,0 +1,381 @@
+use crate::internal::utils::parse_str;
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quint_connect_spec::case::to_camel_case;
+use quote::{format_ident, quote};
+use syn::{
+    Attribute, Data, DeriveInput, Error, Expr, Fields, GenericArgument, Ident, LitStr,
+    PathArguments, Type, ext::IdentExt, meta::ParseNestedMeta, parse::Result, parse_macro_input,
+    parse_quote,
+};
+
+/// Containers whose elements are decoded with the adapters of their type
+/// arguments, e.g. the options in a `Vec<Option<T>>`.
+const CONTAINERS: &[&str] = &[
+    "Vec", "VecDeque", "BTreeSet", "HashSet", "BTreeMap", "HashMap", "Box",
+];
+
+#[derive(Default)]
+struct ContainerAttrs {
+    pub driver: Option<Type>,
+    pub from_driver: Option<Expr>,
+}
+
+impl ContainerAttrs {
+    fn parse(&mut self, meta: ParseNestedMeta) -> Result<()> {
+        if meta.path.is_ident("driver") {
+            self.driver = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("from_driver") {
+            self.from_driver = Some(meta.value()?.parse()?);
+        } else {
+            return Err(meta.error("Invalid attribute"));
+        }
+        Ok(())
+    }
+}
+
+/// The attributes of a field.
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    pub rename: Option<LitStr>,
+    /// The `serde_with` adapter decoding the field, instead of the computed one.
+    pub adapter: Option<Type>,
+}
+
+impl FieldAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut parsed = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("quint")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    parsed.rename = Some(parse_str(&meta)?);
+                } else if meta.path.is_ident("as") {
+                    parsed.adapter = Some(parse_str(&meta)?.parse()?);
+                } else {
+                    return Err(meta.error("Invalid attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(parsed)
+    }
+}
+
+/// Reads the `rename` attribute of a variant.
+pub(crate) fn parse_rename(attrs: &[Attribute]) -> Result<Option<LitStr>> {
+    let mut rename = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("quint")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                rename = Some(parse_str(&meta)?);
+                Ok(())
+            } else {
+                Err(meta.error("Invalid attribute"))
+            }
+        })?;
+    }
+    Ok(rename)
+}
+
+pub(crate) fn expand(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derives `Deserialize` through a private copy of the type annotated with the
+/// serde attributes for Quint's encoding, converting it back field by field.
+fn derive(input: &DeriveInput) -> Result<TokenStream2> {
+    if let Some(lifetime) = input.generics.lifetimes().next() {
+        return Err(Error::new_spanned(
+            lifetime,
+            "QuintState doesn't support borrowed types",
+        ));
+    }
+
+    let mut attrs = ContainerAttrs::default();
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("quint"))
+    {
+        attr.parse_nested_meta(|meta| attrs.parse(meta))?;
+    }
+
+    let name = &input.ident;
+    let repr = Ident::new("QuintRepr", name.span());
+    let generics = &input.generics;
+    let where_clause = &generics.where_clause;
+    let (definition, conversion) = match &input.data {
+        Data::Struct(data) => {
+            let fields = quote_fields(&data.fields)?;
+            let (pattern, value) = quote_conversion(&data.fields, quote!(#repr), quote!(#name));
+            let definition = match data.fields {
+                Fields::Named(_) => quote! { struct #repr #generics #where_clause #fields },
+                _ => quote! { struct #repr #generics #fields #where_clause; },
+            };
+            (definition, quote! { let #pattern = repr; #value })
+        }
+        Data::Enum(data) => {
+            let mut variants = Vec::new();
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                let ident = &variant.ident;
+                let rename = parse_rename(&variant.attrs)?.map(|rename| {
+                    quote! { #[serde(rename = #rename)] }
+                });
+                let fields = quote_fields(&variant.fields)?;
+                variants.push(quote! { #rename #ident #fields });
+
+                let (pattern, value) = quote_conversion(
+                    &variant.fields,
+                    quote!(#repr::#ident),
+                    quote!(#name::#ident),
+                );
+                arms.push(quote! { #pattern => #value });
+            }
+            (
+                quote! {
+                    #[serde(tag = "tag", content = "value")]
+                    enum #repr #generics #where_clause { #(#variants),* }
+                },
+                quote! { match repr { #(#arms),* } },
+            )
+        }
+        Data::Union(data) => {
+            return Err(Error::new_spanned(
+                data.union_token,
+                "QuintState doesn't support unions",
+            ));
+        }
+    };
+
+    let state = quote_state(input, attrs)?;
+
+    // Type parameters must be deserializable, which serde doesn't infer for fields
+    // decoded with adapters.
+    let bound = generics
+        .type_params()
+        .map(|param| {
+            format!(
+                "{}: quint_connect::runner::serde::Deserialize<'de>",
+                param.ident
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut impl_generics = generics.clone();
+    impl_generics.params.insert(0, parse_quote!('de));
+    let (impl_generics, _, _) = impl_generics.split_for_impl();
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let mut where_clause = generics.clone().make_where_clause().clone();
+    where_clause.predicates.push(parse_quote! {
+        #repr #ty_generics: quint_connect::runner::serde::Deserialize<'de>
+    });
+
+    Ok(quote! {
+        const _: () = {
+            #[derive(quint_connect::runner::serde::Deserialize)]
+            #[serde(crate = "quint_connect::runner::serde", bound = #bound)]
+            #definition
+
+            impl #impl_generics quint_connect::runner::serde::Deserialize<'de>
+                for #name #ty_generics #where_clause
+            {
+                fn deserialize<D>(deserializer: D) -> ::core::result::Result<Self, D::Error>
+                where
+                    D: quint_connect::runner::serde::Deserializer<'de>,
+                {
+                    let repr = <#repr #ty_generics as quint_connect::runner::serde::Deserialize>::deserialize(
+                        deserializer,
+                    )?;
+                    ::core::result::Result::Ok({ #conversion })
+                }
+            }
+        };
+        #state
+    })
+}
+
+/// The fields of the private copy, renamed to camelCase and decoded with their
+/// adapters, either given with `as` or computed from their type.
+fn quote_fields(fields: &Fields) -> Result<TokenStream2> {
+    let mut quoted = Vec::new();
+    for field in fields {
+        let ty = &field.ty;
+        let FieldAttrs {
+            rename,
+            adapter: custom,
+        } = FieldAttrs::parse(&field.attrs)?;
+        let adapter = match custom {
+            Some(custom) => Some(quote!(#custom)),
+            None => adapter(ty),
+        };
+        let with = adapter.map(|adapter| {
+            let with = format!("quint_connect::runner::As::<{}>", adapter);
+            quote! { #[serde(with = #with)] }
+        });
+        quoted.push(match &field.ident {
+            Some(ident) => {
+                let rename = rename
+                    .map(|rename| rename.value())
+                    .unwrap_or_else(|| to_camel_case(&ident.unraw().to_string()));
+                quote! { #with #[serde(rename = #rename)] #ident: #ty }
+            }
+            None => {
+                if let Some(rename) = rename {
+                    return Err(Error::new_spanned(
+                        rename,
+                        "Unnamed fields can't be renamed",
+                    ));
+                }
+                quote! { #with #ty }
+            }
+        });
+    }
+    Ok(match fields {
+        Fields::Named(_) => quote! { { #(#quoted),* } },
+        Fields::Unnamed(_) => quote! { ( #(#quoted),* ) },
+        Fields::Unit => quote! {},
+    })
+}
+
+/// A pattern destructuring the private copy and the expression rebuilding the
+/// type from its bindings.
+fn quote_conversion(
+    fields: &Fields,
+    from: TokenStream2,
+    to: TokenStream2,
+) -> (TokenStream2, TokenStream2) {
+    match fields {
+        Fields::Named(named) => {
+            let idents: Vec<_> = named.named.iter().map(|field| &field.ident).collect();
+            (quote!(#from { #(#idents),* }), quote!(#to { #(#idents),* }))
+        }
+        Fields::Unnamed(unnamed) => {
+            let idents: Vec<_> = (0..unnamed.unnamed.len())
+                .map(|i| format_ident!("field{}", i))
+                .collect();
+            (quote!(#from(#(#idents),*)), quote!(#to(#(#idents),*)))
+        }
+        Fields::Unit => (from, to),
+    }
+}
+
+/// The `serde_with` adapter decoding Quint options anywhere in the type, or
+/// `None` if the type decodes as is.
+pub(crate) fn adapter(ty: &Type) -> Option<TokenStream2> {
+    let same = || quote!(quint_connect::runner::Same);
+    match ty {
+        Type::Paren(paren) => adapter(&paren.elem),
+        Type::Group(group) => adapter(&group.elem),
+        Type::Array(array) => {
+            let len = &array.len;
+            adapter(&array.elem).map(|elem| quote!([#elem; #len]))
+        }
+        Type::Tuple(tuple) => {
+            let elems: Vec<_> = tuple.elems.iter().map(adapter).collect();
+            elems.iter().any(Option::is_some).then(|| {
+                let elems = elems.into_iter().map(|elem| elem.unwrap_or_else(same));
+                quote!((#(#elems,)*))
+            })
+        }
+        Type::Path(path) if path.qself.is_none() => {
+            let segment = path.path.segments.last()?;
+            let PathArguments::AngleBracketed(args) = &segment.arguments else {
+                return None;
+            };
+            if segment.ident == "Option" && args.args.len() == 1 {
+                let GenericArgument::Type(inner) = &args.args[0] else {
+                    return None;
+                };
+                let inner = adapter(inner).unwrap_or_else(same);
+                return Some(quote!(quint_connect::runner::QuintOption<#inner>));
+            }
+            if !CONTAINERS
+                .iter()
+                .any(|container| segment.ident == container)
+            {
+                return None;
+            }
+
+            let mut path = path.path.clone();
+            let mut adapted = false;
+            let segment = path.segments.last_mut()?;
+            if let PathArguments::AngleBracketed(args) = &mut segment.arguments {
+                for arg in &mut args.args {
+                    if let GenericArgument::Type(ty) = arg {
+                        let elem = adapter(ty);
+                        adapted |= elem.is_some();
+                        let elem = elem.unwrap_or_else(same);
+                        *ty = parse_quote!(#elem);
+                    }
+                }
+            }
+            adapted.then(|| quote!(#path))
+        }
+        _ => None,
+    }
+}
+
+/// Implements `State` for the driver with the `from_driver` projection, if any.
+fn quote_state(input: &DeriveInput, attrs: ContainerAttrs) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    match (attrs.driver, attrs.from_driver) {
+        (Some(driver), Some(from_driver)) => Ok(quote! {
+            impl #impl_generics quint_connect::State<#driver> for #name #ty_generics #where_clause {
+                fn from_driver(driver: &#driver) -> quint_connect::Result<Self> {
+                    // Annotated so that closure parameters don't need types.
+                    let from_driver: fn(&#driver) -> quint_connect::Result<Self> = #from_driver;
+                    from_driver(driver)
+                }
+            }
+        }),
+        (None, None) => Ok(quote! {}),
+        (Some(driver), None) => Err(Error::new_spanned(
+            driver,
+            "Attribute `driver` requires `from_driver`",
+        )),
+        (None, Some(from_driver)) => Err(Error::new_spanned(
+            from_driver,
+            "Attribute `from_driver` requires `driver`",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adapt(ty: &str) -> Option<String> {
+        adapter(&syn::parse_str(ty).unwrap()).map(|adapter| adapter.to_string())
+    }
+
+    #[test]
+    fn test_adapters() {
+        assert_eq!(adapt("u64"), None);
+        assert_eq!(adapt("Vec<String>"), None);
+        assert_eq!(adapt("BTreeMap<usize, Square>"), None);
+        assert_eq!(
+            adapt("Option<u64>").as_deref(),
+            Some("quint_connect :: runner :: QuintOption < quint_connect :: runner :: Same >")
+        );
+        assert_eq!(
+            adapt("std::collections::BTreeMap<String, Option<u64>>").as_deref(),
+            Some(
+                "std :: collections :: BTreeMap < quint_connect :: runner :: Same , \
+                 quint_connect :: runner :: QuintOption < quint_connect :: runner :: Same > >"
+            )
+        );
+        assert_eq!(
+            adapt("(u64, Option<bool>)").as_deref(),
+            Some(
+                "(quint_connect :: runner :: Same , \
+                 quint_connect :: runner :: QuintOption < quint_connect :: runner :: Same > ,)"
+            )
+        );
+    }
+}
@@ -1,16 +1,40 @@
+use crate::internal::utils::to_ident;
 use proc_macro::TokenStream;
-use quote::quote;
+use proc_macro2::Span;
+use quint_connect_spec::case::{to_camel_case, to_snake_case};
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
 use syn::{
-    Expr, Ident, Token, Type, braced, parenthesized,
+    Error, Expr, Ident, Lifetime, Pat, Token, Type, braced, parenthesized,
     parse::{Parse, ParseBuffer, ParseStream},
     parse_macro_input,
     punctuated::Punctuated,
     token::Paren,
 };
 
-struct Switch {
+pub(crate) struct Switch {
     step: Ident,
-    cases: Vec<Case>,
+    pub cases: Vec<Case>,
+}
+
+/// Parses the `rename = snake_case` option, the only naming convention supported.
+fn parse_rename(input: ParseStream) -> syn::Result<()> {
+    let key: Ident = input.parse()?;
+    if key != "rename" {
+        return Err(Error::new(key.span(), "Invalid switch! option"));
+    }
+    input.parse::<Token![=]>()?;
+    let convention: Ident = input.parse()?;
+    if convention != "snake_case" {
+        return Err(Error::new(
+            convention.span(),
+            format!(
+                "Unsupported naming convention `{}`, expected `snake_case`",
+                convention
+            ),
+        ));
+    }
+    Ok(())
 }
 
 impl Parse for Switch {
@@ -18,6 +42,13 @@ impl Parse for Switch {
         let step: Ident = input.parse()?;
         let mut cases: Vec<Case> = Vec::new();
 
+        let mut snake_case = false;
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+            parse_rename(input)?;
+            snake_case = true;
+        }
+
         let actions;
         braced!(actions in input);
 
@@ -35,10 +66,23 @@ impl Parse for Switch {
             }
         }
 
-        if cases.iter().filter(|case| case.action.is_none()).count() > 1 {
+        if cases.iter().filter(|case| case.matches_all()).count() > 1 {
             return Err(input.error("switch! has more than one _ case"));
         }
 
+        // Cases are written with Quint's action names and Rust's binding names,
+        // so implicit handlers call snake_case methods and picks are looked up by
+        // their camelCase names.
+        if snake_case {
+            for alternative in cases.iter_mut().flat_map(|case| &mut case.alternatives) {
+                let method = to_snake_case(&alternative.action.to_string());
+                alternative.method = to_ident(&method, alternative.action.span());
+                for pick in &mut alternative.nondet_picks {
+                    pick.key = to_camel_case(&pick.name.to_string());
+                }
+            }
+        }
+
         Ok(Self { step, cases })
     }
 }
@@ -46,8 +90,27 @@ impl Parse for Switch {
 impl Switch {
     fn expand(self) -> proc_macro2::TokenStream {
         let Self { step, cases } = self;
-        let cases: Vec<_> = cases.into_iter().map(Case::expand).collect();
 
+        // Labels and bookkeeping variables are hygienic so that they don't clash
+        // with the ones in handlers.
+        let switch = Lifetime::new("'switch", Span::mixed_site());
+        let matched = Ident::new("matched", Span::mixed_site());
+        let missing = Ident::new("missing", Span::mixed_site());
+        let labels = Labels {
+            switch: &switch,
+            matched: &matched,
+            missing: &missing,
+        };
+
+        let mut alternatives = Vec::new();
+        for case in &cases {
+            case.expand(&labels, &mut alternatives);
+        }
+
+        // Cases are tried in order, each alternative in its own labeled block that
+        // is exited as soon as the step doesn't match it. The first matching
+        // alternative breaks out of the switch with its handler's result.
+        //
         // Note that we allow unreachable code so that users can short-circuit
         // on action handlers without getting distracted by this warning.
         // Moreover, we reintroduce a binding named "step" so that it can be
@@ -58,117 +121,274 @@ impl Switch {
         //
         // Expands to:
         //     let step = step; // <- make sure we have a binding named "step"
-        //     match step.action_taken.as_str() {
-        //         _ => {
-        //             todo!();
-        //             Ok(()) // <- unreachable code warning
+        //     'switch: {
+        //         'alternative: {
+        //             break 'switch {
+        //                 todo!();
+        //                 Ok(()) // <- unreachable code warning
+        //             };
         //         }
+        //         ...
         //     }
         quote! {
-            #[allow(unreachable_code)]
+            #[allow(unreachable_code, unused_labels, unused_mut, unused_assignments)]
             {
                 let step = #step;
-                match step.action_taken.as_str() {
-                    #(#cases),*
-                    action => anyhow::bail!("Unimplemented action `{}`", action)
+                let mut #matched = false;
+                let mut #missing: Option<&str> = None;
+                #switch: {
+                    #(#alternatives)*
+                    let action = step.action_taken.as_str();
+                    if let Some(pick) = #missing {
+                        if step.nondet_picks.contains(pick) {
+                            anyhow::bail!("Nondet pick `{}` is `None` in action `{}`", pick, action)
+                        } else {
+                            anyhow::bail!("Unknown nondet pick `{}`", pick)
+                        }
+                    } else if #matched {
+                        anyhow::bail!("No case matches the nondet picks of action `{}`", action)
+                    } else {
+                        anyhow::bail!("Unimplemented action `{}`", action)
+                    }
                 }
             }
         }
     }
 }
 
-struct Case {
-    action: Option<Ident>,
-    nondet_picks: Vec<NondetPick>,
+struct Labels<'a> {
+    switch: &'a Lifetime,
+    matched: &'a Ident,
+    missing: &'a Ident,
+}
+
+/// A case of the switch: either alternative patterns, or none for the `_` case,
+/// followed by an optional guard and handler.
+pub(crate) struct Case {
+    pub alternatives: Vec<Alternative>,
+    guard: Option<Expr>,
     handler: Option<Expr>,
+    is_fallible: bool,
+}
+
+/// An action along with the nondet picks it must have.
+pub(crate) struct Alternative {
+    pub action: Ident,
+    pub nondet_picks: Vec<NondetPick>,
+    /// The method called by implicit handlers.
+    method: Ident,
 }
 
 impl Parse for Case {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let mut action = None;
-        let mut nondet_picks = Vec::new();
-        let handler;
+        let mut alternatives = Vec::new();
 
         if input.peek(Token![_]) {
             input.parse::<Token![_]>()?;
-            handler = parse_handler(input)?;
-            if handler.is_none() {
-                return Err(input.error("_ case requires a handler block"));
+        } else {
+            loop {
+                alternatives.push(input.parse::<Alternative>()?);
+                if !input.peek(Token![|]) {
+                    break;
+                }
+                input.parse::<Token![|]>()?;
             }
+        }
+
+        let guard = if input.peek(Token![if]) {
+            input.parse::<Token![if]>()?;
+            Some(input.call(Expr::parse_without_eager_brace)?)
         } else {
-            action = Some(input.parse()?);
-            if input.peek(Paren) {
-                let parens;
-                parenthesized!(parens in input);
-                nondet_picks.extend(Punctuated::<_, Token![,]>::parse_terminated(&parens)?);
+            None
+        };
+
+        let (handler, is_fallible) = parse_handler(input)?;
+        if handler.is_none() {
+            if alternatives.is_empty() {
+                return Err(input.error("_ case requires a handler block"));
+            }
+            if let [_, second, ..] = alternatives.as_slice() {
+                return Err(Error::new(
+                    second.action.span(),
+                    "alternative patterns require a handler",
+                ));
             }
-            handler = parse_handler(input)?;
         }
 
         Ok(Self {
-            action,
+            alternatives,
+            guard,
             handler,
+            is_fallible,
+        })
+    }
+}
+
+impl Parse for Alternative {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let action: Ident = input.parse()?;
+        let mut nondet_picks = Vec::new();
+        if input.peek(Paren) {
+            let parens;
+            parenthesized!(parens in input);
+            nondet_picks.extend(Punctuated::<_, Token![,]>::parse_terminated(&parens)?);
+        }
+        Ok(Self {
+            method: action.clone(),
+            action,
             nondet_picks,
         })
     }
 }
 
-fn parse_handler(input: &ParseBuffer<'_>) -> syn::Result<Option<Expr>> {
+/// Parses an optional `=> expr` or `=> try expr` handler.
+fn parse_handler(input: &ParseBuffer<'_>) -> syn::Result<(Option<Expr>, bool)> {
     if input.peek(Token![=>]) {
         input.parse::<Token![=>]>()?;
-        return Ok(Some(input.parse()?));
+        let is_fallible = input.parse::<Option<Token![try]>>()?.is_some();
+        return Ok((Some(input.parse()?), is_fallible));
     }
-    Ok(None)
+    Ok((None, false))
 }
 
 impl Case {
-    fn expand(self) -> proc_macro2::TokenStream {
-        let Self {
+    pub fn is_catch_all(&self) -> bool {
+        self.alternatives.is_empty()
+    }
+
+    /// Whether the case handles any step, i.e. a `_` case without a guard.
+    pub fn matches_all(&self) -> bool {
+        self.is_catch_all() && self.guard.is_none()
+    }
+
+    /// Expands the case into one labeled block per alternative.
+    fn expand(&self, labels: &Labels, blocks: &mut Vec<proc_macro2::TokenStream>) {
+        let label = Lifetime::new("'alternative", Span::mixed_site());
+        let switch = labels.switch;
+        let guard = self.guard.as_ref().map(|guard| {
+            quote! {
+                if !(#guard) {
+                    break #label;
+                }
+            }
+        });
+
+        if self.is_catch_all() {
+            let handler = self.handler.as_ref().expect("_ case has a handler");
+            let result = quote_result(handler, self.is_fallible, quote!(step.action_taken));
+            blocks.push(quote! {
+                #label: {
+                    #guard
+                    break #switch { #result };
+                }
+            });
+            return;
+        }
+
+        for Alternative {
             action,
             nondet_picks,
-            handler,
-        } = self;
-
-        match action {
-            Some(action) => {
-                let handler = handler.map(|expr| quote!(#expr)).unwrap_or_else(|| {
-                    let nondets = nondet_picks.iter().map(|nondet| nondet.name.clone());
-                    quote!(self.#action(#(#nondets),*))
-                });
-                let nondets = nondet_picks.into_iter().map(NondetPick::expand);
-                let action_str = action.to_string();
-
-                quote! {
-                    #action_str => {
-                        #(#nondets)*
-                        #handler;
-                        Ok(())
+            method,
+        } in &self.alternatives
+        {
+            let action_str = action.to_string();
+            let (matched, missing) = (labels.matched, labels.missing);
+            let nondets = nondet_picks
+                .iter()
+                .map(|nondet| nondet.expand(&label, missing));
+            let result = match &self.handler {
+                Some(expr) => quote_result(expr, self.is_fallible, quote!(#action_str)),
+                None => {
+                    // Implicit handlers call a method whose return type is
+                    // known, so results are told apart from other values.
+                    let nondets = nondet_picks.iter().map(NondetPick::binding);
+                    let context = quote_context(quote!(#action_str));
+                    quote! {
+                        {
+                            use quint_connect::runner::{FallibleHandler as _, InfallibleHandler as _};
+                            (&mut quint_connect::runner::Handler(Some(self.#method(#(#nondets),*))))
+                                .step_result()
+                                #context
+                        }
                     }
                 }
-            }
-            None => {
-                quote! {
-                    _ => {
-                        #handler;
-                        Ok(())
+            };
+
+            blocks.push(quote! {
+                #label: {
+                    if step.action_taken != #action_str {
+                        break #label;
                     }
+                    #matched = true;
+                    #(#nondets)*
+                    #guard
+                    break #switch { #result };
                 }
-            }
+            });
+        }
+    }
+}
+
+/// Evaluates an explicit handler. Handlers marked with `try` must return a
+/// `Result`, whose error fails the step, while other handlers always succeed.
+/// `try` handlers run in a closure, so that errors returned early with `?` get
+/// the same context as the handler's result.
+fn quote_result(
+    handler: &Expr,
+    is_fallible: bool,
+    action: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    if is_fallible {
+        let context = quote_context(action);
+        let result = quote_spanned!(handler.span()=> quint_connect::runner::fallible(#handler));
+        quote! {
+            (|| -> quint_connect::Result { #result })() #context
+        }
+    } else {
+        quote! {
+            #handler;
+            Ok(())
         }
     }
 }
 
-struct NondetPick {
-    name: Ident,
+/// Adds the action being handled as context to errors returned by handlers.
+fn quote_context(action: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote! {
+        .map_err(|err| err.context(format!("Failed to handle action `{}`", #action)))
+    }
+}
+
+/// A nondet pick, written as `name [as binding] [: Type] [?] [@ pattern]`, or all
+/// of the action's picks decoded into a struct, written as `..name [: Type]`.
+pub(crate) struct NondetPick {
+    pub name: Ident,
+    /// The name of the pick in the spec.
+    pub key: String,
+    pub is_record: bool,
+    rename: Option<Ident>,
     ty: Option<Type>,
     is_required: bool,
+    pattern: Option<Pat>,
 }
 
 impl Parse for NondetPick {
     fn parse(input: ParseStream) -> syn::Result<Self> {
+        let is_record = input.parse::<Option<Token![..]>>()?.is_some();
         let name: Ident = input.parse()?;
+        let mut rename = None;
         let mut ty = None;
         let mut is_required = true;
+        let mut pattern = None;
+
+        if is_record && (input.peek(Token![as]) || input.peek(Token![?])) {
+            return Err(input.error("`..` picks can't be renamed or optional"));
+        }
+
+        if input.peek(Token![as]) {
+            input.parse::<Token![as]>()?;
+            rename = Some(input.parse()?);
+        }
 
         if input.peek(Token![:]) {
             input.parse::<Token![:]>()?;
@@ -180,41 +400,75 @@ impl Parse for NondetPick {
             is_required = false;
         }
 
+        if input.peek(Token![@]) {
+            input.parse::<Token![@]>()?;
+            pattern = Some(Pat::parse_multi(input)?);
+        }
+
         Ok(Self {
+            key: name.to_string(),
+            is_record,
             name,
+            rename,
             ty,
             is_required,
+            pattern,
         })
     }
 }
 
 impl NondetPick {
-    fn expand(self) -> proc_macro2::TokenStream {
-        let Self {
-            name,
-            ty,
-            is_required,
-        } = self;
+    /// The Rust name the pick is bound to.
+    fn binding(&self) -> &Ident {
+        self.rename.as_ref().unwrap_or(&self.name)
+    }
 
-        let name_str = name.to_string();
-        let ty = ty.map(|ty| quote!(#ty)).unwrap_or_else(|| quote!(_));
+    /// Decodes the pick, leaving the alternative if it's missing or doesn't
+    /// match its pattern.
+    fn expand(&self, label: &Lifetime, missing: &Ident) -> proc_macro2::TokenStream {
+        let name_str = &self.key;
+        let binding = self.binding();
+        let ty = self
+            .ty
+            .as_ref()
+            .map(|ty| quote!(#ty))
+            .unwrap_or_else(|| quote!(_));
 
-        if is_required {
+        let decode = if self.is_record {
+            quote! {
+                let #binding: #ty = step.nondet_picks.decode()?;
+            }
+        } else if self.is_required {
             quote! {
-                let #name = step.nondet_picks.get(#name_str);
-                let #name: #ty = match #name {
+                let #binding: #ty = match step.nondet_picks.get(#name_str) {
                     Some(pick) => <#ty as serde::Deserialize>::deserialize(pick.clone())?,
-                    None => anyhow::bail!("Unknown nondet pick `{}`", #name_str.to_string()),
+                    None => {
+                        #missing = Some(#name_str);
+                        break #label;
+                    }
                 };
             }
         } else {
             quote! {
-                let #name: Option<#ty> = step
+                let #binding: Option<#ty> = step
                     .nondet_picks
                     .get(#name_str)
                     .map(|nondet| <#ty as serde::Deserialize>::deserialize(nondet.clone()))
                     .transpose()?;
             }
+        };
+
+        let check = self.pattern.as_ref().map(|pattern| {
+            quote! {
+                if !matches!(&#binding, #pattern) {
+                    break #label;
+                }
+            }
+        });
+
+        quote! {
+            #decode
+            #check
         }
     }
 }
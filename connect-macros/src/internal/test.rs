@@ -1,21 +1,36 @@
-use crate::internal::utils::{parse_num, parse_str, quote_opt_lit, quote_opt_str, quote_seed};
+use crate::internal::{
+    matrix::{self, Axis, Kind, Value},
+    spec::SpecFile,
+    utils::{parse_consts, parse_num, parse_str, quote_consts, quote_opt_lit, quote_opt_str},
+};
 use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::quote;
-use syn::{ItemFn, meta::ParseNestedMeta, parse::Result, parse_macro_input};
+use syn::{Error, Ident, ItemFn, LitStr, meta::ParseNestedMeta, parse::Result, parse_macro_input};
 
-#[derive(Default, Debug)]
+const MATRIX_KEYS: &[(&str, Kind)] = &[
+    ("main", Kind::Str),
+    ("test", Kind::Str),
+    ("max_samples", Kind::Num),
+    ("seed", Kind::Str),
+    ("consts", Kind::Consts),
+];
+
+#[derive(Default, Clone)]
 struct TestAttrs {
-    pub spec: Option<String>,
-    pub main: Option<String>,
-    pub test: Option<String>,
+    pub spec: Option<LitStr>,
+    pub main: Option<LitStr>,
+    pub test: Option<LitStr>,
     pub max_samples: Option<usize>,
-    pub seed: Option<String>,
+    pub seed: Option<LitStr>,
+    pub consts: Vec<(String, String)>,
+    pub matrix: Vec<Axis>,
 }
 
 impl TestAttrs {
     fn parse(&mut self, meta: ParseNestedMeta) -> Result<()> {
         if meta.path.is_ident("spec") {
-            self.spec = Some(parse_str(&meta)?);
+            self.spec = Some(meta.value()?.parse()?);
         } else if meta.path.is_ident("main") {
             self.main = Some(parse_str(&meta)?);
         } else if meta.path.is_ident("test") {
@@ -24,11 +39,48 @@ impl TestAttrs {
             self.max_samples = Some(parse_num(&meta)?);
         } else if meta.path.is_ident("seed") {
             self.seed = Some(parse_str(&meta)?);
+        } else if meta.path.is_ident("consts") {
+            self.consts = parse_consts(&meta)?;
+        } else if meta.path.is_ident("matrix") {
+            self.matrix = matrix::parse_matrix(&meta, MATRIX_KEYS)?;
         } else {
             return Err(meta.error("Invalid attribute"));
         }
         Ok(())
     }
+
+    /// Sets an attribute to one of its values in the matrix.
+    fn apply(&mut self, key: &Ident, value: &Value) -> Result<()> {
+        let set = match (key.to_string().as_str(), value) {
+            ("main", Value::Str(value)) => self.main.replace(value.clone()).is_some(),
+            ("test", Value::Str(value)) => self.test.replace(value.clone()).is_some(),
+            ("seed", Value::Str(value)) => self.seed.replace(value.clone()).is_some(),
+            ("max_samples", Value::Num(value)) => self.max_samples.replace(*value).is_some(),
+            ("consts", Value::Consts(value)) => {
+                !std::mem::replace(&mut self.consts, value.clone()).is_empty()
+            }
+            _ => unreachable!("matrix values are parsed by kind"),
+        };
+        if set {
+            return Err(Error::new(
+                key.span(),
+                format!(
+                    "Attribute `{}` can't be set both directly and in the matrix",
+                    key
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks that the main module and run exist in the spec.
+    fn validate(&self, spec_file: &SpecFile) -> Result<()> {
+        let main = spec_file.main_module(self.main.as_ref())?;
+        if let Some(test) = &self.test {
+            spec_file.check_decl(main, "run", test)?;
+        }
+        Ok(())
+    }
 }
 
 pub(crate) fn expand(args: TokenStream, item: TokenStream) -> TokenStream {
@@ -36,51 +88,115 @@ pub(crate) fn expand(args: TokenStream, item: TokenStream) -> TokenStream {
     let parser = syn::meta::parser(|meta| attrs.parse(meta));
     parse_macro_input!(args with parser);
 
-    if attrs.spec.as_ref().is_none_or(|spec| spec.is_empty()) {
+    let Some(spec_lit) = attrs.spec.as_ref().filter(|spec| !spec.value().is_empty()) else {
         return quote! {
             compile_error!("Missing required attribute `spec`");
         }
         .into();
-    }
+    };
+    let spec_file = match SpecFile::read(spec_lit) {
+        Ok(spec_file) => spec_file,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let test_fn = parse_macro_input!(item as ItemFn);
+    let test_attrs = test_fn.attrs;
+    let test_ident = test_fn.sig.ident;
+    let test_block = test_fn.block;
+    // The spec is tracked once per expansion, rather than in every test.
+    let dependencies = spec_file.quote_dependencies();
 
-    if attrs.test.as_ref().is_none_or(|test| test.is_empty()) {
+    if attrs.matrix.is_empty() {
+        let test_name = test_ident.to_string();
+        let test_body = match quote_test_body(&spec_file, &attrs, &test_name, &test_block) {
+            Ok(test_body) => test_body,
+            Err(err) => return err.to_compile_error().into(),
+        };
         return quote! {
-            compile_error!("Missing required attribute `test`");
+            #dependencies
+
+            #[test]
+            #(#test_attrs)*
+            fn #test_ident() {
+                #test_body
+            }
         }
         .into();
     }
 
-    let spec = attrs.spec.unwrap();
-    let test = attrs.test.unwrap();
-    let main = quote_opt_str(&attrs.main);
-    let max_samples = quote_opt_lit(&attrs.max_samples);
-    let seed = quote_seed(&attrs.seed);
-
-    let test_fn = parse_macro_input!(item as ItemFn);
-    let test_attrs = test_fn.attrs;
-    let test_ident = test_fn.sig.ident;
-    let test_name = test_ident.to_string();
-    let test_block = test_fn.block;
+    let mut cases = Vec::new();
+    let matrix_cases = match matrix::cases(&attrs.matrix) {
+        Ok(cases) => cases,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    for (case_ident, case) in matrix_cases {
+        let mut case_attrs = attrs.clone();
+        for (key, value) in case {
+            if let Err(err) = case_attrs.apply(key, value) {
+                return err.to_compile_error().into();
+            }
+        }
+        let test_name = format!("{}::{}", test_ident, case_ident);
+        let test_body = match quote_test_body(&spec_file, &case_attrs, &test_name, &test_block) {
+            Ok(test_body) => test_body,
+            Err(err) => return err.to_compile_error().into(),
+        };
+        cases.push(quote! {
+            #[test]
+            #(#test_attrs)*
+            fn #case_ident() {
+                #test_body
+            }
+        });
+    }
 
     quote! {
-        #[test]
-        #(#test_attrs)*
-        fn #test_ident() {
-            let driver = #test_block;
-            let config = quint_connect::runner::Config {
-                test_name: #test_name.to_string(),
-                gen_config: quint_connect::runner::TestConfig {
-                    spec: #spec.to_string(),
-                    test: #test.to_string(),
-                    main: #main,
-                    max_samples: #max_samples,
-                    seed: #seed.to_string(),
-                }
-            };
-            if let Err(err) = quint_connect::runner::run_test(driver, config) {
-                panic!("{}", err);
-            }
+        mod #test_ident {
+            use super::*;
+
+            #dependencies
+
+            #(#cases)*
         }
     }
     .into()
 }
+
+fn quote_test_body(
+    spec_file: &SpecFile,
+    attrs: &TestAttrs,
+    test_name: &str,
+    test_block: &syn::Block,
+) -> Result<TokenStream2> {
+    let Some(test) = attrs.test.as_ref().filter(|test| !test.value().is_empty()) else {
+        return Err(Error::new(
+            Span::call_site(),
+            "Missing required attribute `test`",
+        ));
+    };
+    attrs.validate(spec_file)?;
+    let spec = spec_file.path_str();
+    let main = quote_opt_str(&attrs.main);
+    let max_samples = quote_opt_lit(&attrs.max_samples);
+    let seed = quote_opt_str(&attrs.seed);
+    let consts = quote_consts(&attrs.consts);
+
+    Ok(quote! {
+        let new_driver = || #test_block;
+        let config = quint_connect::runner::Config {
+            test_name: #test_name.to_string(),
+            gen_config: quint_connect::runner::TestConfig {
+                spec: #spec.to_string(),
+                test: #test.to_string(),
+                main: #main,
+                max_samples: #max_samples,
+                seed: #seed,
+                consts: #consts,
+                ..Default::default()
+            }
+        };
+        if let Err(err) = quint_connect::runner::run_test(new_driver, config) {
+            panic!("{:#}", err);
+        }
+    })
+}
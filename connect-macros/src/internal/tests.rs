@@ -0,0 +1,120 @@
+use crate::internal::{
+    spec::SpecFile,
+    utils::{
+        parse_consts, parse_num, parse_str, quote_consts, quote_opt_lit, quote_opt_str, to_ident,
+    },
+};
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quint_connect_spec::test_name;
+use quote::quote;
+use syn::{Error, Expr, LitStr, meta::ParseNestedMeta, parse::Result, parse_macro_input};
+
+#[derive(Default)]
+struct TestsAttrs {
+    pub spec: Option<LitStr>,
+    pub main: Option<LitStr>,
+    pub driver: Option<Expr>,
+    pub max_samples: Option<usize>,
+    pub seed: Option<LitStr>,
+    pub consts: Vec<(String, String)>,
+}
+
+impl TestsAttrs {
+    fn parse(&mut self, meta: ParseNestedMeta) -> Result<()> {
+        if meta.path.is_ident("spec") {
+            self.spec = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("main") {
+            self.main = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("driver") {
+            self.driver = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("max_samples") {
+            self.max_samples = Some(parse_num(&meta)?);
+        } else if meta.path.is_ident("seed") {
+            self.seed = Some(parse_str(&meta)?);
+        } else if meta.path.is_ident("consts") {
+            self.consts = parse_consts(&meta)?;
+        } else {
+            return Err(meta.error("Invalid attribute"));
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn expand(input: TokenStream) -> TokenStream {
+    let mut attrs = TestsAttrs::default();
+    let parser = syn::meta::parser(|meta| attrs.parse(meta));
+    parse_macro_input!(input with parser);
+
+    let Some(spec_lit) = attrs.spec.filter(|spec| !spec.value().is_empty()) else {
+        return quote! {
+            compile_error!("Missing required attribute `spec`");
+        }
+        .into();
+    };
+    let Some(driver) = attrs.driver else {
+        return quote! {
+            compile_error!("Missing required attribute `driver`");
+        }
+        .into();
+    };
+
+    let (spec_file, runs) = match find_runs(&spec_lit, attrs.main.as_ref()) {
+        Ok(found) => found,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let spec = spec_file.path_str();
+    let dependencies = spec_file.quote_dependencies();
+
+    let main = quote_opt_str(&attrs.main);
+    let max_samples = quote_opt_lit(&attrs.max_samples);
+    let seed = quote_opt_str(&attrs.seed);
+    let consts = quote_consts(&attrs.consts);
+
+    let tests = runs.iter().map(|run| {
+        let test_ident = to_ident(&test_name(run), Span::call_site());
+        quote! {
+            #[test]
+            #[allow(non_snake_case)]
+            fn #test_ident() {
+                let config = quint_connect::runner::Config {
+                    test_name: #run.to_string(),
+                    gen_config: quint_connect::runner::TestConfig {
+                        spec: #spec.to_string(),
+                        test: #run.to_string(),
+                        main: #main,
+                        max_samples: #max_samples,
+                        seed: #seed,
+                        consts: #consts,
+                        ..Default::default()
+                    }
+                };
+                if let Err(err) = quint_connect::runner::run_test(#driver, config) {
+                    panic!("{:#}", err);
+                }
+            }
+        }
+    });
+
+    quote! {
+        #dependencies
+        #(#tests)*
+    }
+    .into()
+}
+
+/// Reads the spec and lists the `run` definitions in its main module, including
+/// those it imports, as `cargo quint-connect` does.
+fn find_runs(spec: &LitStr, main: Option<&LitStr>) -> Result<(SpecFile, Vec<String>)> {
+    let spec_file = SpecFile::read(spec)?;
+    let module = spec_file.main_module(main)?;
+
+    let runs = spec_file.sources.visible_names(module, "run");
+    if runs.is_empty() {
+        return Err(Error::new(
+            spec.span(),
+            format!("No `run` definitions found in module `{}`", module.name),
+        ));
+    }
+    Ok((spec_file, runs))
+}
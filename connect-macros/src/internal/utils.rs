@@ -1,16 +1,16 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream, TokenTree};
 use quote::{ToTokens, quote};
-use syn::{LitInt, LitStr, meta::ParseNestedMeta, parse::Result};
+use syn::{Ident, LitInt, LitStr, Token, meta::ParseNestedMeta, parse::Result};
 
-pub(crate) fn parse_str(meta: &ParseNestedMeta<'_>) -> Result<String> {
-    Ok(meta.value()?.parse::<LitStr>()?.value())
+pub(crate) fn parse_str(meta: &ParseNestedMeta<'_>) -> Result<LitStr> {
+    meta.value()?.parse()
 }
 
 pub(crate) fn parse_num(meta: &ParseNestedMeta<'_>) -> Result<usize> {
     meta.value()?.parse::<LitInt>()?.base10_parse::<usize>()
 }
 
-pub(crate) fn quote_opt_str(opt: &Option<String>) -> TokenStream {
+pub(crate) fn quote_opt_str(opt: &Option<LitStr>) -> TokenStream {
     match opt {
         Some(val) => quote! { Some(#val.to_string()) },
         None => quote! { None },
@@ -24,11 +24,125 @@ pub(crate) fn quote_opt_lit<T: ToTokens>(opt: &Option<T>) -> TokenStream {
     }
 }
 
-pub(crate) fn quote_seed(opt: &Option<String>) -> TokenStream {
-    match opt {
-        Some(seed) => quote! { #seed.to_string() },
-        None => quote! {
-            quint_connect::runner::gen_random_seed()
-        },
+/// Parses `consts(NAME = <quint expr>, ...)`, where Quint expressions are written
+/// as Rust tokens, e.g. `NODES = Set("a", "b")`. String literals are Quint strings.
+pub(crate) fn parse_consts(meta: &ParseNestedMeta<'_>) -> Result<Vec<(String, String)>> {
+    let mut consts = Vec::new();
+    meta.parse_nested_meta(|meta| parse_const(meta, &mut consts))?;
+    Ok(consts)
+}
+
+/// Parses a single `NAME = <quint expr>` constant.
+pub(crate) fn parse_const(meta: ParseNestedMeta, consts: &mut Vec<(String, String)>) -> Result<()> {
+    let Some(name) = meta.path.get_ident().map(ToString::to_string) else {
+        return Err(meta.error("Expected a constant name"));
+    };
+    if consts.iter().any(|(other, _)| *other == name) {
+        return Err(meta.error(format!("Duplicated constant `{}`", name)));
+    }
+
+    let input = meta.value()?;
+    let mut tokens = TokenStream::new();
+    while !input.is_empty() && !input.peek(Token![,]) {
+        tokens.extend([input.parse::<TokenTree>()?]);
+    }
+    let expr = tokens.to_string();
+    if expr.is_empty() {
+        return Err(meta.error(format!("Missing value for constant `{}`", name)));
+    }
+
+    consts.push((name, expr));
+    Ok(())
+}
+
+pub(crate) fn quote_consts(consts: &[(String, String)]) -> TokenStream {
+    let consts = consts
+        .iter()
+        .map(|(name, value)| quote! { (#name, #value) });
+    quote! { &[#(#consts),*] }
+}
+
+/// A hint pointing to the candidate closest to `name`, if any is close enough to
+/// likely be a typo.
+pub(crate) fn did_you_mean<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> String {
+    let max_distance = (name.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .map(|candidate| (distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| format!(", did you mean `{}`?", candidate))
+        .unwrap_or_default()
+}
+
+/// Edit distance between two strings, ignoring case and counting swapped
+/// adjacent characters as a single edit.
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<_> = a.chars().flat_map(char::to_lowercase).collect();
+    let b: Vec<_> = b.chars().flat_map(char::to_lowercase).collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// An identifier for the given name, escaping Rust keywords.
+pub(crate) fn to_ident(name: &str, span: Span) -> Ident {
+    syn::parse_str::<Ident>(name)
+        .map(|mut ident| {
+            ident.set_span(span);
+            ident
+        })
+        .unwrap_or_else(|_| Ident::new_raw(name, span))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_consts() {
+        let attr: syn::Attribute = syn::parse_quote! {
+            #[quint_run(consts(
+                N = 3,
+                NODES = Set("a", "b").map(x => x),
+                NAME = "node",
+            ))]
+        };
+        let mut consts = Vec::new();
+        attr.parse_nested_meta(|meta| {
+            consts = parse_consts(&meta)?;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(
+            consts,
+            [
+                ("N".to_string(), "3".to_string()),
+                (
+                    "NODES".to_string(),
+                    "Set (\"a\" , \"b\") . map (x => x)".to_string()
+                ),
+                ("NAME".to_string(), "\"node\"".to_string()),
+            ]
+        );
     }
 }
@@ -16,3 +16,23 @@ pub fn quint_run(args: TokenStream, item: TokenStream) -> TokenStream {
 pub fn quint_test(args: TokenStream, item: TokenStream) -> TokenStream {
     internal::test::expand(args, item)
 }
+
+#[proc_macro]
+pub fn quint_tests(input: TokenStream) -> TokenStream {
+    internal::tests::expand(input)
+}
+
+#[proc_macro_attribute]
+pub fn quint_driver(args: TokenStream, item: TokenStream) -> TokenStream {
+    internal::driver::expand(args, item)
+}
+
+#[proc_macro_derive(QuintState, attributes(quint))]
+pub fn quint_state(input: TokenStream) -> TokenStream {
+    internal::state::expand(input)
+}
+
+#[proc_macro_derive(QuintAction, attributes(quint))]
+pub fn quint_action(input: TokenStream) -> TokenStream {
+    internal::action::expand(input)
+}
@@ -0,0 +1,61 @@
+//! Naming conventions shared by the macros and the type generator.
+
+/// Converts a `CamelCase` or `camelCase` name into `snake_case`, e.g. `MoveX` into
+/// `move_x` and `HTTPRequest` into `http_request`.
+pub fn to_snake_case(name: &str) -> String {
+    let chars: Vec<_> = name.chars().collect();
+    let mut snake = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if prev.is_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_uppercase() && next_is_lower)
+            {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
+
+/// Converts a `snake_case` name into `camelCase`, e.g. `node_id` into `nodeId`.
+pub fn to_camel_case(name: &str) -> String {
+    let mut parts = name.split('_').filter(|part| !part.is_empty());
+    let mut camel = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        let mut chars = part.chars();
+        camel.extend(chars.next().into_iter().flat_map(char::to_uppercase));
+        camel.push_str(chars.as_str());
+    }
+    camel
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_snake_case() {
+        assert_eq!(to_snake_case("init"), "init");
+        assert_eq!(
+            to_snake_case("SpontaneouslyPrepares"),
+            "spontaneously_prepares"
+        );
+        assert_eq!(to_snake_case("moveTo"), "move_to");
+        assert_eq!(to_snake_case("MoveX"), "move_x");
+        assert_eq!(to_snake_case("HTTPRequest"), "http_request");
+        assert_eq!(to_snake_case("step2Phase"), "step2_phase");
+        assert_eq!(to_snake_case("already_snake"), "already_snake");
+    }
+
+    #[test]
+    fn test_to_camel_case() {
+        assert_eq!(to_camel_case("node"), "node");
+        assert_eq!(to_camel_case("node_id"), "nodeId");
+        assert_eq!(to_camel_case("max_msg_count"), "maxMsgCount");
+        assert_eq!(to_camel_case("nodeId"), "nodeId");
+    }
+}
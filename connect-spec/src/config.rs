@@ -0,0 +1,109 @@
+//! Locates `quint-connect.toml` and the Quint binary it configures, so that the
+//! macros, the build script helpers and the tests all run the same Quint.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Name of the optional project configuration file.
+pub const CONFIG_FILE: &str = "quint-connect.toml";
+
+/// Reads an environment variable, treating empty values as unset.
+pub fn var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.trim().is_empty())
+}
+
+/// Finds the configuration file, either from `QUINT_CONFIG` or by looking up
+/// `quint-connect.toml` from the given directory upwards.
+pub fn config_file(dir: &Path) -> Option<PathBuf> {
+    if let Some(path) = var("QUINT_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    dir.ancestors()
+        .map(|dir| dir.join(CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+/// Resolves the Quint binary set in the configuration file in `dir`. Paths are
+/// relative to that directory, while command names are left to the `PATH`.
+pub fn resolve_quint(quint: String, dir: &Path) -> String {
+    let path = Path::new(&quint);
+    match path.is_relative() && path.components().count() > 1 {
+        true => dir.join(path).to_string_lossy().into_owned(),
+        false => quint,
+    }
+}
+
+/// The Quint binary shared by all tests: `QUINT_BIN`, then the `quint` setting
+/// in the `[defaults]` table of the configuration file found from `dir`, and
+/// finally `quint` on the `PATH`.
+pub fn quint_bin(dir: &Path) -> io::Result<String> {
+    if let Some(quint) = var("QUINT_BIN") {
+        return Ok(quint);
+    }
+    let Some(path) = config_file(dir) else {
+        return Ok("quint".to_string());
+    };
+    let contents = fs::read_to_string(&path)?;
+    let quint = default_quint(&contents).map_err(|err| {
+        let msg = format!(
+            "Failed to parse config file at: {}: {}",
+            path.display(),
+            err
+        );
+        io::Error::new(io::ErrorKind::InvalidData, msg)
+    })?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    Ok(quint.map_or("quint".to_string(), |quint| resolve_quint(quint, dir)))
+}
+
+/// The `quint` setting in the `[defaults]` table, if any.
+fn default_quint(contents: &str) -> Result<Option<String>, String> {
+    let table: toml::Table = contents.parse().map_err(|err| format!("{}", err))?;
+    match table
+        .get("defaults")
+        .and_then(|defaults| defaults.get("quint"))
+    {
+        None => Ok(None),
+        Some(toml::Value::String(quint)) => Ok(Some(quint.clone())),
+        Some(_) => Err("Expected `defaults.quint` to be a string".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_quint() {
+        let config = r#"
+            [defaults]
+            quint = "node_modules/.bin/quint"
+
+            [tests.happyPath]
+            quint = "/opt/quint/bin/quint"
+        "#;
+        assert_eq!(
+            default_quint(config).unwrap().as_deref(),
+            Some("node_modules/.bin/quint")
+        );
+        assert_eq!(default_quint("[tests.happyPath]").unwrap(), None);
+        assert!(default_quint("[defaults]\nquint = 1").is_err());
+        assert!(default_quint("[defaults").is_err());
+    }
+
+    #[test]
+    fn test_resolve_quint() {
+        let dir = Path::new("/workspace");
+        assert_eq!(
+            resolve_quint("node_modules/.bin/quint".to_string(), dir),
+            "/workspace/node_modules/.bin/quint"
+        );
+        assert_eq!(resolve_quint("quint".to_string(), dir), "quint");
+        assert_eq!(
+            resolve_quint("/opt/quint/bin/quint".to_string(), dir),
+            "/opt/quint/bin/quint"
+        );
+    }
+}
@@ -0,0 +1,20 @@
+//! Reads Quint specs the way the `quint-connect` crates need to, without requiring
+//! Quint to be installed, and finds the Quint binary to run where it is.
+//!
+//! This crate is an implementation detail of `quint-connect`, shared by its macros
+//! and the `cargo quint-connect` subcommand so that both list the same tests. Its
+//! API is not stable.
+
+pub mod case;
+pub mod config;
+
+mod scan;
+
+pub use scan::{Decl, Import, Module, Spec, SpecSources};
+
+/// The name of the test generated by `quint_tests!` for a run. Runs reached
+/// through qualified imports, like `choreo::happyTest`, are named
+/// `choreo__happyTest`.
+pub fn test_name(run: &str) -> String {
+    run.replace("::", "__")
+}
@@ -0,0 +1,515 @@
+//! A lightweight scanner for Quint sources.
+//!
+//! It only recognizes module-level declarations and imports, which is all that is
+//! needed to name the tests of a spec and to check the names given to macros.
+
+use std::{
+    fs, io, iter,
+    path::{Path, PathBuf},
+};
+
+/// Declaration kinds that can only appear at the top level of a module. Notably,
+/// `val` and `def` are left out since they also introduce local bindings.
+const DECL_KINDS: &[&str] = &["action", "run", "temporal", "const", "var"];
+
+/// A spec file along with the files it imports.
+#[derive(Debug)]
+pub struct SpecSources {
+    pub path: PathBuf,
+    pub spec: Spec,
+    /// Files imported by the spec, transitively. Imports that can't be read are
+    /// left for Quint to report.
+    imports: Vec<(PathBuf, Spec)>,
+}
+
+impl SpecSources {
+    /// Reads the spec at the given path and the files it imports.
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let path = path.canonicalize()?;
+        let source = fs::read_to_string(&path)?;
+        let spec = Spec::parse(&source);
+        let imports = read_imports(&path, &spec);
+        Ok(Self {
+            path,
+            spec,
+            imports,
+        })
+    }
+
+    /// Scans the given source as the spec at `path`, without reading its imports.
+    pub fn parse(path: impl Into<PathBuf>, source: &str) -> Self {
+        Self {
+            path: path.into(),
+            spec: Spec::parse(source),
+            imports: Vec::new(),
+        }
+    }
+
+    /// The name of the main module: the given one, or the spec's file name as
+    /// Quint defaults to.
+    pub fn main_name(&self, main: Option<&str>) -> String {
+        match main {
+            Some(main) => main.to_string(),
+            None => self
+                .path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Finds a module declared in the spec file itself.
+    pub fn module(&self, name: &str) -> Option<&Module> {
+        self.spec.modules.iter().find(|module| module.name == name)
+    }
+
+    /// Names of the declarations of the given kind in the module or in modules
+    /// it imports, as they are referred to from the module, e.g. `choreo::cue`
+    /// for `import choreo(processes = NODES) as choreo`.
+    ///
+    /// All of the module's imports are followed, while the modules it imports
+    /// only contribute what they export.
+    pub fn visible_names(&self, module: &Module, kind: &str) -> Vec<String> {
+        let mut names = Vec::new();
+        self.collect_names(module, kind, true, &mut vec![], &mut names);
+        names
+    }
+
+    fn collect_names<'a>(
+        &'a self,
+        module: &'a Module,
+        kind: &str,
+        main: bool,
+        visiting: &mut Vec<&'a str>,
+        names: &mut Vec<String>,
+    ) {
+        visiting.push(&module.name);
+        for decl in module.decls.iter().filter(|decl| decl.kind == kind) {
+            names.push(decl.name.clone());
+        }
+        for import in module.imports.iter().filter(|import| main || import.export) {
+            let Some(imported) = self.modules().find(|module| module.name == import.module) else {
+                continue;
+            };
+            if visiting.contains(&imported.name.as_str()) {
+                continue;
+            }
+            let mut imported_names = Vec::new();
+            self.collect_names(imported, kind, false, visiting, &mut imported_names);
+            for name in imported_names {
+                if import.only.as_ref().is_some_and(|only| *only != name) {
+                    continue;
+                }
+                let name = match &import.prefix {
+                    Some(prefix) => format!("{}::{}", prefix, name),
+                    None => name,
+                };
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        visiting.pop();
+    }
+
+    /// The spec file followed by every file it imports, transitively.
+    pub fn dependencies(&self) -> impl Iterator<Item = &Path> {
+        iter::once(self.path.as_path()).chain(self.imports.iter().map(|(path, _)| path.as_path()))
+    }
+
+    /// The modules of the spec file and of the files it imports.
+    pub fn modules(&self) -> impl Iterator<Item = &Module> {
+        self.spec
+            .modules
+            .iter()
+            .chain(self.imports.iter().flat_map(|(_, spec)| &spec.modules))
+    }
+}
+
+/// Reads the files imported by a spec, transitively.
+fn read_imports(path: &Path, spec: &Spec) -> Vec<(PathBuf, Spec)> {
+    let mut imports: Vec<(PathBuf, Spec)> = Vec::new();
+    let mut pending = vec![(path.to_path_buf(), spec.imports.clone())];
+    while let Some((file, paths)) = pending.pop() {
+        let dir = file.parent().unwrap_or(Path::new("."));
+        for import in paths {
+            let mut import = dir.join(&import);
+            if import.extension().is_none_or(|ext| ext != "qnt") {
+                import.set_extension("qnt");
+            }
+            let Ok(import) = import.canonicalize() else {
+                continue;
+            };
+            if import == path || imports.iter().any(|(other, _)| *other == import) {
+                continue;
+            }
+            if let Ok(source) = fs::read_to_string(&import) {
+                let spec = Spec::parse(&source);
+                pending.push((import.clone(), spec.imports.clone()));
+                imports.push((import, spec));
+            }
+        }
+    }
+    imports
+}
+
+/// The modules declared in a Quint file.
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct Spec {
+    pub modules: Vec<Module>,
+    /// Paths of the files imported with `import ... from "<path>"`.
+    pub imports: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Module {
+    pub name: String,
+    pub decls: Vec<Decl>,
+    /// Modules imported or exported by this module.
+    pub imports: Vec<Import>,
+}
+
+/// An `import` or `export` of a module, possibly instantiating its constants.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Import {
+    pub module: String,
+    /// The qualifier of the imported names: `P` for `import M as P` and `M` for
+    /// a plain `import M`. Names imported with `import M.*` are unqualified.
+    pub prefix: Option<String>,
+    /// The single name imported with `import M.name`.
+    pub only: Option<String>,
+    /// Whether the imported names are visible to modules importing this one.
+    pub export: bool,
+}
+
+/// A top-level declaration, such as `action step` or `run happyTest`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Decl {
+    pub kind: &'static str,
+    pub name: String,
+}
+
+impl Spec {
+    pub fn parse(source: &str) -> Self {
+        let tokens = tokenize(source);
+        let mut spec = Self::default();
+        let mut i = 0;
+        while i < tokens.len() {
+            match &tokens[i..] {
+                [Token::Ident(kw), Token::Ident(name), Token::Punct('{'), ..] if kw == "module" => {
+                    let (module, end) = scan_module(name, &tokens, i + 3);
+                    spec.modules.push(module);
+                    i = end;
+                }
+                _ => i += 1,
+            }
+        }
+        spec.imports = tokens
+            .windows(2)
+            .filter_map(|pair| match pair {
+                [Token::Ident(kw), Token::Str(path)] if kw == "from" => Some(path.clone()),
+                _ => None,
+            })
+            .collect();
+        spec
+    }
+}
+
+/// Collects the module's declarations and imports, starting right after its
+/// opening brace. Returns the position after the module's closing brace.
+fn scan_module(name: &str, tokens: &[Token], start: usize) -> (Module, usize) {
+    let mut module = Module {
+        name: name.to_string(),
+        decls: Vec::new(),
+        imports: Vec::new(),
+    };
+    let mut depth = 1;
+    let mut i = start;
+    while i < tokens.len() {
+        match (&tokens[i], tokens.get(i + 1)) {
+            (Token::Punct('{' | '(' | '['), _) => depth += 1,
+            (Token::Punct('}' | ')' | ']'), _) => depth -= 1,
+            (Token::Ident(kw), Some(Token::Ident(name))) if depth == 1 => {
+                if let Some(kind) = DECL_KINDS.iter().find(|kind| *kind == kw) {
+                    module.decls.push(Decl {
+                        kind,
+                        name: name.clone(),
+                    });
+                    i += 1;
+                } else if kw == "import" || kw == "export" {
+                    let (import, end) = scan_import(kw == "export", name, tokens, i + 2);
+                    module.imports.push(import);
+                    i = end - 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+        if depth == 0 {
+            break;
+        }
+    }
+    (module, i)
+}
+
+/// Reads what follows the module name of an `import` or `export`: instance
+/// arguments, then `.*`, `.name` or `as P`. Returns the position after them.
+fn scan_import(export: bool, module: &str, tokens: &[Token], start: usize) -> (Import, usize) {
+    let mut i = start;
+    if tokens.get(i) == Some(&Token::Punct('(')) {
+        let mut depth = 0;
+        while let Some(token) = tokens.get(i) {
+            i += 1;
+            match token {
+                Token::Punct('(') => depth += 1,
+                Token::Punct(')') if depth == 1 => break,
+                Token::Punct(')') => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+    let mut import = Import {
+        module: module.to_string(),
+        prefix: Some(module.to_string()),
+        only: None,
+        export,
+    };
+    match &tokens[i.min(tokens.len())..] {
+        [Token::Punct('.'), Token::Punct('*'), ..] => {
+            import.prefix = None;
+            i += 2;
+        }
+        [Token::Punct('.'), Token::Ident(name), ..] => {
+            import.prefix = None;
+            import.only = Some(name.clone());
+            i += 2;
+        }
+        [Token::Ident(kw), Token::Ident(prefix), ..] if kw == "as" => {
+            import.prefix = Some(prefix.clone());
+            i += 2;
+        }
+        _ => {}
+    }
+    (import, i)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Punct(char),
+}
+
+/// Splits Quint source into identifiers, string literals and punctuation,
+/// skipping whitespace and comments.
+fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            '"' => {
+                let mut value = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => value.extend(chars.next()),
+                        c => value.push(c),
+                    }
+                }
+                tokens.push(Token::Str(value));
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut ident = String::from(c);
+                while let Some(&c) = chars.peek() {
+                    if !c.is_alphanumeric() && c != '_' {
+                        break;
+                    }
+                    ident.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            c => tokens.push(Token::Punct(c)),
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r#"
+        // A counter. module ignored { run ignoredTest = true }
+        module counter {
+          const MAX: int
+          var n: int
+
+          /* action commented = n' = 0 */
+          action init = n' = 0
+          action step = {
+            nondet by = 1.to(MAX).oneOf()
+            n' = n + by
+          }
+
+          def inc(x) = { val y = x + 1 y }
+          run incTest = init.then(step)
+          pure val msg = "run stringTest = {"
+          run resetTest = init.then(10.reps(_ => step))
+        }
+
+        module other {
+          run otherTest = true
+        }
+    "#;
+
+    #[test]
+    fn test_parse_modules() {
+        let spec = Spec::parse(SPEC);
+        let names: Vec<_> = spec.modules.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["counter", "other"]);
+    }
+
+    #[test]
+    fn test_top_level_declarations() {
+        let sources = SpecSources::parse("/crate/specs/counter.qnt", SPEC);
+        let main = sources.module(&sources.main_name(None)).unwrap();
+        assert_eq!(sources.visible_names(main, "run"), ["incTest", "resetTest"]);
+        assert_eq!(sources.visible_names(main, "action"), ["init", "step"]);
+        assert_eq!(sources.visible_names(main, "const"), ["MAX"]);
+
+        let other = sources.module(&sources.main_name(Some("other"))).unwrap();
+        assert_eq!(sources.visible_names(other, "run"), ["otherTest"]);
+        assert!(sources.module("missing").is_none());
+    }
+
+    const INSTANCES: &str = r#"
+        module base {
+          action init = true
+          run baseTest = init
+        }
+
+        module lib {
+          export base.*
+          import base as hidden
+          action libStep = true
+          run libTest = libStep
+        }
+
+        module main {
+          const N: int
+          import lib(N = N, M = Set(1, 2)) as inst from "./lib"
+          import base.baseTest
+          import lib
+          export lib.libStep
+          run mainTest = true
+        }
+    "#;
+
+    #[test]
+    fn test_import_forms() {
+        let spec = Spec::parse(INSTANCES);
+        let imports: Vec<_> = spec.modules[2]
+            .imports
+            .iter()
+            .map(|import| {
+                (
+                    import.module.as_str(),
+                    import.prefix.as_deref(),
+                    import.only.as_deref(),
+                    import.export,
+                )
+            })
+            .collect();
+        assert_eq!(
+            imports,
+            [
+                ("lib", Some("inst"), None, false),
+                ("base", None, Some("baseTest"), false),
+                ("lib", Some("lib"), None, false),
+                ("lib", None, Some("libStep"), true),
+            ]
+        );
+        assert_eq!(spec.modules[2].decls.len(), 2);
+
+        let sources = SpecSources::parse("/crate/main.qnt", INSTANCES);
+        let main = sources.module("main").unwrap();
+        assert_eq!(
+            sources.visible_names(main, "run"),
+            [
+                "mainTest",
+                "inst::libTest",
+                "inst::baseTest",
+                "baseTest",
+                "lib::libTest",
+                "lib::baseTest"
+            ]
+        );
+        assert_eq!(
+            sources.visible_names(main, "action"),
+            [
+                "inst::libStep",
+                "inst::init",
+                "lib::libStep",
+                "lib::init",
+                "libStep"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_imports() {
+        let dir = std::env::temp_dir().join(format!("quint-connect-spec-{}", std::process::id()));
+        fs::create_dir_all(dir.join("lib")).unwrap();
+        let write = |name: &str, source: &str| {
+            let path = dir.join(name);
+            fs::write(&path, source).unwrap();
+            path.canonicalize().unwrap()
+        };
+        let spells = write("lib/spells.qnt", "module spells { run spellsTest = true }");
+        let utils = write(
+            "lib/utils.qnt",
+            r#"module utils {
+              import spells.* from "./spells"
+              action init = true
+            }"#,
+        );
+        let main = write(
+            "main.qnt",
+            r#"module main {
+              import utils.* from "./lib/utils"
+              import spells.* from "lib/spells.qnt"
+              import missing.* from "./missing"
+              action step = true
+            }"#,
+        );
+
+        let sources = SpecSources::read(&main).unwrap();
+        assert_eq!(
+            sources.dependencies().collect::<Vec<_>>(),
+            [main.as_path(), &utils, &spells]
+        );
+        let module = sources.module("main").unwrap();
+        assert_eq!(sources.visible_names(module, "action"), ["step", "init"]);
+        assert_eq!(sources.visible_names(module, "run"), ["spellsTest"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,24 +1,22 @@
 use crate::game::*;
 use quint_connect::*;
-use serde::Deserialize;
 use std::collections::BTreeMap;
 
-#[derive(Eq, PartialEq, Deserialize, Debug)]
-#[serde(tag = "tag", content = "value")]
+#[derive(Eq, PartialEq, QuintState, Debug)]
 enum Square {
     Occupied(Player),
     Empty,
 }
 
-#[derive(Eq, PartialEq, Deserialize, Debug)]
+#[derive(Eq, PartialEq, QuintState, Debug)]
+#[quint(driver = TicTacToeDriver, from_driver = GameState::project)]
 struct GameState {
     board: BTreeMap<usize, BTreeMap<usize, Square>>,
-    #[serde(rename = "nextTurn")]
     next_turn: Player,
 }
 
-impl State<TicTacToeDriver> for GameState {
-    fn from_driver(driver: &TicTacToeDriver) -> Result<Self> {
+impl GameState {
+    fn project(driver: &TicTacToeDriver) -> Result<Self> {
         let mut board: BTreeMap<usize, BTreeMap<usize, Square>> = BTreeMap::new();
         for (col, x) in driver.game.board.iter().zip(1..) {
             for (cell, y) in col.iter().zip(1..) {
@@ -38,16 +36,17 @@ struct TicTacToeDriver {
     game: TicTacToe,
 }
 
+#[quint_driver(spec = "examples/tictactoe/spec/tictactoe.qnt")]
 impl Driver for TicTacToeDriver {
     type State = GameState;
 
     fn step(&mut self, step: &Step) -> Result {
         switch!(step {
             init => self.game = TicTacToe::default(),
-            MoveX(corner?, coordinate?) => match corner.or(coordinate) {
-                Some(pos) => self.game.move_to(to_game_pos(pos), Player::X),
-                None => self.game.move_to((1, 1), Player::X)
-            },
+            MoveX(corner as pos) | MoveX(coordinate as pos) => {
+                self.game.move_to(to_game_pos(pos), Player::X)
+            }
+            MoveX => self.game.move_to((1, 1), Player::X), // takes the center
             MoveO(coordinate) => self.game.move_to(to_game_pos(coordinate), Player::O),
             stuttered => () // do nothing...
         })
@@ -44,15 +44,22 @@ impl Driver for TwoPhaseCommitDriver {
         }
     }
 
+    fn sequence() -> Option<Sequence> {
+        Some(Sequence::Picks {
+            participant: "node",
+            receiver: None,
+        })
+    }
+
     fn step(&mut self, step: &Step) -> Result {
-        switch!(step {
-            Init => self.init(),
-            SpontaneouslyPrepares(node) => self.spontaneously_prepares(node),
-            SpontaneouslyAborts(node) => self.spontaneously_aborts(node),
-            AbortsAsInstructed(node) => self.aborts_as_instructed(node),
-            CommitsAsInstructed(node) => self.commits_as_instructed(node),
-            DecidesOnCommit(node) => self.decides_on_commit(node),
-            DecidesOnAbort(node) => self.decides_on_abort(node)
+        switch!(step, rename = snake_case {
+            Init,
+            SpontaneouslyPrepares(node),
+            SpontaneouslyAborts(node),
+            AbortsAsInstructed(node),
+            CommitsAsInstructed(node),
+            DecidesOnCommit(node),
+            DecidesOnAbort(node),
         })
     }
 }
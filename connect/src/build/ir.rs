@@ -0,0 +1,291 @@
+//! A subset of Quint's JSON intermediate representation: type definitions, state
+//! variables, and the types they refer to.
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde_json::Value;
+
+/// A Quint type.
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) enum Type {
+    Bool,
+    Int,
+    Str,
+    /// A reference to a type definition, or an uninterpreted type.
+    Const(String),
+    /// A type variable of a polymorphic type definition.
+    Var(String),
+    Set(Box<Type>),
+    List(Box<Type>),
+    Map(Box<Type>, Box<Type>),
+    Tuple(Vec<Type>),
+    Record(Vec<(String, Type)>),
+    Sum(Vec<(String, Type)>),
+    /// An application of a polymorphic type definition, e.g. `Option[int]`.
+    App(String, Vec<Type>),
+}
+
+impl Type {
+    /// Type variables in order of appearance.
+    pub fn vars(&self, vars: &mut Vec<String>) {
+        match self {
+            Type::Var(name) => {
+                if !vars.contains(name) {
+                    vars.push(name.clone());
+                }
+            }
+            Type::Set(elem) | Type::List(elem) => elem.vars(vars),
+            Type::Map(key, value) => {
+                key.vars(vars);
+                value.vars(vars);
+            }
+            Type::Tuple(elems) | Type::App(_, elems) => {
+                elems.iter().for_each(|elem| elem.vars(vars));
+            }
+            Type::Record(fields) | Type::Sum(fields) => {
+                fields.iter().for_each(|(_, ty)| ty.vars(vars));
+            }
+            Type::Bool | Type::Int | Type::Str | Type::Const(_) => {}
+        }
+    }
+}
+
+/// A `type` declaration. Uninterpreted types have no definition.
+#[derive(Debug)]
+pub(crate) struct TypeDef {
+    pub name: String,
+    pub params: Vec<String>,
+    pub ty: Option<Type>,
+}
+
+/// A `var` declaration.
+#[derive(Debug)]
+pub(crate) struct Var {
+    pub name: String,
+    pub ty: Type,
+}
+
+/// The declarations of a Quint module relevant to code generation.
+#[derive(Debug)]
+pub(crate) struct Module {
+    pub name: String,
+    pub typedefs: Vec<TypeDef>,
+    pub vars: Vec<Var>,
+}
+
+/// Reads the modules from the output of `quint typecheck`. Variables without
+/// a type annotation take their inferred type.
+pub(crate) fn parse_modules(ir: &Value) -> Result<Vec<Module>> {
+    let types = &ir["types"];
+    ir["modules"]
+        .as_array()
+        .context("Expected a `modules` array in Quint's output")?
+        .iter()
+        .map(|module| parse_module(module, types))
+        .collect()
+}
+
+fn parse_module(module: &Value, types: &Value) -> Result<Module> {
+    let name = str_field(module, "name")?;
+    let mut typedefs = Vec::new();
+    let mut vars = Vec::new();
+    let decls = module["declarations"].as_array().into_iter().flatten();
+    for decl in decls {
+        match decl["kind"].as_str() {
+            Some("typedef") => typedefs.push(parse_typedef(decl)?),
+            Some("var") => vars.push(parse_var(decl, types)?),
+            _ => {}
+        }
+    }
+    Ok(Module {
+        name,
+        typedefs,
+        vars,
+    })
+}
+
+fn parse_typedef(decl: &Value) -> Result<TypeDef> {
+    let name = str_field(decl, "name")?;
+    let ty = match decl.get("type") {
+        Some(ty) => Some(parse_type(ty).with_context(|| format!("Invalid type `{}`", name))?),
+        None => None,
+    };
+    let params = match decl["params"].as_array() {
+        Some(params) => params
+            .iter()
+            .map(|param| param.as_str().map(str::to_string))
+            .collect::<Option<_>>()
+            .with_context(|| format!("Invalid type parameters of `{}`", name))?,
+        None => {
+            let mut vars = Vec::new();
+            ty.iter().for_each(|ty| ty.vars(&mut vars));
+            vars
+        }
+    };
+    Ok(TypeDef { name, params, ty })
+}
+
+fn parse_var(decl: &Value, types: &Value) -> Result<Var> {
+    let name = str_field(decl, "name")?;
+    let ty = match decl.get("typeAnnotation") {
+        Some(ty) => ty,
+        None => {
+            let id = decl["id"].to_string();
+            types[id.trim_matches('"')]
+                .get("type")
+                .with_context(|| format!("Missing type of variable `{}`", name))?
+        }
+    };
+    let ty = parse_type(ty).with_context(|| format!("Invalid type of variable `{}`", name))?;
+    Ok(Var { name, ty })
+}
+
+pub(crate) fn parse_type(ty: &Value) -> Result<Type> {
+    let kind = str_field(ty, "kind")?;
+    Ok(match kind.as_str() {
+        "bool" => Type::Bool,
+        "int" => Type::Int,
+        "str" => Type::Str,
+        "const" => Type::Const(unqualified(&str_field(ty, "name")?)),
+        "var" => Type::Var(str_field(ty, "name")?),
+        "set" => Type::Set(Box::new(parse_type(&ty["elem"])?)),
+        "list" => Type::List(Box::new(parse_type(&ty["elem"])?)),
+        "fun" => Type::Map(
+            Box::new(parse_type(&ty["arg"])?),
+            Box::new(parse_type(&ty["res"])?),
+        ),
+        "tup" => Type::Tuple(
+            parse_row(&ty["fields"])?
+                .into_iter()
+                .map(|(_, ty)| ty)
+                .collect(),
+        ),
+        "rec" => Type::Record(parse_row(&ty["fields"])?),
+        "sum" => Type::Sum(parse_row(&ty["fields"])?),
+        "app" => {
+            let ctor = match parse_type(&ty["ctor"])? {
+                Type::Const(name) => name,
+                other => bail!("Unsupported type constructor: {:?}", other),
+            };
+            let args = ty["args"]
+                .as_array()
+                .context("Expected type arguments")?
+                .iter()
+                .map(parse_type)
+                .collect::<Result<_>>()?;
+            Type::App(ctor, args)
+        }
+        other => bail!("Unsupported type kind `{}`", other),
+    })
+}
+
+/// The fields of a row type, e.g. of records, tuples and sum types.
+fn parse_row(row: &Value) -> Result<Vec<(String, Type)>> {
+    row["fields"]
+        .as_array()
+        .context("Expected a row of fields")?
+        .iter()
+        .map(|field| {
+            let name = str_field(field, "fieldName")?;
+            let ty = parse_type(&field["fieldType"])?;
+            Ok((name, ty))
+        })
+        .collect()
+}
+
+/// Drops the namespace of a qualified name, e.g. `Square` for `types::Square`.
+fn unqualified(name: &str) -> String {
+    name.rsplit("::").next().unwrap_or(name).to_string()
+}
+
+fn str_field(value: &Value, field: &str) -> Result<String> {
+    value[field]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("Expected a `{}` string in: {}", field, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn row(fields: Vec<(&str, Value)>) -> Value {
+        let fields: Vec<_> = fields
+            .into_iter()
+            .map(|(name, ty)| json!({ "fieldName": name, "fieldType": ty }))
+            .collect();
+        json!({ "kind": "row", "fields": fields, "other": { "kind": "empty" } })
+    }
+
+    #[test]
+    fn test_parse_types() {
+        let ty = json!({
+            "kind": "fun",
+            "arg": { "kind": "int" },
+            "res": { "kind": "set", "elem": { "kind": "const", "name": "T::Player" } }
+        });
+        assert_eq!(
+            parse_type(&ty).unwrap(),
+            Type::Map(
+                Box::new(Type::Int),
+                Box::new(Type::Set(Box::new(Type::Const("Player".to_string()))))
+            )
+        );
+
+        let ty = json!({
+            "kind": "tup",
+            "fields": row(vec![("0", json!({ "kind": "str" })), ("1", json!({ "kind": "bool" }))])
+        });
+        assert_eq!(
+            parse_type(&ty).unwrap(),
+            Type::Tuple(vec![Type::Str, Type::Bool])
+        );
+
+        let ty = json!({
+            "kind": "app",
+            "ctor": { "kind": "const", "name": "Option" },
+            "args": [{ "kind": "var", "name": "a" }]
+        });
+        assert_eq!(
+            parse_type(&ty).unwrap(),
+            Type::App("Option".to_string(), vec![Type::Var("a".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_parse_modules() {
+        let ir = json!({
+            "modules": [{
+                "name": "bank",
+                "declarations": [
+                    { "kind": "typedef", "id": 1, "name": "ACCOUNT" },
+                    {
+                        "kind": "typedef", "id": 2, "name": "Result",
+                        "type": { "kind": "sum", "fields": row(vec![
+                            ("Ok", json!({ "kind": "var", "name": "a" })),
+                            ("Err", json!({ "kind": "str" })),
+                        ]) }
+                    },
+                    { "kind": "var", "id": 3, "name": "balance", "typeAnnotation": { "kind": "int" } },
+                    { "kind": "var", "id": 4, "name": "open" },
+                    { "kind": "def", "id": 5, "name": "init" }
+                ]
+            }],
+            "types": { "4": { "type": { "kind": "bool" }, "typeVariables": [] } }
+        });
+        let modules = parse_modules(&ir).unwrap();
+        let bank = &modules[0];
+        assert_eq!(bank.name, "bank");
+        assert_eq!(bank.typedefs[0].ty, None);
+        assert_eq!(bank.typedefs[1].params, ["a"]);
+        assert_eq!(bank.vars[0].ty, Type::Int);
+        assert_eq!(bank.vars[1].ty, Type::Bool);
+    }
+
+    #[test]
+    fn test_unsupported_types() {
+        let ty = json!({ "kind": "oper", "args": [], "res": { "kind": "int" } });
+        let err = parse_type(&ty).unwrap_err();
+        assert_eq!(err.to_string(), "Unsupported type kind `oper`");
+    }
+}
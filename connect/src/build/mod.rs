@@ -0,0 +1,403 @@
+//! Build script helpers.
+//!
+//! Generates Rust types from the type definitions and state variables of a
+//! Quint specification, keeping them in sync with the spec. See
+//! [`generate_types`].
+
+mod ir;
+mod render;
+
+use crate::settings::{self, Settings};
+use anyhow::{Context, Result, anyhow, bail};
+use ir::{Module, parse_modules};
+use render::Renderer;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use tempfile::TempDir;
+
+/// Default name of the struct holding the spec's state variables.
+const DEFAULT_STATE: &str = "SpecState";
+
+/// Generates Rust types for a Quint specification from a build script.
+///
+/// Runs `quint typecheck` on the spec and writes to `OUT_DIR` a Rust file with:
+///
+/// - A struct or enum for each record or sum type definition, and an alias for
+///   other type definitions
+/// - A struct with a field per state variable, named `SpecState` by default
+/// - The definitions these types refer to, including those from imported modules
+///
+/// All generated types derive [`QuintState`](crate::QuintState), so fields and
+/// variants are renamed to Rust conventions while decoding Quint's names. Integers
+/// are `i64`, sets are `BTreeSet`, maps are `BTreeMap`, and uninterpreted types
+/// are strings. Anonymous records are named after the field or variable they are
+/// the type of.
+///
+/// By default, all type definitions and variables of the main module are
+/// generated. The file is named after the main module, and changes to files in
+/// the spec's directory or to `quint-connect.toml` rebuild it.
+///
+/// # Examples
+///
+/// In `build.rs`:
+///
+/// ```rust,no_run
+/// fn main() {
+///     quint_connect::build::generate_types("spec/tictactoe.qnt")
+///         .types(["Square"])
+///         .state("GameState")
+///         .generate()
+///         .unwrap();
+/// }
+/// ```
+///
+/// Then include the generated types where needed, e.g. in the test module, with
+/// `include!(concat!(env!("OUT_DIR"), "/tictactoe.rs"))`.
+pub fn generate_types(spec: impl AsRef<Path>) -> TypeGenerator {
+    TypeGenerator {
+        spec: spec.as_ref().to_path_buf(),
+        main: None,
+        types: None,
+        vars: None,
+        state: DEFAULT_STATE.to_string(),
+        out_file: None,
+    }
+}
+
+/// Options for generating Rust types, created with [`generate_types`].
+#[derive(Debug)]
+pub struct TypeGenerator {
+    spec: PathBuf,
+    main: Option<String>,
+    types: Option<Vec<String>>,
+    vars: Option<Vec<String>>,
+    state: String,
+    out_file: Option<String>,
+}
+
+impl TypeGenerator {
+    /// Sets the main module (defaults to the spec's file name).
+    pub fn main(mut self, module: impl Into<String>) -> Self {
+        self.main = Some(module.into());
+        self
+    }
+
+    /// Generates only the given type definitions, besides the ones they and the
+    /// state variables refer to.
+    pub fn types<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.types = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Includes only the given variables in the state struct. Without variables,
+    /// no state struct is generated.
+    pub fn vars<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.vars = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets the name of the state struct (defaults to `SpecState`).
+    pub fn state(mut self, name: impl Into<String>) -> Self {
+        self.state = name.into();
+        self
+    }
+
+    /// Sets the name of the generated file in `OUT_DIR` (defaults to the main
+    /// module's name with the `.rs` extension).
+    pub fn out_file(mut self, name: impl Into<String>) -> Self {
+        self.out_file = Some(name.into());
+        self
+    }
+
+    /// Generates the types, returning the path of the generated file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if not called from a build script, if Quint fails to
+    /// typecheck the spec, or if a type can't be represented in Rust.
+    pub fn generate(self) -> Result<PathBuf> {
+        let out_dir = env::var_os("OUT_DIR")
+            .map(PathBuf::from)
+            .context("`OUT_DIR` is not set, please generate types from a build script")?;
+
+        let dir = self.spec.parent().unwrap_or(Path::new("."));
+        println!("cargo:rerun-if-changed={}", dir.display());
+        println!("cargo:rerun-if-env-changed=QUINT_BIN");
+        println!("cargo:rerun-if-env-changed=QUINT_CONFIG");
+        if let Some(config) = settings::config_file()? {
+            println!("cargo:rerun-if-changed={}", config.display());
+        }
+
+        let ir = self.typecheck()?;
+        let modules = parse_modules(&ir).context("Failed to read Quint's output")?;
+        let main = self.main_module(&modules)?;
+        let code = self
+            .render(&modules, main)
+            .with_context(|| format!("Failed to generate types for: {}", self.spec.display()))?;
+
+        let out_file = match &self.out_file {
+            Some(name) => out_dir.join(name),
+            None => out_dir.join(format!("{}.rs", main.name)),
+        };
+        fs::write(&out_file, code)
+            .with_context(|| format!("Failed to write types to: {}", out_file.display()))?;
+        Ok(out_file)
+    }
+
+    /// Runs `quint typecheck`, returning its JSON output.
+    fn typecheck(&self) -> Result<serde_json::Value> {
+        let tmpdir = TempDir::with_prefix("quint-connect-")?;
+        let out = tmpdir.path().join("typecheck.json");
+        let output = Command::new(Settings::load_defaults()?.quint())
+            .arg("typecheck")
+            .arg(&self.spec)
+            .arg("--out")
+            .arg(&out)
+            .output()
+            .context("Failed to execute Quint command")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!("{}", stderr)).context("Quint returned non-zero code.");
+        }
+
+        let json = fs::read_to_string(&out)
+            .with_context(|| format!("Failed to read Quint's output at: {}", out.display()))?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    fn main_module<'a>(&self, modules: &'a [Module]) -> Result<&'a Module> {
+        let name = match &self.main {
+            Some(main) => main.clone(),
+            None => self
+                .spec
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .with_context(|| format!("Invalid spec file name: {}", self.spec.display()))?,
+        };
+        match modules.iter().find(|module| module.name == name) {
+            Some(module) => Ok(module),
+            None => bail!(
+                "Module `{}` not found in spec, please set it with `main`",
+                name
+            ),
+        }
+    }
+
+    fn render(&self, modules: &[Module], main: &Module) -> Result<String> {
+        let mut renderer = Renderer::new(modules, main);
+
+        match &self.types {
+            Some(types) => {
+                for name in types {
+                    renderer.typedef(name)?;
+                }
+            }
+            None => {
+                for typedef in &main.typedefs {
+                    renderer.typedef(&typedef.name)?;
+                }
+            }
+        }
+
+        let vars = match &self.vars {
+            Some(names) => names
+                .iter()
+                .map(|name| {
+                    main.vars
+                        .iter()
+                        .find(|var| var.name == *name)
+                        .with_context(|| format!("Variable `{}` not found", name))
+                })
+                .collect::<Result<Vec<_>>>()?,
+            None => main.vars.iter().collect(),
+        };
+        if !vars.is_empty() {
+            renderer.state(&self.state, &vars)?;
+        }
+
+        let header = format!(
+            "Generated by quint-connect from `{}`. Do not edit by hand.",
+            self.spec.display()
+        );
+        Ok(renderer.finish(&header))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    fn row(fields: Vec<(&str, Value)>) -> Value {
+        let fields: Vec<_> = fields
+            .into_iter()
+            .map(|(name, ty)| json!({ "fieldName": name, "fieldType": ty }))
+            .collect();
+        json!({ "kind": "row", "fields": fields, "other": { "kind": "empty" } })
+    }
+
+    fn unit() -> Value {
+        json!({ "kind": "tup", "fields": row(vec![]) })
+    }
+
+    fn named(name: &str) -> Value {
+        json!({ "kind": "const", "name": name })
+    }
+
+    /// The output of `quint typecheck` for a tic-tac-toe spec importing its
+    /// types from another module.
+    fn tictactoe() -> Value {
+        let int = json!({ "kind": "int" });
+        json!({
+            "stage": "typechecking",
+            "modules": [
+                {
+                    "name": "types",
+                    "declarations": [
+                        {
+                            "kind": "typedef", "id": 1, "name": "Player",
+                            "type": { "kind": "sum", "fields": row(vec![("X", unit()), ("O", unit())]) }
+                        },
+                        {
+                            "kind": "typedef", "id": 2, "name": "Square",
+                            "type": { "kind": "sum", "fields": row(vec![
+                                ("Occupied", named("Player")),
+                                ("Empty", unit()),
+                            ]) }
+                        },
+                    ]
+                },
+                {
+                    "name": "tictactoe",
+                    "declarations": [
+                        { "kind": "import", "id": 3, "protoName": "types" },
+                        { "kind": "typedef", "id": 4, "name": "PLAYER_ID" },
+                        {
+                            "kind": "typedef", "id": 5, "name": "Position",
+                            "type": { "kind": "tup", "fields": row(vec![("0", int.clone()), ("1", int.clone())]) }
+                        },
+                        {
+                            "kind": "typedef", "id": 6, "name": "Option",
+                            "type": { "kind": "sum", "fields": row(vec![
+                                ("Some", json!({ "kind": "var", "name": "a" })),
+                                ("None", unit()),
+                            ]) }
+                        },
+                        {
+                            "kind": "var", "id": 7, "name": "board",
+                            "typeAnnotation": {
+                                "kind": "fun",
+                                "arg": named("Position"),
+                                "res": named("types::Square")
+                            }
+                        },
+                        {
+                            "kind": "var", "id": 8, "name": "nextTurn",
+                            "typeAnnotation": named("Player")
+                        },
+                        {
+                            "kind": "var", "id": 9, "name": "winner",
+                            "typeAnnotation": {
+                                "kind": "app",
+                                "ctor": named("Option"),
+                                "args": [named("Player")]
+                            }
+                        },
+                        { "kind": "var", "id": 10, "name": "scores" },
+                    ]
+                }
+            ],
+            "types": {
+                "10": {
+                    "type": {
+                        "kind": "fun",
+                        "arg": named("PLAYER_ID"),
+                        "res": { "kind": "rec", "fields": row(vec![
+                            ("wins", int.clone()),
+                            ("type", json!({ "kind": "str" })),
+                        ]) }
+                    }
+                }
+            }
+        })
+    }
+
+    fn render(generator: TypeGenerator) -> String {
+        let modules = parse_modules(&tictactoe()).unwrap();
+        let main = generator.main_module(&modules).unwrap();
+        generator.render(&modules, main).unwrap()
+    }
+
+    #[test]
+    fn test_generate_types() {
+        let code = render(generate_types("spec/tictactoe.qnt"));
+        assert_eq!(
+            code,
+            "// Generated by quint-connect from `spec/tictactoe.qnt`. Do not edit by hand.\n\
+             \n\
+             pub type Position = (i64, i64);\n\
+             \n\
+             #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, quint_connect::QuintState)]\n\
+             pub enum Player {\n    X,\n    O,\n}\n\
+             \n\
+             #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, quint_connect::QuintState)]\n\
+             pub enum Square {\n    Occupied(Player),\n    Empty,\n}\n\
+             \n\
+             #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, quint_connect::QuintState)]\n\
+             pub struct Scores {\n    pub wins: i64,\n    pub r#type: ::std::string::String,\n}\n\
+             \n\
+             #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, quint_connect::QuintState)]\n\
+             pub struct SpecState {\n    \
+                 pub board: ::std::collections::BTreeMap<Position, Square>,\n    \
+                 pub next_turn: Player,\n    \
+                 pub winner: ::std::option::Option<Player>,\n    \
+                 pub scores: ::std::collections::BTreeMap<::std::string::String, Scores>,\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_generate_selected_types() {
+        let generator = generate_types("tictactoe.qnt")
+            .types(["Square"])
+            .vars(["nextTurn"])
+            .state("GameState");
+        let code = render(generator);
+        assert!(code.contains("pub enum Square"));
+        assert!(code.contains("pub enum Player"));
+        assert!(!code.contains("Position"));
+        assert!(code.contains("pub struct GameState {\n    pub next_turn: Player,\n}"));
+    }
+
+    #[test]
+    fn test_generate_errors() {
+        let modules = parse_modules(&tictactoe()).unwrap();
+
+        let generator = generate_types("game.qnt");
+        let err = generator.main_module(&modules).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Module `game` not found in spec, please set it with `main`"
+        );
+
+        let generator = generate_types("tictactoe.qnt").types(["Board"]);
+        let err = generator.render(&modules, &modules[1]).unwrap_err();
+        assert_eq!(err.to_string(), "Type `Board` not found");
+
+        let generator = generate_types("tictactoe.qnt").vars(["turn"]);
+        let err = generator.render(&modules, &modules[1]).unwrap_err();
+        assert_eq!(err.to_string(), "Variable `turn` not found");
+    }
+}
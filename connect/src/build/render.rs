@@ -0,0 +1,311 @@
+//! Rendering of Quint types as Rust items deriving `QuintState`.
+
+use crate::build::ir::{Module, Type, TypeDef, Var};
+use anyhow::{Result, bail};
+use quint_connect_spec::case::{to_camel_case, to_snake_case};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+const DERIVES: &str = "Clone, PartialEq, Eq, PartialOrd, Ord, Debug, quint_connect::QuintState";
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// Keywords that can't be used as raw identifiers.
+const RESERVED: &[&str] = &["self", "Self", "super", "crate"];
+
+/// Renders type definitions and a state struct, along with the definitions they
+/// depend on.
+pub(crate) struct Renderer<'a> {
+    modules: &'a [Module],
+    main: &'a Module,
+    items: Vec<String>,
+    /// Rust names of the Quint type definitions already rendered.
+    rendered: BTreeMap<String, String>,
+    /// Names of the rendered Rust items.
+    names: BTreeSet<String>,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(modules: &'a [Module], main: &'a Module) -> Self {
+        Self {
+            modules,
+            main,
+            items: Vec::new(),
+            rendered: BTreeMap::new(),
+            names: BTreeSet::new(),
+        }
+    }
+
+    pub fn finish(self, header: &str) -> String {
+        let mut out = format!("// {}\n", header);
+        for item in self.items {
+            out.push('\n');
+            out.push_str(&item);
+        }
+        out
+    }
+
+    /// Finds a type definition, preferring the main module over imported ones.
+    fn find(&self, name: &str) -> Option<&'a TypeDef> {
+        let main = std::iter::once(self.main);
+        let others = self
+            .modules
+            .iter()
+            .filter(|module| module.name != self.main.name);
+        main.chain(others)
+            .flat_map(|module| &module.typedefs)
+            .find(|typedef| typedef.name == name)
+    }
+
+    pub fn typedef(&mut self, name: &str) -> Result<()> {
+        let Some(typedef) = self.find(name) else {
+            bail!("Type `{}` not found", name)
+        };
+        // Uninterpreted types are strings, and options are Rust's.
+        let Some(ty) = &typedef.ty else {
+            return Ok(());
+        };
+        if is_option(name) || self.rendered.contains_key(name) {
+            return Ok(());
+        }
+
+        let rust_name = self.claim(&to_pascal_case(name))?;
+        self.rendered.insert(name.to_string(), rust_name.clone());
+        let generics = render_generics(&typedef.params);
+        let item = match ty {
+            Type::Record(fields) => self.record(&rust_name, &generics, fields)?,
+            Type::Sum(variants) => self.sum(&rust_name, &generics, variants)?,
+            other => {
+                let ty = self.rust_type(other, name)?;
+                format!("pub type {}{} = {};\n", rust_name, generics, ty)
+            }
+        };
+        self.items.push(item);
+        Ok(())
+    }
+
+    pub fn state(&mut self, name: &str, vars: &[&Var]) -> Result<()> {
+        let fields: Vec<_> = vars
+            .iter()
+            .map(|var| (var.name.clone(), var.ty.clone()))
+            .collect();
+        let name = self.claim(name)?;
+        let item = self.record(&name, "", &fields)?;
+        self.items.push(item);
+        Ok(())
+    }
+
+    /// Reserves a Rust item name, adding a numeric suffix if it's taken.
+    fn claim(&mut self, name: &str) -> Result<String> {
+        let mut claimed = to_ident(name)?;
+        let mut n = 2;
+        while !self.names.insert(claimed.clone()) {
+            claimed = format!("{}{}", name, n);
+            n += 1;
+        }
+        Ok(claimed)
+    }
+
+    fn record(&mut self, name: &str, generics: &str, fields: &[(String, Type)]) -> Result<String> {
+        let mut item = format!(
+            "#[derive({})]\npub struct {}{} {{\n",
+            DERIVES, name, generics
+        );
+        for (field, ty) in fields {
+            let ty = self.rust_type(ty, field)?;
+            let ident = to_snake_case(field);
+            if to_camel_case(&ident) != *field {
+                writeln!(item, "    #[quint(rename = {:?})]", field)?;
+            }
+            writeln!(item, "    pub {}: {},", to_ident(&ident)?, ty)?;
+        }
+        item.push_str("}\n");
+        Ok(item)
+    }
+
+    fn sum(&mut self, name: &str, generics: &str, variants: &[(String, Type)]) -> Result<String> {
+        let mut item = format!("#[derive({})]\npub enum {}{} {{\n", DERIVES, name, generics);
+        for (variant, ty) in variants {
+            let ident = to_ident(&to_pascal_case(variant))?;
+            if ident != *variant {
+                writeln!(item, "    #[quint(rename = {:?})]", variant)?;
+            }
+            match ty {
+                Type::Tuple(elems) if elems.is_empty() => writeln!(item, "    {},", ident)?,
+                Type::Tuple(elems) => {
+                    let elems = elems
+                        .iter()
+                        .map(|elem| self.rust_type(elem, variant))
+                        .collect::<Result<Vec<_>>>()?;
+                    writeln!(item, "    {}({}),", ident, elems.join(", "))?;
+                }
+                Type::Record(fields) => {
+                    writeln!(item, "    {} {{", ident)?;
+                    for (field, ty) in fields {
+                        let ty = self.rust_type(ty, field)?;
+                        let field_ident = to_snake_case(field);
+                        if to_camel_case(&field_ident) != *field {
+                            writeln!(item, "        #[quint(rename = {:?})]", field)?;
+                        }
+                        writeln!(item, "        {}: {},", to_ident(&field_ident)?, ty)?;
+                    }
+                    writeln!(item, "    }},")?;
+                }
+                other => {
+                    let ty = self.rust_type(other, variant)?;
+                    writeln!(item, "    {}({}),", ident, ty)?;
+                }
+            }
+        }
+        item.push_str("}\n");
+        Ok(item)
+    }
+
+    /// The Rust type for a Quint type, rendering the definitions it refers to.
+    /// Anonymous records are rendered as structs named after `context`, the name
+    /// of the field or variable they are the type of.
+    fn rust_type(&mut self, ty: &Type, context: &str) -> Result<String> {
+        Ok(match ty {
+            Type::Bool => "bool".to_string(),
+            Type::Int => "i64".to_string(),
+            Type::Str => "::std::string::String".to_string(),
+            Type::Var(name) => to_pascal_case(name),
+            Type::Const(name) => {
+                let Some(typedef) = self.find(name) else {
+                    bail!("Type `{}` not found", name)
+                };
+                if typedef.ty.is_none() {
+                    return Ok("::std::string::String".to_string());
+                }
+                if !typedef.params.is_empty() {
+                    bail!("Type `{}` is missing its type arguments", name)
+                }
+                self.typedef(name)?;
+                self.rust_name(name)
+            }
+            Type::App(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.rust_type(arg, context))
+                    .collect::<Result<Vec<_>>>()?;
+                if is_option(name) {
+                    format!("::std::option::Option<{}>", args.join(", "))
+                } else {
+                    self.typedef(name)?;
+                    format!("{}<{}>", self.rust_name(name), args.join(", "))
+                }
+            }
+            Type::Set(elem) => format!(
+                "::std::collections::BTreeSet<{}>",
+                self.rust_type(elem, context)?
+            ),
+            Type::List(elem) => format!("::std::vec::Vec<{}>", self.rust_type(elem, context)?),
+            Type::Map(key, value) => format!(
+                "::std::collections::BTreeMap<{}, {}>",
+                self.rust_type(key, context)?,
+                self.rust_type(value, context)?
+            ),
+            Type::Tuple(elems) => {
+                let elems = elems
+                    .iter()
+                    .map(|elem| self.rust_type(elem, context))
+                    .collect::<Result<Vec<_>>>()?;
+                match elems.len() {
+                    1 => format!("({},)", elems[0]),
+                    _ => format!("({})", elems.join(", ")),
+                }
+            }
+            Type::Record(fields) => {
+                let mut vars = Vec::new();
+                ty.vars(&mut vars);
+                if !vars.is_empty() {
+                    bail!(
+                        "Record type of `{}` can't have type variables, please declare it with `type`",
+                        context
+                    )
+                }
+                let name = self.claim(&to_pascal_case(context))?;
+                let item = self.record(&name, "", fields)?;
+                self.items.push(item);
+                name
+            }
+            Type::Sum(_) => bail!("Sum type of `{}` must be declared with `type`", context),
+        })
+    }
+
+    fn rust_name(&self, name: &str) -> String {
+        self.rendered[name].clone()
+    }
+}
+
+fn is_option(name: &str) -> bool {
+    name == "Option"
+}
+
+fn render_generics(params: &[String]) -> String {
+    if params.is_empty() {
+        return String::new();
+    }
+    let params: Vec<_> = params.iter().map(|param| to_pascal_case(param)).collect();
+    format!("<{}>", params.join(", "))
+}
+
+fn to_ident(name: &str) -> Result<String> {
+    if RESERVED.contains(&name) {
+        bail!("`{}` can't be used as a Rust identifier", name)
+    } else if KEYWORDS.contains(&name) {
+        Ok(format!("r#{}", name))
+    } else {
+        Ok(name.to_string())
+    }
+}
+
+/// Converts a Quint name into `PascalCase`, e.g. `procState` into `ProcState`
+/// and `PROC` into `Proc`.
+pub(crate) fn to_pascal_case(name: &str) -> String {
+    let name = if name.chars().any(char::is_lowercase) {
+        name.to_string()
+    } else {
+        name.to_lowercase()
+    };
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .into_iter()
+                .flat_map(char::to_uppercase)
+                .chain(chars)
+                .collect::<String>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_naming() {
+        assert_eq!(to_pascal_case("procState"), "ProcState");
+        assert_eq!(to_pascal_case("PROC"), "Proc");
+        assert_eq!(to_pascal_case("a"), "A");
+        assert_eq!(to_pascal_case("msg_kind"), "MsgKind");
+        assert_eq!(to_snake_case("nextTurn"), "next_turn");
+        assert_eq!(to_snake_case("HTTPCode"), "http_code");
+        assert_eq!(to_camel_case("http_code"), "httpCode");
+        assert_eq!(to_ident("type").unwrap(), "r#type");
+        assert_eq!(to_ident("virtual").unwrap(), "r#virtual");
+        assert!(to_ident("self").is_err());
+        assert!(to_ident("Self").is_err());
+    }
+}
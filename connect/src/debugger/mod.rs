@@ -0,0 +1,81 @@
+#[cfg(any(feature = "debugger", test))]
+mod session;
+#[cfg(feature = "debugger")]
+mod ui;
+
+use crate::{Driver, settings, trace::Trace};
+use anyhow::Result;
+use std::path::PathBuf;
+
+/// How the debugger is triggered, as configured by `QUINT_DEBUG`.
+#[derive(PartialEq, Eq, Debug)]
+enum Mode {
+    /// The debugger is disabled.
+    Off,
+    /// The debugger opens on the first failing trace.
+    OnFailure,
+    /// The debugger opens on a trace loaded from an ITF file, skipping trace
+    /// generation.
+    Trace(PathBuf),
+}
+
+fn parse_mode(var: Option<&str>) -> Mode {
+    match var.map(str::trim) {
+        None | Some("" | "0" | "false" | "off") => Mode::Off,
+        Some("1" | "true" | "on") => Mode::OnFailure,
+        Some(path) => Mode::Trace(PathBuf::from(path)),
+    }
+}
+
+/// Whether the debugger should open when a trace fails.
+pub(crate) fn on_failure() -> bool {
+    parse_mode(settings::var("QUINT_DEBUG").as_deref()) == Mode::OnFailure
+}
+
+/// The ITF file to open in the debugger, if any.
+pub(crate) fn trace_file() -> Option<PathBuf> {
+    match parse_mode(settings::var("QUINT_DEBUG").as_deref()) {
+        Mode::Trace(path) => Some(path),
+        _ => None,
+    }
+}
+
+/// Opens the interactive debugger on the given trace.
+#[cfg(feature = "debugger")]
+pub(crate) fn open<D, F>(new_driver: &F, trace: &Trace, t: usize) -> Result<()>
+where
+    D: Driver,
+    F: Fn() -> D,
+{
+    ui::run(session::Session::new(new_driver, trace, t))
+}
+
+/// Opens the interactive debugger on the given trace.
+#[cfg(not(feature = "debugger"))]
+pub(crate) fn open<D, F>(_new_driver: &F, _trace: &Trace, _t: usize) -> Result<()>
+where
+    D: Driver,
+    F: Fn() -> D,
+{
+    anyhow::bail!(
+        "The trace debugger is not available.\n\
+         Please enable the `debugger` feature of `quint-connect`."
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(parse_mode(None), Mode::Off);
+        assert_eq!(parse_mode(Some("0")), Mode::Off);
+        assert_eq!(parse_mode(Some("1")), Mode::OnFailure);
+        assert_eq!(parse_mode(Some("true")), Mode::OnFailure);
+        assert_eq!(
+            parse_mode(Some("target/quint-connect/test.itf.json")),
+            Mode::Trace(PathBuf::from("target/quint-connect/test.itf.json"))
+        );
+    }
+}
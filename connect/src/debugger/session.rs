@@ -0,0 +1,402 @@
+use crate::{
+    Driver, State, Step,
+    runner::{derive_steps, diff_states},
+    trace::Trace,
+};
+use anyhow::{Result, anyhow};
+use std::{
+    cell::Cell,
+    collections::BTreeSet,
+    panic::{self, AssertUnwindSafe},
+    sync::Once,
+};
+
+/// The outcome of executing a single step against the driver.
+#[derive(Default)]
+pub(crate) struct StepView {
+    /// The implementation state after the step, if it could be extracted.
+    pub impl_state: Option<String>,
+    /// The diff between specification and implementation states, if they diverge.
+    pub diff: Option<String>,
+    /// The error raised while executing the step, if any.
+    pub error: Option<String>,
+}
+
+impl StepView {
+    pub fn failed(&self) -> bool {
+        self.diff.is_some() || self.error.is_some()
+    }
+}
+
+/// A debugging session over a single trace.
+///
+/// Steps are executed lazily and forward-only against a driver. Selecting a step
+/// executes all steps up to it, while going backwards only selects steps whose
+/// results were cached already. Resetting the session re-executes the trace from
+/// the start using a fresh driver.
+pub(crate) struct Session<'a, D, F> {
+    new_driver: &'a F,
+    steps: Vec<Result<Step, String>>,
+    views: Vec<StepView>,
+    driver: Option<D>,
+    halted: bool,
+    selected: usize,
+    breakpoints: BTreeSet<String>,
+}
+
+impl<'a, D, F> Session<'a, D, F>
+where
+    D: Driver,
+    F: Fn() -> D,
+{
+    pub fn new(new_driver: &'a F, trace: &Trace, t: usize) -> Self {
+        let ann = D::config();
+        let steps = derive_steps(trace, t, &ann)
+            .map(|step| step.map_err(|err| format!("{:?}", err)))
+            .collect();
+
+        let mut session = Self {
+            new_driver,
+            steps,
+            views: Vec::new(),
+            driver: None,
+            halted: false,
+            selected: 0,
+            breakpoints: BTreeSet::new(),
+        };
+        session.reset();
+        session
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// The step at the given index, or the error raised while deriving it.
+    pub fn step(&self, index: usize) -> Result<&Step, &str> {
+        self.steps[index].as_ref().map_err(String::as_str)
+    }
+
+    pub fn action(&self, index: usize) -> &str {
+        match &self.steps[index] {
+            Ok(step) => &step.action_taken,
+            Err(_) => "<invalid>",
+        }
+    }
+
+    /// The result of executing the step at the given index, if it was executed.
+    pub fn view(&self, index: usize) -> Option<&StepView> {
+        self.views.get(index)
+    }
+
+    /// Whether execution stopped before the end of the trace due to a failure.
+    pub fn halted(&self) -> bool {
+        self.halted
+    }
+
+    pub fn has_breakpoint(&self, index: usize) -> bool {
+        self.breakpoints.contains(self.action(index))
+    }
+
+    /// Toggles a breakpoint on the action of the selected step.
+    pub fn toggle_breakpoint(&mut self) {
+        if self.steps.is_empty() {
+            return;
+        }
+        let action = self.action(self.selected).to_string();
+        if !self.breakpoints.remove(&action) {
+            self.breakpoints.insert(action);
+        }
+    }
+
+    /// Selects the given step, executing all steps up to it if needed. Selection
+    /// stops at the first failed step since the driver can not make progress past it.
+    pub fn select(&mut self, index: usize) {
+        let index = index.min(self.len().saturating_sub(1));
+        self.execute_until(index);
+        self.selected = index.min(self.views.len().saturating_sub(1));
+    }
+
+    pub fn next(&mut self) {
+        self.select(self.selected + 1);
+    }
+
+    pub fn prev(&mut self) {
+        self.select(self.selected.saturating_sub(1));
+    }
+
+    pub fn first(&mut self) {
+        self.select(0);
+    }
+
+    pub fn last(&mut self) {
+        self.select(self.len());
+    }
+
+    /// Advances to the next step with a breakpoint, the first failed step, or the
+    /// end of the trace.
+    pub fn resume(&mut self) {
+        loop {
+            let current = self.selected;
+            self.next();
+            if self.selected == current
+                || self.has_breakpoint(self.selected)
+                || self.views[self.selected].failed()
+            {
+                break;
+            }
+        }
+    }
+
+    /// Discards all executed steps and re-executes the trace up to the selected step
+    /// with a fresh driver.
+    pub fn reset(&mut self) {
+        self.views.clear();
+        self.driver = None;
+        self.halted = false;
+        let selected = self.selected;
+        self.selected = 0;
+        self.select(selected);
+    }
+
+    fn execute_until(&mut self, index: usize) {
+        while self.views.len() <= index && !self.halted && self.views.len() < self.len() {
+            let view = self.execute_next();
+            self.halted = view.error.is_some();
+            self.views.push(view);
+        }
+    }
+
+    fn execute_next(&mut self) -> StepView {
+        let step = match &self.steps[self.views.len()] {
+            Ok(step) => step,
+            Err(err) => {
+                return StepView {
+                    error: Some(err.clone()),
+                    ..StepView::default()
+                };
+            }
+        };
+
+        let new_driver = self.new_driver;
+        let driver = self.driver.get_or_insert_with(new_driver);
+        match silence_panics(|| execute(driver, step)) {
+            Ok(view) => view,
+            Err(err) => StepView {
+                error: Some(format!("{:?}", err)),
+                ..StepView::default()
+            },
+        }
+    }
+}
+
+fn execute<D: Driver>(driver: &mut D, step: &Step) -> Result<StepView> {
+    driver.step(step)?;
+
+    let spec_state = D::State::from_spec(step.state.clone())?;
+    let driver_state = D::State::from_driver(driver)?;
+    let diff = (!spec_state.matches(&driver_state))
+        .then(|| diff_states::<D, _>(&spec_state, &driver_state));
+
+    Ok(StepView {
+        impl_state: Some(format!("{:#?}", driver_state)),
+        diff,
+        error: None,
+    })
+}
+
+thread_local! {
+    static SILENCED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs the given function, turning panics into errors without printing them to
+/// the terminal, which is owned by the debugger UI.
+///
+/// The panic hook is global, so it is replaced only once, by one that stays
+/// silent on threads running this function and defers to the previous hook
+/// otherwise.
+fn silence_panics<T>(f: impl FnOnce() -> Result<T>) -> Result<T> {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !SILENCED.get() {
+                hook(info);
+            }
+        }));
+    });
+
+    let silenced = SILENCED.replace(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    SILENCED.set(silenced);
+
+    result.unwrap_or_else(|panic| {
+        let msg = panic
+            .downcast_ref::<&str>()
+            .map(|msg| msg.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "<unknown>".to_string());
+        Err(anyhow!("Driver panicked: {}", msg))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::Config;
+    use itf::{
+        State as ItfState, Trace as ItfTrace,
+        value::{Record, Value},
+    };
+    use serde::Deserialize;
+
+    #[derive(PartialEq, Deserialize, Debug)]
+    struct Counter {
+        n: i64,
+    }
+
+    impl State<CounterDriver> for Counter {
+        fn from_driver(driver: &CounterDriver) -> Result<Self> {
+            Ok(Counter { n: driver.n })
+        }
+    }
+
+    #[derive(Default)]
+    struct CounterDriver {
+        n: i64,
+    }
+
+    impl Driver for CounterDriver {
+        type State = Counter;
+
+        fn config() -> Config {
+            Config {
+                state: &["counter"],
+                ..Config::default()
+            }
+        }
+
+        fn step(&mut self, step: &Step) -> crate::Result {
+            match step.action_taken.as_str() {
+                "init" => self.n = 0,
+                "inc" => self.n += 1,
+                "bug" => self.n += 2,
+                "crash" => panic!("boom"),
+                _ => {}
+            }
+            Ok(())
+        }
+    }
+
+    fn trace(actions: &[&str]) -> Trace {
+        let mut n = 0;
+        let states = actions
+            .iter()
+            .map(|action| {
+                n = if *action == "init" { 0 } else { n + 1 };
+                let mut counter = Record::new();
+                counter.insert("n".to_string(), Value::Number(n));
+                let mut state = Record::new();
+                state.insert(
+                    "mbt::actionTaken".to_string(),
+                    Value::String(action.to_string()),
+                );
+                state.insert("mbt::nondetPicks".to_string(), Value::Record(Record::new()));
+                state.insert("counter".to_string(), Value::Record(counter));
+                ItfState {
+                    meta: Default::default(),
+                    value: Value::Record(state),
+                }
+            })
+            .collect();
+
+        ItfTrace {
+            meta: Default::default(),
+            params: Vec::new(),
+            vars: Vec::new(),
+            loop_index: None,
+            states,
+        }
+    }
+
+    #[test]
+    fn test_steps_are_executed_lazily() {
+        let trace = trace(&["init", "inc", "inc"]);
+        let session = Session::new(&CounterDriver::default, &trace, 1);
+        assert_eq!(session.len(), 3);
+        assert!(session.view(0).is_some());
+        assert!(session.view(1).is_none());
+    }
+
+    #[test]
+    fn test_navigation() {
+        let trace = trace(&["init", "inc", "inc"]);
+        let mut session = Session::new(&CounterDriver::default, &trace, 1);
+
+        session.last();
+        assert_eq!(session.selected(), 2);
+        assert_eq!(session.step(2).unwrap().action_taken, "inc");
+        assert_eq!(
+            session.view(2).unwrap().impl_state.as_deref(),
+            Some("Counter {\n    n: 2,\n}")
+        );
+
+        session.prev();
+        session.prev();
+        session.prev();
+        assert_eq!(session.selected(), 0);
+
+        session.reset();
+        assert_eq!(session.selected(), 0);
+        assert!(session.view(1).is_none());
+    }
+
+    #[test]
+    fn test_divergence_shows_diff() {
+        let trace = trace(&["init", "bug", "inc"]);
+        let mut session = Session::new(&CounterDriver::default, &trace, 1);
+
+        session.resume();
+        assert_eq!(session.selected(), 1);
+        let view = session.view(1).unwrap();
+        assert!(view.failed());
+        assert_eq!(view.diff.as_deref(), Some("  n: 1 → 2"));
+        assert!(!session.halted());
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let trace = trace(&["init", "inc", "inc", "inc"]);
+        let mut session = Session::new(&CounterDriver::default, &trace, 1);
+
+        session.select(1);
+        session.toggle_breakpoint();
+        session.first();
+        session.resume();
+        assert_eq!(session.selected(), 1);
+        session.resume();
+        assert_eq!(session.selected(), 2);
+        assert!(session.has_breakpoint(3));
+
+        session.toggle_breakpoint();
+        session.first();
+        session.resume();
+        assert_eq!(session.selected(), 3);
+    }
+
+    #[test]
+    fn test_panics_halt_execution() {
+        let trace = trace(&["init", "crash", "inc"]);
+        let mut session = Session::new(&CounterDriver::default, &trace, 1);
+
+        session.last();
+        assert_eq!(session.selected(), 1);
+        assert!(session.halted());
+        let error = session.view(1).unwrap().error.as_deref().unwrap();
+        assert!(error.contains("Driver panicked: boom"));
+    }
+}
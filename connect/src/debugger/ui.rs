@@ -0,0 +1,195 @@
+use crate::{Driver, debugger::session::Session};
+use anyhow::{Context, Result};
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::{
+        event::{self, Event, KeyCode, KeyEventKind},
+        execute,
+        terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+    },
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, List, ListItem, ListState, Paragraph, Wrap},
+};
+use std::io::stdout;
+
+const HELP: &str = " j/k: step  g/G: first/last  c: continue  b: breakpoint  \
+                     r: restart  PgUp/PgDn: scroll  q: quit ";
+
+/// Runs the debugger UI until the user quits.
+pub(super) fn run<D, F>(mut session: Session<D, F>) -> Result<()>
+where
+    D: Driver,
+    F: Fn() -> D,
+{
+    // The terminal is set up manually rather than with `ratatui::init` so that no
+    // panic hook is installed: driver panics are reported by the session instead.
+    enable_raw_mode().context("Failed to enable raw mode")?;
+    execute!(stdout(), EnterAlternateScreen).context("Failed to enter alternate screen")?;
+    let result = DefaultTerminal::new(ratatui::backend::CrosstermBackend::new(stdout()))
+        .context("Failed to initialize terminal")
+        .and_then(|mut terminal| event_loop(&mut terminal, &mut session));
+    let _ = execute!(stdout(), LeaveAlternateScreen);
+    let _ = disable_raw_mode();
+    result
+}
+
+fn event_loop<D, F>(terminal: &mut DefaultTerminal, session: &mut Session<D, F>) -> Result<()>
+where
+    D: Driver,
+    F: Fn() -> D,
+{
+    let mut scroll = 0u16;
+    loop {
+        terminal.draw(|frame| draw(frame, session, scroll))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        let selected = session.selected();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Char('j') | KeyCode::Down => session.next(),
+            KeyCode::Char('k') | KeyCode::Up => session.prev(),
+            KeyCode::Char('g') | KeyCode::Home => session.first(),
+            KeyCode::Char('G') | KeyCode::End => session.last(),
+            KeyCode::Char('c') => session.resume(),
+            KeyCode::Char('b') => session.toggle_breakpoint(),
+            KeyCode::Char('r') => session.reset(),
+            KeyCode::PageDown => scroll = scroll.saturating_add(10),
+            KeyCode::PageUp => scroll = scroll.saturating_sub(10),
+            _ => {}
+        }
+        if session.selected() != selected {
+            scroll = 0;
+        }
+    }
+}
+
+fn draw<D, F>(frame: &mut Frame, session: &Session<D, F>, scroll: u16)
+where
+    D: Driver,
+    F: Fn() -> D,
+{
+    let [main, status] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+    let [steps, details] =
+        Layout::horizontal([Constraint::Length(32), Constraint::Min(0)]).areas(main);
+
+    draw_steps(frame, session, steps);
+    draw_details(frame, session, details, scroll);
+
+    let status_text = if session.halted() {
+        format!(
+            "{}| halted: the driver can not run past the failed step ",
+            HELP
+        )
+    } else {
+        HELP.to_string()
+    };
+    frame.render_widget(
+        Paragraph::new(status_text).style(Style::new().add_modifier(Modifier::REVERSED)),
+        status,
+    );
+}
+
+fn draw_steps<D, F>(frame: &mut Frame, session: &Session<D, F>, area: Rect)
+where
+    D: Driver,
+    F: Fn() -> D,
+{
+    let items: Vec<_> = (0..session.len())
+        .map(|index| {
+            let (marker, style) = match session.view(index) {
+                Some(view) if view.failed() => ("✗", Style::new().fg(Color::Red)),
+                Some(_) => ("✓", Style::new().fg(Color::Green)),
+                None => (" ", Style::new().fg(Color::DarkGray)),
+            };
+            let breakpoint = if session.has_breakpoint(index) {
+                Span::styled("●", Style::new().fg(Color::Red))
+            } else {
+                Span::raw(" ")
+            };
+            ListItem::new(Line::from(vec![
+                breakpoint,
+                Span::styled(marker, style),
+                Span::raw(format!(" {:>3} {}", index, session.action(index))),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::bordered().title(" Trace "))
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(Some(session.selected()));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_details<D, F>(frame: &mut Frame, session: &Session<D, F>, area: Rect, scroll: u16)
+where
+    D: Driver,
+    F: Fn() -> D,
+{
+    if session.len() == 0 {
+        let empty = Paragraph::new("The trace has no states.").block(Block::bordered());
+        frame.render_widget(empty, area);
+        return;
+    }
+
+    let index = session.selected();
+    let step = match session.step(index) {
+        Ok(step) => step.to_string(),
+        Err(err) => err.to_string(),
+    };
+    let view = session.view(index);
+    let impl_state = view
+        .and_then(|view| view.impl_state.clone())
+        .unwrap_or_else(|| "<not available>".to_string());
+
+    let diff = match view {
+        None => Text::raw("Not executed."),
+        Some(view) => match (&view.error, &view.diff) {
+            (Some(error), _) => Text::styled(error.clone(), Style::new().fg(Color::Red)),
+            (None, Some(diff)) => diff_text(diff),
+            (None, None) => Text::styled("States match.", Style::new().fg(Color::Green)),
+        },
+    };
+
+    let [top, bottom] =
+        Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(area);
+    let [spec, imp] =
+        Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(top);
+
+    let panel = |title: String, text: Text<'static>| {
+        Paragraph::new(text)
+            .block(Block::bordered().title(title))
+            .wrap(Wrap { trim: false })
+            .scroll((scroll, 0))
+    };
+    frame.render_widget(panel(format!(" Step {} ", index), Text::raw(step)), spec);
+    frame.render_widget(
+        panel(" Implementation state ".to_string(), Text::raw(impl_state)),
+        imp,
+    );
+    frame.render_widget(panel(" Diff ".to_string(), diff), bottom);
+}
+
+fn diff_text(diff: &str) -> Text<'static> {
+    diff.lines()
+        .map(|line| {
+            let style = if line.starts_with('+') && !line.starts_with("+++") {
+                Style::new().fg(Color::Green)
+            } else if line.starts_with('-') && !line.starts_with("---") {
+                Style::new().fg(Color::Red)
+            } else {
+                Style::new()
+            };
+            Line::styled(line.to_string(), style)
+        })
+        .collect()
+}
@@ -0,0 +1,345 @@
+use crate::{
+    driver::{Config, Result, Step},
+    value::to_value,
+};
+use anyhow::{Context, bail};
+use itf::{
+    State as ItfState, Trace as ItfTrace, Value,
+    value::{Map, Record},
+};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// Builds a [`Step`] by hand, e.g. to unit-test a driver's handlers without
+/// running Quint.
+///
+/// Picks and spec variables can be given as any serializable Rust value, which is
+/// encoded as Quint would, or with the `_itf` methods as [`serde_json::Value`]s in
+/// the ITF format of Quint's traces, e.g. `json!({ "#set": [1, 2] })`. Errors are
+/// reported by [`build`](Self::build).
+///
+/// # Examples
+///
+/// ```rust
+/// use quint_connect::*;
+/// use serde_json::json;
+///
+/// # fn main() -> Result {
+/// let step = Step::builder("MoveX")
+///     .pick("corner", (1, 1))
+///     .state_itf(json!({ "nextTurn": { "tag": "O", "value": { "#tup": [] } } }))
+///     .build()?;
+///
+/// assert_eq!(step.action(), "MoveX");
+/// assert_eq!(step.pick::<(u64, u64)>("corner")?, (1, 1));
+/// # Ok(())
+/// # }
+/// ```
+pub struct StepBuilder {
+    action: String,
+    picks: Record,
+    vars: Record,
+    error: Option<anyhow::Error>,
+}
+
+impl Step {
+    /// Starts building a step taking the given action. See [`StepBuilder`].
+    pub fn builder(action: impl Into<String>) -> StepBuilder {
+        StepBuilder {
+            action: action.into(),
+            picks: Record::new(),
+            vars: Record::new(),
+            error: None,
+        }
+    }
+}
+
+impl StepBuilder {
+    /// Adds a nondeterministic pick made by the action. An [`Option`] adds the pick
+    /// only if it's `Some`, like Quint does for nondets the action didn't pick.
+    pub fn pick(self, name: &str, value: impl Serialize) -> Self {
+        self.add_pick(name, to_value(&value))
+    }
+
+    /// Adds a nondeterministic pick given as ITF JSON, e.g. `json!({ "#tup": [1, 2] })`.
+    pub fn pick_itf(self, name: &str, value: serde_json::Value) -> Self {
+        self.add_pick(name, from_itf(value))
+    }
+
+    /// Sets a spec variable of the state after the step.
+    pub fn var(self, name: &str, value: impl Serialize) -> Self {
+        self.add_var(name, to_value(&value))
+    }
+
+    /// Sets a spec variable of the state after the step given as ITF JSON, e.g.
+    /// `json!({ "#set": [1, 2] })`.
+    pub fn var_itf(self, name: &str, value: serde_json::Value) -> Self {
+        self.add_var(name, from_itf(value))
+    }
+
+    /// Sets the spec variables of the state after the step from a struct or a map
+    /// with an entry per variable.
+    pub fn state(self, vars: impl Serialize) -> Self {
+        self.add_vars(to_value(&vars))
+    }
+
+    /// Sets the spec variables of the state after the step from an ITF JSON object
+    /// with a field per variable.
+    pub fn state_itf(self, vars: serde_json::Value) -> Self {
+        self.add_vars(from_itf(vars))
+    }
+
+    /// Builds the step. Its state for [`Driver::State`](crate::Driver::State) is
+    /// the whole record of spec variables.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error raised while encoding picks and variables.
+    pub fn build(self) -> Result<Step> {
+        Step::new(self.into_state()?, &Config::default())
+    }
+
+    fn add_pick(mut self, name: &str, value: Result<Value>) -> Self {
+        match value.with_context(|| format!("Failed to encode nondet pick `{}`", name)) {
+            Ok(value) => {
+                self.picks.insert(name.to_string(), as_option(value));
+            }
+            Err(err) => self.fail(err),
+        }
+        self
+    }
+
+    fn add_var(mut self, name: &str, value: Result<Value>) -> Self {
+        match value.with_context(|| format!("Failed to encode spec variable `{}`", name)) {
+            Ok(value) => {
+                self.vars.insert(name.to_string(), value);
+            }
+            Err(err) => self.fail(err),
+        }
+        self
+    }
+
+    fn add_vars(mut self, vars: Result<Value>) -> Self {
+        match vars.and_then(into_record) {
+            Ok(vars) => {
+                for (name, value) in vars {
+                    self.vars.insert(name, value);
+                }
+            }
+            Err(err) => self.fail(err.context("Failed to encode spec state")),
+        }
+        self
+    }
+
+    fn fail(&mut self, err: anyhow::Error) {
+        self.error.get_or_insert(err);
+    }
+
+    /// The trace state of the step, with Quint's `mbt::` variables.
+    fn into_state(self) -> Result<Record> {
+        if let Some(err) = self.error {
+            return Err(err.context(format!("Failed to build step `{}`", self.action)));
+        }
+        let mut state = self.vars;
+        state.insert("mbt::actionTaken".to_string(), Value::String(self.action));
+        state.insert("mbt::nondetPicks".to_string(), Value::Record(self.picks));
+        Ok(state)
+    }
+}
+
+/// Builds an ITF trace by hand, e.g. a regression scenario to [`replay`](crate::replay)
+/// against a driver without running Quint.
+///
+/// Steps are built with [`Step::builder`]. Traces use Quint's `mbt::actionTaken`
+/// and `mbt::nondetPicks` variables, and can be saved as ITF JSON with
+/// `serde_json`, e.g. to replay them with `QUINT_REPLAY`.
+///
+/// # Examples
+///
+/// ```rust
+/// use quint_connect::*;
+///
+/// # fn main() -> Result {
+/// let trace = TraceBuilder::new()
+///     .step(Step::builder("init").var("count", 0))
+///     .step(Step::builder("add").pick("amount", 2).var("count", 2))
+///     .build()?;
+///
+/// assert_eq!(trace.states.len(), 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct TraceBuilder {
+    steps: Vec<StepBuilder>,
+}
+
+impl TraceBuilder {
+    /// Creates a builder for an empty trace.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a step to the trace.
+    pub fn step(mut self, step: StepBuilder) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Builds the trace.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error raised while encoding the picks and variables of a
+    /// step.
+    pub fn build(self) -> Result<ItfTrace<Value>> {
+        let mut vars = BTreeSet::new();
+        let mut states = Vec::new();
+        for (i, step) in self.steps.into_iter().enumerate() {
+            let state = step
+                .into_state()
+                .with_context(|| format!("Failed to build step {}", i))?;
+            vars.extend(state.iter().map(|(name, _)| name.clone()));
+            states.push(ItfState {
+                meta: Default::default(),
+                value: Value::Record(state),
+            });
+        }
+        Ok(ItfTrace {
+            meta: Default::default(),
+            params: Vec::new(),
+            vars: vars.into_iter().collect(),
+            loop_index: None,
+            states,
+        })
+    }
+}
+
+/// Reads a value written as ITF JSON.
+fn from_itf(json: serde_json::Value) -> Result<Value> {
+    Ok(serde_json::from_value(json)?)
+}
+
+fn into_record(value: Value) -> Result<Record> {
+    match value {
+        Value::Record(rec) => Ok(rec),
+        Value::Map(map) => string_keys(map),
+        _ => bail!("Expected a record of spec variables"),
+    }
+}
+
+/// Turns a map with string keys, such as a `HashMap<String, _>`, into a record.
+fn string_keys(map: Map<Value, Value>) -> Result<Record> {
+    let mut rec = Record::new();
+    for (key, value) in map {
+        let Value::String(key) = key else {
+            bail!("Expected spec variable names to be strings")
+        };
+        rec.insert(key, value);
+    }
+    Ok(rec)
+}
+
+/// Wraps a pick into a Quint option, as in Quint's traces, unless it already is
+/// one.
+fn as_option(value: Value) -> Value {
+    if let Value::Record(rec) = &value
+        && let Some(Value::String(tag)) = rec.get("tag")
+        && (tag == "Some" || tag == "None")
+    {
+        return value;
+    }
+    let mut rec = Record::new();
+    rec.insert("tag".to_string(), Value::String("Some".to_string()));
+    rec.insert("value".to_string(), value);
+    Value::Record(rec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    struct Bank {
+        balances: BTreeMap<String, u64>,
+    }
+
+    #[test]
+    fn test_build_step() {
+        let bank = Bank {
+            balances: [("alice".to_string(), 10)].into(),
+        };
+        let step = Step::builder("Deposit")
+            .pick("account", "alice")
+            .pick("memo", None::<String>)
+            .state(bank)
+            .var_itf("open", json!({ "#set": ["alice"] }))
+            .pick_itf("range", json!({ "#tup": [1, 3] }))
+            .build()
+            .unwrap();
+
+        assert_eq!(step.action(), "Deposit");
+        assert_eq!(step.pick::<String>("account").unwrap(), "alice");
+        assert_eq!(step.try_pick::<String>("memo").unwrap(), None);
+        assert_eq!(
+            step.pick::<String>("memo").unwrap_err().to_string(),
+            "Nondet pick `memo` is `None` in action `Deposit`"
+        );
+
+        let balances: BTreeMap<String, u64> = step.spec_var(&["balances"]).unwrap();
+        assert_eq!(balances["alice"], 10);
+        let open: BTreeSet<String> = step.spec_var(&["open"]).unwrap();
+        assert!(open.contains("alice"));
+        assert_eq!(step.pick::<(u64, u64)>("range").unwrap(), (1, 3));
+    }
+
+    #[test]
+    fn test_build_trace() {
+        let trace = TraceBuilder::new()
+            .step(Step::builder("init").state_itf(json!({ "count": 0 })))
+            .step(Step::builder("add").pick("amount", 2).var("count", 2))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            trace.vars,
+            ["count", "mbt::actionTaken", "mbt::nondetPicks"]
+        );
+        let steps: Vec<_> = trace
+            .states
+            .into_iter()
+            .map(|state| match state.value {
+                Value::Record(rec) => Step::new(rec, &Config::default()).unwrap(),
+                other => panic!("expected a record, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(steps[1].pick::<u64>("amount").unwrap(), 2);
+        assert_eq!(steps[1].spec_var::<u64>(&["count"]).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let err = TraceBuilder::new()
+            .step(Step::builder("init"))
+            .step(Step::builder("add").pick("amount", 1.5).var("count", 2))
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "Failed to build step 1: Failed to build step `add`: \
+             Failed to encode nondet pick `amount`: \
+             Can not serialize floating-point number 1.5 into a Quint value"
+        );
+
+        let Err(err) = Step::builder("init").state(42).build() else {
+            panic!("expected an error")
+        };
+        assert_eq!(
+            format!("{:#}", err),
+            "Failed to build step `init`: Failed to encode spec state: \
+             Expected a record of spec variables"
+        );
+    }
+}
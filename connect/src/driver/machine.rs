@@ -0,0 +1,92 @@
+use crate::driver::{Config, Driver, Result, Sequence, State, Step};
+
+/// Trait for types decoded from the action taken by a [`Step`] and its
+/// nondeterministic picks.
+///
+/// Implement this trait with [`#[derive(QuintAction)]`](crate::QuintAction) on an
+/// enum with a variant per action, and decode steps with [`Step::decode_action`].
+pub trait Action: Sized {
+    /// Decodes the action taken by the step.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the step's action is not a variant of this type, or if
+    /// its nondeterministic picks can't be decoded into the variant's fields.
+    fn from_step(step: &Step) -> Result<Self>;
+}
+
+/// A [`Driver`] that applies typed actions instead of matching on steps.
+///
+/// Every type implementing this trait is a [`Driver`] that decodes each step into
+/// its [`Action`](StateMachine::Action) type and passes it to
+/// [`apply`](StateMachine::apply). Matching on the action enum lets the compiler
+/// check that every action is handled, and the enum can be reused elsewhere, e.g.
+/// in property tests.
+///
+/// # Examples
+///
+/// ```rust
+/// use quint_connect::*;
+///
+/// #[derive(QuintAction, Debug)]
+/// enum CounterAction {
+///     Init,
+///     Add { amount: u64 },
+/// }
+///
+/// #[derive(Default)]
+/// struct Counter {
+///     count: u64,
+/// }
+///
+/// impl StateMachine for Counter {
+///     type State = ();
+///     type Action = CounterAction;
+///
+///     fn apply(&mut self, action: CounterAction) -> Result {
+///         match action {
+///             CounterAction::Init => self.count = 0,
+///             CounterAction::Add { amount } => self.count += amount,
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait StateMachine: Sized {
+    /// The state type that can be extracted from both the machine and the
+    /// specification. See [`Driver::State`].
+    type State: State<Self>;
+
+    /// The actions of the specification.
+    type Action: Action;
+
+    /// Applies an action decoded from a step of a Quint trace.
+    fn apply(&mut self, action: Self::Action) -> Result;
+
+    /// Returns configuration for this machine. See [`Driver::config`].
+    fn config() -> Config {
+        Config::default()
+    }
+
+    /// Returns how steps map onto a sequence diagram. See [`Driver::sequence`].
+    fn sequence() -> Option<Sequence> {
+        None
+    }
+}
+
+impl<M: StateMachine> Driver for M {
+    type State = M::State;
+
+    fn step(&mut self, step: &Step) -> Result {
+        let action = step.decode_action::<M::Action>()?;
+        self.apply(action)
+    }
+
+    fn config() -> Config {
+        M::config()
+    }
+
+    fn sequence() -> Option<Sequence> {
+        M::sequence()
+    }
+}
@@ -1,8 +1,12 @@
+mod builder;
+mod machine;
 mod nondet;
 mod state;
 mod step;
 
-pub use state::State;
+pub use builder::{StepBuilder, TraceBuilder};
+pub use machine::{Action, StateMachine};
+pub use state::{Projected, Projection, State};
 pub use step::Step;
 
 /// A convenience type alias for [`anyhow::Result`] used throughout this crate.
@@ -87,6 +91,101 @@ pub struct Config {
     pub nondet: Path,
 }
 
+/// Describes how the steps of a trace map onto a sequence diagram.
+///
+/// With [`Sequence::Picks`], each step is drawn as a message sent by the
+/// participant found in the `participant` nondeterministic pick, optionally to the
+/// participant found in the `receiver` pick. Messages are labeled with the action
+/// name and the remaining nondeterministic picks. Steps without a `participant`
+/// pick, such as `init`, are drawn as notes spanning all participants.
+///
+/// With [`Sequence::Map`], a function maps each step to its [`SequenceMessage`],
+/// e.g. to find the participants in a record or sum type pick, or in the spec
+/// state. Steps it maps to `None` are drawn as notes. Picks read by the function
+/// are not considered read by the driver.
+///
+/// Sequence diagrams are written along with other failure reports when the
+/// `mermaid` or `plantuml` formats are enabled in `QUINT_REPORT`.
+///
+/// # Examples
+///
+/// ```rust
+/// use quint_connect::*;
+/// # struct MyDriver;
+///
+/// impl Driver for MyDriver {
+///     type State = ();
+///
+///     fn sequence() -> Option<Sequence> {
+///         Some(Sequence::Picks {
+///             participant: "node",
+///             receiver: Some("target"),
+///         })
+///     }
+///
+///     fn step(&mut self, step: &Step) -> Result {
+///         // ...
+/// #       Ok(())
+///     }
+/// }
+/// ```
+///
+/// Mapping steps with a function instead, here for messages picked as records:
+///
+/// ```rust
+/// use quint_connect::*;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Msg {
+///     src: String,
+///     dst: String,
+///     kind: String,
+/// }
+///
+/// fn message(step: &Step) -> Option<SequenceMessage> {
+///     let msg: Msg = step.try_pick("msg").ok()??;
+///     Some(SequenceMessage {
+///         from: msg.src,
+///         to: Some(msg.dst),
+///         label: format!("{}: {}", step.action(), msg.kind),
+///     })
+/// }
+///
+/// let sequence = Sequence::Map(message);
+/// ```
+#[derive(Clone, Debug)]
+pub enum Sequence {
+    /// Finds the participants of each step in its nondeterministic picks.
+    Picks {
+        /// Name of the nondeterministic pick holding the participant taking the
+        /// action.
+        participant: &'static str,
+
+        /// Name of the nondeterministic pick holding the participant receiving a
+        /// message, if any. Actions without a receiver are drawn as self-messages.
+        receiver: Option<&'static str>,
+    },
+
+    /// Maps each step to the message drawn for it, or to `None` to draw it as a
+    /// note.
+    Map(fn(&Step) -> Option<SequenceMessage>),
+}
+
+/// A step drawn as a message in a sequence diagram. See [`Sequence`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SequenceMessage {
+    /// The participant sending the message.
+    pub from: String,
+
+    /// The participant receiving the message, if any. Messages without a receiver
+    /// are drawn as self-messages.
+    pub to: Option<String>,
+
+    /// The text of the message.
+    pub label: String,
+}
+
 /// Core trait for connecting Rust implementations to Quint specifications.
 ///
 /// Implementations of this trait define how to execute steps from a Quint trace against
@@ -112,6 +211,8 @@ pub struct Config {
 ///
 /// - [`config`](Driver::config): Returns configuration specifying where to find state
 ///   and nondeterministic picks in the specification. Defaults to top-level paths.
+/// - [`sequence`](Driver::sequence): Returns how steps map onto a sequence diagram for
+///   failure reports. Defaults to `None`.
 pub trait Driver: Sized {
     /// The state type that can be extracted from both the driver and the specification.
     ///
@@ -133,4 +234,12 @@ pub trait Driver: Sized {
     fn config() -> Config {
         Config::default()
     }
+
+    /// Returns how steps map onto a sequence diagram.
+    ///
+    /// Override this method to export failing traces as sequence diagrams. See
+    /// [`Sequence`] for details.
+    fn sequence() -> Option<Sequence> {
+        None
+    }
 }
@@ -1,21 +1,45 @@
 use crate::value::{ValueDisplay, ValueOption};
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, anyhow, bail};
 use itf::value::{Record, Value};
-use std::fmt;
+use serde::{
+    Deserializer,
+    de::{self, DeserializeOwned, Visitor, value::MapDeserializer},
+    forward_to_deserialize_any,
+};
+use std::{any, cell::RefCell, collections::BTreeSet, fmt};
 
 /// Wraps nondeterministic choices made during trace generation.
+///
+/// Quint records every nondet of the spec on each step, with `None` for the ones
+/// the taken action didn't pick. Those are kept apart from the picks that were
+/// made, so that a pick set to `None` can be told apart from an unknown one.
+/// Reads are tracked to find the picks a driver never used.
 #[doc(hidden)] // public for macro use
-pub struct NondetPicks(Record);
+pub struct NondetPicks {
+    picks: Record,
+    unset: BTreeSet<String>,
+    consumed: RefCell<BTreeSet<String>>,
+}
 
 impl From<Record> for NondetPicks {
     fn from(record: Record) -> Self {
-        let mut nondets = Record::new();
+        let mut picks = Record::new();
+        let mut unset = BTreeSet::new();
         for (key, value) in record {
-            if let Some(value) = value.into_option() {
-                nondets.insert(key, value);
+            match value.into_option() {
+                Some(value) => {
+                    picks.insert(key, value);
+                }
+                None => {
+                    unset.insert(key);
+                }
             }
         }
-        Self(nondets)
+        Self {
+            picks,
+            unset,
+            consumed: RefCell::default(),
+        }
     }
 }
 
@@ -28,22 +52,122 @@ impl NondetPicks {
     }
 
     pub(crate) fn empty() -> Self {
-        Self(Record::new())
+        Record::new().into()
     }
 
     pub(crate) fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.picks.is_empty()
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.picks.iter()
     }
 
+    /// Returns the value of a pick, marking it as consumed. Returns `None` both
+    /// for picks set to `None` and for unknown ones, see [`Self::contains`].
     #[doc(hidden)] // public for macro use
     pub fn get<'a>(&'a self, var: &str) -> Option<&'a Value> {
-        self.0.get(var)
+        let value = self.picks.get(var)?;
+        self.consumed.borrow_mut().insert(var.to_string());
+        Some(value)
+    }
+
+    /// Runs `f` without marking the picks it reads as consumed.
+    pub(crate) fn untracked<R>(&self, f: impl FnOnce() -> R) -> R {
+        let consumed = self.consumed.borrow().clone();
+        let result = f();
+        *self.consumed.borrow_mut() = consumed;
+        result
+    }
+
+    /// Whether the spec has a nondet with the given name, picked or not.
+    #[doc(hidden)] // public for macro use
+    pub fn contains(&self, var: &str) -> bool {
+        self.picks.contains_key(var) || self.unset.contains(var)
+    }
+
+    /// The error for a pick that `action` didn't make.
+    pub(crate) fn missing(&self, var: &str, action: &str) -> anyhow::Error {
+        if self.contains(var) {
+            anyhow!("Nondet pick `{}` is `None` in action `{}`", var, action)
+        } else {
+            anyhow!("Missing nondet pick `{}` in action `{}`", var, action)
+        }
+    }
+
+    /// The picks that were made but never read through [`Self::get`] or
+    /// [`Self::decode`].
+    pub(crate) fn unconsumed(&self) -> Vec<&str> {
+        let consumed = self.consumed.borrow();
+        self.picks
+            .iter()
+            .map(|(key, _)| key.as_str())
+            .filter(|key| !consumed.contains(*key))
+            .collect()
+    }
+
+    /// Decodes all picks into a struct. Picks that weren't made decode as `None`
+    /// into optional fields, while picks the struct doesn't declare are an error.
+    #[doc(hidden)] // public for macro use
+    pub fn decode<T: DeserializeOwned>(&self) -> Result<T> {
+        self.consumed
+            .borrow_mut()
+            .extend(self.picks.iter().map(|(key, _)| key.clone()));
+        T::deserialize(DenyUnknownFields(Value::Record(self.picks.clone()))).with_context(|| {
+            format!(
+                "Failed to decode nondet picks into `{}`",
+                any::type_name::<T>()
+            )
+        })
+    }
+}
+
+/// Deserializes a value, rejecting record fields that the struct it's
+/// deserialized into doesn't declare.
+struct DenyUnknownFields(Value);
+
+impl<'de> Deserializer<'de> for DenyUnknownFields {
+    type Error = itf::de::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.0.deserialize_any(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let Value::Record(record) = self.0 else {
+            return self.0.deserialize_struct(name, fields, visitor);
+        };
+        if let Some((unknown, _)) = record
+            .iter()
+            .find(|(key, _)| !fields.contains(&key.as_str()))
+        {
+            return Err(de::Error::unknown_field(unknown, fields));
+        }
+        // Visit the record as a map, since an empty one would be taken as unit.
+        let entries = record
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()));
+        let mut map = MapDeserializer::new(entries);
+        let value = visitor.visit_map(&mut map)?;
+        map.end()?;
+        Ok(value)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes
+        byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct map
+        enum identifier ignored_any
     }
 }
 
 impl fmt::Display for NondetPicks {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut iter = self.0.iter();
+        let mut iter = self.picks.iter();
         if let Some((key, value)) = iter.next() {
             write!(f, "+ {}: {}", key, value.display())?;
             for (key, value) in iter {
@@ -79,4 +203,82 @@ mod tests {
 
         assert!(nondet.is_some(), "failed to find nondet value")
     }
+
+    #[test]
+    fn test_unset_nondet_picks() {
+        let mut none = Record::new();
+        none.insert("tag".to_string(), Value::String("None".to_string()));
+        none.insert("value".to_string(), Value::Tuple(vec![].into()));
+
+        let mut record = Record::new();
+        record.insert("foo".to_string(), Value::Record(none));
+
+        let nondets = NondetPicks::new(Value::Record(record)).unwrap();
+        assert!(nondets.is_empty());
+        assert!(nondets.get("foo").is_none());
+        assert!(nondets.contains("foo"));
+        assert!(!nondets.contains("bar"));
+    }
+
+    #[test]
+    fn test_unconsumed_nondet_picks() {
+        let nondets = picks(&[("amount", Value::Number(10)), ("fee", Value::Number(1))]);
+        assert_eq!(nondets.unconsumed(), ["amount", "fee"]);
+
+        nondets.get("fee");
+        nondets.get("memo");
+        assert_eq!(nondets.unconsumed(), ["amount"]);
+
+        let _ = nondets.decode::<TransferArgs>();
+        assert!(nondets.unconsumed().is_empty());
+    }
+
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct TransferArgs {
+        amount: i64,
+        memo: Option<String>,
+    }
+
+    fn picks(entries: &[(&str, Value)]) -> NondetPicks {
+        let mut record = Record::new();
+        for (key, value) in entries {
+            record.insert(key.to_string(), value.clone());
+        }
+        record.into()
+    }
+
+    #[test]
+    fn test_decode_nondet_picks() {
+        let nondets = picks(&[("amount", Value::Number(10))]);
+        let args: TransferArgs = nondets.decode().unwrap();
+        assert_eq!(
+            args,
+            TransferArgs {
+                amount: 10,
+                memo: None
+            }
+        );
+
+        let memo = Value::String("rent".to_string());
+        let nondets = picks(&[("amount", Value::Number(10)), ("memo", memo)]);
+        let args: TransferArgs = nondets.decode().unwrap();
+        assert_eq!(args.memo.as_deref(), Some("rent"));
+    }
+
+    #[test]
+    fn test_decode_nondet_picks_errors() {
+        let nondets = picks(&[("amount", Value::Number(10)), ("fee", Value::Number(1))]);
+        let err = nondets.decode::<TransferArgs>().unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("Failed to decode nondet picks into")
+        );
+        assert_eq!(
+            err.root_cause().to_string(),
+            "unknown field `fee`, expected `amount` or `memo`"
+        );
+
+        let err = picks(&[]).decode::<TransferArgs>().unwrap_err();
+        assert_eq!(err.root_cause().to_string(), "missing field `amount`");
+    }
 }
@@ -1,7 +1,11 @@
+use crate::value::{ValueDisplay, from_debug, matches, to_value};
 use anyhow::{Context, Result};
 use itf::Value;
-use serde::de::DeserializeOwned;
-use std::fmt::Debug;
+use serde::{Deserialize, Deserializer, Serialize, de::DeserializeOwned};
+use std::{
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+};
 
 /// Trait for extracting and comparing state between a Quint specification and a Rust
 /// implementation.
@@ -57,6 +61,16 @@ pub trait State<D>: PartialEq + DeserializeOwned + Debug {
              Please check the crate docs for tips and tricks on state deserialization.",
         )
     }
+
+    #[doc(hidden)] // internal use only
+    fn matches(&self, driver_state: &Self) -> bool {
+        self == driver_state
+    }
+
+    #[doc(hidden)] // internal use only
+    fn to_value(&self) -> Option<Value> {
+        from_debug(&format!("{:?}", self))
+    }
 }
 
 /// Implements [State] for the unit type, effectively disabling state checking for
@@ -70,3 +84,120 @@ impl<D> State<D> for () {
         Ok(())
     }
 }
+
+/// Trait for projecting a driver's state into a serializable type that is compared with
+/// the specification's state as a Quint value.
+///
+/// Unlike [`State`], a projection never deserializes the specification's state. Instead,
+/// the projection is serialized into an ITF value and compared with the specification's
+/// value following Quint's semantics: sets and maps match regardless of their order,
+/// integers regardless of their size, and [`Option`]s match Quint's `Option` sum type.
+/// Record fields that are not part of the projection are ignored. Use it as a driver's
+/// state through the [`Projected`] wrapper.
+///
+/// # Examples
+///
+/// ```rust
+/// use quint_connect::*;
+/// use serde::Serialize;
+/// use std::collections::HashSet;
+///
+/// #[derive(Serialize)]
+/// struct BankState {
+///     balances: HashSet<u64>,
+///     owner: Option<String>,
+/// }
+///
+/// struct Bank {
+///     accounts: Vec<u64>,
+/// }
+///
+/// impl Projection<Bank> for BankState {
+///     fn from_driver(driver: &Bank) -> Result<Self> {
+///         Ok(BankState {
+///             balances: driver.accounts.iter().copied().collect(),
+///             owner: None,
+///         })
+///     }
+/// }
+///
+/// impl Driver for Bank {
+///     type State = Projected<BankState>;
+///
+///     fn step(&mut self, step: &Step) -> Result {
+///         switch!(step {
+///             init => self.accounts = vec![0],
+///         })
+///     }
+/// }
+/// ```
+pub trait Projection<D>: Serialize {
+    /// Extracts the projection from a driver implementation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the projection cannot be extracted from the driver.
+    fn from_driver(driver: &D) -> Result<Self>
+    where
+        Self: Sized;
+}
+
+/// A [`State`] compared with the specification's state as a Quint value.
+///
+/// See [`Projection`] for details.
+pub struct Projected<P> {
+    value: Value,
+    projection: PhantomData<P>,
+}
+
+impl<P> Projected<P> {
+    fn new(value: Value) -> Self {
+        Self {
+            value,
+            projection: PhantomData,
+        }
+    }
+
+    /// The state as a Quint value.
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+}
+
+impl<D, P: Projection<D>> State<D> for Projected<P> {
+    fn from_driver(driver: &D) -> Result<Self> {
+        let projection = P::from_driver(driver)?;
+        let value = to_value(&projection).context("Failed to serialize driver's projection")?;
+        Ok(Self::new(value))
+    }
+
+    fn from_spec(value: Value) -> Result<Self> {
+        Ok(Self::new(value))
+    }
+
+    fn matches(&self, driver_state: &Self) -> bool {
+        matches(&self.value, &driver_state.value)
+    }
+
+    fn to_value(&self) -> Option<Value> {
+        Some(self.value.clone())
+    }
+}
+
+impl<P> PartialEq for Projected<P> {
+    fn eq(&self, other: &Self) -> bool {
+        matches(&self.value, &other.value) && matches(&other.value, &self.value)
+    }
+}
+
+impl<'de, P> Deserialize<'de> for Projected<P> {
+    fn deserialize<De: Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        Value::deserialize(deserializer).map(Self::new)
+    }
+}
+
+impl<P> Debug for Projected<P> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.value.display())
+    }
+}
@@ -1,26 +1,174 @@
 use crate::{
-    driver::{Config, Path, nondet::NondetPicks},
+    driver::{Action, Config, Path, nondet::NondetPicks},
     value::ValueDisplay,
 };
 use anyhow::{Context, Result, anyhow, bail};
 use itf::value::{Record, Value};
-use serde::Deserialize;
-use std::fmt;
+use serde::{Deserialize, de::DeserializeOwned};
+use std::{collections::BTreeSet, fmt, sync::Arc};
 
 /// Represents a single step in a trace generated from a Quint specification.
 ///
 /// Steps are passed to [`Driver::step`](crate::Driver::step) for execution against
 /// the implementation. Use the [`switch!`](crate::switch) macro to pattern-match
-/// on action names and extract nondeterministic picks from steps.
+/// on action names and extract nondeterministic picks from steps, or read them
+/// directly with the accessors below.
+///
+/// # Examples
+///
+/// ```rust
+/// use quint_connect::*;
+///
+/// fn deliver(step: &Step) -> Result {
+///     if step.action() == "Deliver" {
+///         let to: String = step.pick("to")?;
+///         let delay: Option<u64> = step.try_pick("delay")?;
+///         let inbox: Vec<String> = step.spec_var(&["inboxes", &to])?;
+///         // ...
+///     }
+///     Ok(())
+/// }
+/// ```
 pub struct Step {
     #[doc(hidden)] // public for macro use
     pub action_taken: String,
     #[doc(hidden)] // public for macro use
     pub nondet_picks: NondetPicks,
     pub(crate) state: Value,
+    /// All spec variables of the post-state, except for the `mbt::` ones.
+    pub(crate) vars: Arc<Record>,
+    /// The spec variables of the previous step, if any.
+    pub(crate) prev_vars: Option<Arc<Record>>,
+    pub(crate) trace: usize,
+    pub(crate) index: usize,
 }
 
 impl Step {
+    /// The name of the action taken by this step, or an empty string for an
+    /// anonymous action.
+    pub fn action(&self) -> &str {
+        &self.action_taken
+    }
+
+    /// Decodes the nondeterministic pick `name` of this step.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the action didn't make the pick, either because its
+    /// value is `None` or because the spec has no such nondet, or if the value
+    /// can't be decoded into `T`.
+    pub fn pick<T: DeserializeOwned>(&self, name: &str) -> Result<T> {
+        match self.try_pick(name)? {
+            Some(value) => Ok(value),
+            None => Err(self.nondet_picks.missing(name, &self.action_taken)),
+        }
+    }
+
+    /// Decodes the nondeterministic pick `name` of this step, or returns `None`
+    /// if the action didn't make it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value can't be decoded into `T`.
+    pub fn try_pick<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>> {
+        self.nondet_picks
+            .get(name)
+            .map(|value| {
+                T::deserialize(value.clone()).with_context(|| {
+                    format!(
+                        "Failed to decode nondet pick `{}` of action `{}`",
+                        name, self.action_taken
+                    )
+                })
+            })
+            .transpose()
+    }
+
+    /// Iterates over the nondeterministic picks made by this step, in name order.
+    /// Picks set to `None` are skipped, and every pick is considered read.
+    pub fn picks(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.nondet_picks
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .filter_map(|name| Some((name, self.nondet_picks.get(name)?)))
+    }
+
+    /// Decodes a spec variable of the post-state, or a value nested in it when the
+    /// path has more than one segment, e.g. `&["balances", "alice"]`. Segments
+    /// after the first select record fields or map keys, where maps with integer
+    /// keys are indexed by their decimal representation, e.g. `&["votes", "3"]`.
+    /// Unlike
+    /// [`Driver::State`](crate::Driver::State), any variable can be read, whatever
+    /// the driver's [`Config::state`] path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path doesn't lead to a value, or if the value can't
+    /// be decoded into `T`.
+    pub fn spec_var<T: DeserializeOwned>(&self, path: &[&str]) -> Result<T> {
+        let value = find_value_in_path(&self.vars, path)?;
+        T::deserialize(value.clone())
+            .with_context(|| format!("Failed to decode spec variable at {:?}", path))
+    }
+
+    /// The position of the trace this step belongs to among the traces of the
+    /// test, starting at 1 as in the logs.
+    pub fn trace_index(&self) -> usize {
+        self.trace
+    }
+
+    /// The position of this step in its trace, where step 0 is the initial state.
+    pub fn step_index(&self) -> usize {
+        self.index
+    }
+
+    /// Decodes a spec variable of the pre-state, i.e. of the previous step, with a
+    /// path as in [`Self::spec_var`]. Returns `None` for the initial step.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path doesn't lead to a value, or if the value can't
+    /// be decoded into `T`.
+    pub fn prev_spec_var<T: DeserializeOwned>(&self, path: &[&str]) -> Result<Option<T>> {
+        let Some(prev) = &self.prev_vars else {
+            return Ok(None);
+        };
+        let value = find_value_in_path(prev, path)?;
+        T::deserialize(value.clone())
+            .map(Some)
+            .with_context(|| format!("Failed to decode previous spec variable at {:?}", path))
+    }
+
+    /// The names of the spec variables whose value changed in this step. All
+    /// variables are considered changed in the initial step.
+    pub fn changed_vars(&self) -> BTreeSet<&str> {
+        self.vars
+            .iter()
+            .filter(|(key, value)| {
+                self.prev_vars
+                    .as_ref()
+                    .is_none_or(|prev| prev.get(key) != Some(value))
+            })
+            .map(|(key, _)| key.as_str())
+            .collect()
+    }
+
+    /// Displays the action and picks of this step along with the spec variables
+    /// that changed, instead of the whole next state.
+    pub(crate) fn display_changes(&self) -> impl fmt::Display + '_ {
+        StepChanges(self)
+    }
+
+    /// Decodes the action taken by this step into a typed [`Action`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the action is not one of `A`'s variants, or if its
+    /// nondeterministic picks can't be decoded.
+    pub fn decode_action<A: Action>(&self) -> Result<A> {
+        A::from_step(self)
+    }
+
     pub(crate) fn new(state: Record, config: &Config) -> Result<Self> {
         if config.nondet.is_empty() {
             extract_from_mbt_vars(state, config.state)
@@ -28,13 +176,25 @@ impl Step {
             extract_from_sum_type(state, config.nondet, config.state)
         }
     }
+
+    /// Places the step in a trace, after the step with the given variables.
+    pub(crate) fn at(mut self, trace: usize, index: usize, prev_vars: Option<Arc<Record>>) -> Self {
+        self.trace = trace;
+        self.index = index;
+        self.prev_vars = prev_vars;
+        self
+    }
 }
 
 fn extract_from_mbt_vars(mut state: Record, state_path: Path) -> Result<Step> {
     Ok(Step {
         action_taken: extract_action_from_mbt_var(&mut state)?,
         nondet_picks: extract_nondet_from_mbt_var(&mut state)?,
-        state: extract_value_in_path(state, state_path)?,
+        state: extract_value_in_path(state.clone(), state_path)?,
+        vars: Arc::new(state),
+        prev_vars: None,
+        trace: 0,
+        index: 0,
     })
 }
 
@@ -47,12 +207,14 @@ fn extract_from_sum_type(mut state: Record, sum_type_path: Path, state_path: Pat
     let _ = state.remove("mbt::actionTaken");
     let _ = state.remove("mbt::nondetPicks");
 
-    let state = extract_value_in_path(state, state_path)?;
-
     Ok(Step {
         action_taken,
         nondet_picks,
-        state,
+        state: extract_value_in_path(state.clone(), state_path)?,
+        vars: Arc::new(state),
+        prev_vars: None,
+        trace: 0,
+        index: 0,
     })
 }
 
@@ -100,6 +262,53 @@ fn extract_value_in_path(state: Record, path: &[&str]) -> Result<Value> {
     Ok(value)
 }
 
+fn find_value_in_path<'a>(state: &'a Record, path: &[&str]) -> Result<&'a Value> {
+    let Some((first, rest)) = path.split_first() else {
+        bail!("Expected a non-empty path to a spec variable")
+    };
+    let Some(mut value) = state.get(first) else {
+        bail!("Can not find spec variable {:?}", first)
+    };
+    for segment in rest {
+        let next = match value {
+            Value::Record(rec) => rec.get(segment),
+            Value::Map(map) => map
+                .iter()
+                .find(|(key, _)| is_map_key(key, segment))
+                .map(|(_, value)| value),
+            _ => bail!(
+                "Can not read {:?} from non-record value in path: {:?}\n\
+                 Current value: {}",
+                segment,
+                path,
+                value.display(),
+            ),
+        };
+        let Some(next) = next else {
+            bail!(
+                "Can not find a value at {:?} in path: {:?}\n\
+                 Current value: {}",
+                segment,
+                path,
+                value.display()
+            )
+        };
+        value = next;
+    }
+    Ok(value)
+}
+
+/// Whether a map key is written as the given path segment, i.e. a string equal
+/// to it or an integer whose decimal representation is the segment.
+fn is_map_key(key: &Value, segment: &str) -> bool {
+    match key {
+        Value::String(key) => key == segment,
+        Value::Number(key) => key.to_string() == segment,
+        Value::BigInt(key) => key.to_string() == segment,
+        _ => false,
+    }
+}
+
 fn find_record_in_path<'a>(state: &'a Record, path: &[&str]) -> Result<&'a Record> {
     let mut rec = state;
     for segment in path {
@@ -140,8 +349,8 @@ fn extract_nondet_from_sum_type(ty: &Record) -> Result<NondetPicks> {
     }
 }
 
-impl fmt::Display for Step {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl Step {
+    fn fmt_action(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Action taken:")?;
         if self.action_taken.is_empty() {
             writeln!(f, " <anonymous>")?;
@@ -151,10 +360,37 @@ impl fmt::Display for Step {
 
         write!(f, "Nondet picks:")?;
         if self.nondet_picks.is_empty() {
-            writeln!(f, " <none>")?;
+            writeln!(f, " <none>")
         } else {
-            writeln!(f, "\n{}", self.nondet_picks)?;
+            writeln!(f, "\n{}", self.nondet_picks)
         }
+    }
+}
+
+struct StepChanges<'a>(&'a Step);
+
+impl fmt::Display for StepChanges<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let step = self.0;
+        step.fmt_action(f)?;
+
+        write!(f, "Changed variables:")?;
+        let changed = step.changed_vars();
+        if changed.is_empty() {
+            write!(f, " <none>")?;
+        }
+        for key in changed {
+            if let Some(value) = step.vars.get(key) {
+                write!(f, "\n+ {}: {}", key, value.display())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_action(f)?;
 
         write!(f, "Next state:")?;
         match &self.state {
@@ -185,7 +421,10 @@ impl fmt::Display for Step {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use itf::Value;
+    use itf::{
+        Value,
+        value::{BigInt, Map},
+    };
 
     #[test]
     fn test_extract_value_in_path_empty_path() {
@@ -453,4 +692,123 @@ mod tests {
         let result = extract_from_sum_type(rec, &["sum_type"], &["state_var"]).unwrap();
         assert_eq!(result.action_taken, "TestAction");
     }
+
+    fn bank_step() -> Step {
+        let mut picks = Record::new();
+        picks.insert("amount".to_string(), Value::Number(10));
+        picks.insert("account".to_string(), Value::String("alice".to_string()));
+
+        let mut balances = Record::new();
+        balances.insert("alice".to_string(), Value::Number(90));
+
+        let mut rec = Record::new();
+        rec.insert(
+            "mbt::actionTaken".to_string(),
+            Value::String("Withdraw".to_string()),
+        );
+        rec.insert("mbt::nondetPicks".to_string(), Value::Record(picks));
+        rec.insert("balances".to_string(), Value::Record(balances));
+        rec.insert("fees".to_string(), Value::Number(1));
+
+        extract_from_mbt_vars(rec, &["balances"]).unwrap()
+    }
+
+    #[test]
+    fn test_step_accessors() {
+        let step = bank_step();
+        assert_eq!(step.action(), "Withdraw");
+        assert_eq!(step.pick::<u64>("amount").unwrap(), 10);
+        assert_eq!(step.try_pick::<u64>("fee").unwrap(), None);
+        assert_eq!(step.spec_var::<u64>(&["fees"]).unwrap(), 1);
+        assert_eq!(step.spec_var::<i64>(&["balances", "alice"]).unwrap(), 90);
+
+        let picks: Vec<_> = step.picks().map(|(name, _)| name).collect();
+        assert_eq!(picks, ["account", "amount"]);
+        assert!(step.nondet_picks.unconsumed().is_empty());
+    }
+
+    #[test]
+    fn test_step_accessor_errors() {
+        let step = bank_step();
+        let err = step.pick::<u64>("fee").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Missing nondet pick `fee` in action `Withdraw`"
+        );
+
+        let err = step.pick::<u64>("account").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to decode nondet pick `account` of action `Withdraw`"
+        );
+
+        let err = step.spec_var::<u64>(&["mbt::actionTaken"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Can not find spec variable \"mbt::actionTaken\""
+        );
+
+        let err = step.spec_var::<u64>(&["fees", "alice"]).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("Can not read \"alice\" from non-record")
+        );
+    }
+
+    #[test]
+    fn test_step_map_paths() {
+        let mut inboxes = Map::new();
+        inboxes.insert(
+            Value::String("bob".to_string()),
+            Value::List(vec![Value::String("hi".to_string())]),
+        );
+        let mut votes = Map::new();
+        votes.insert(Value::BigInt(BigInt::new(3)), Value::Bool(true));
+
+        let mut rec = Record::new();
+        rec.insert(
+            "mbt::actionTaken".to_string(),
+            Value::String("Deliver".to_string()),
+        );
+        rec.insert("mbt::nondetPicks".to_string(), Value::Record(Record::new()));
+        rec.insert("inboxes".to_string(), Value::Map(inboxes));
+        rec.insert("votes".to_string(), Value::Map(votes));
+        let step = Step::new(rec, &Config::default()).unwrap();
+
+        assert_eq!(
+            step.spec_var::<Vec<String>>(&["inboxes", "bob"]).unwrap(),
+            ["hi"]
+        );
+        assert!(step.spec_var::<bool>(&["votes", "3"]).unwrap());
+
+        let err = step.spec_var::<bool>(&["votes", "4"]).unwrap_err();
+        assert!(err.to_string().starts_with("Can not find a value at \"4\""));
+    }
+
+    #[test]
+    fn test_step_context() {
+        let first = bank_step().at(2, 0, None);
+        assert_eq!(first.prev_spec_var::<u64>(&["fees"]).unwrap(), None);
+        assert_eq!(first.changed_vars(), BTreeSet::from(["balances", "fees"]));
+
+        let mut rec = (*first.vars).clone();
+        rec.insert("fees".to_string(), Value::Number(2));
+        rec.insert(
+            "mbt::actionTaken".to_string(),
+            Value::String("Charge".to_string()),
+        );
+        rec.insert("mbt::nondetPicks".to_string(), Value::Record(Record::new()));
+        let second = Step::new(rec, &Config::default())
+            .unwrap()
+            .at(2, 1, Some(first.vars.clone()));
+
+        assert_eq!(second.trace_index(), 2);
+        assert_eq!(second.step_index(), 1);
+        assert_eq!(second.prev_spec_var::<u64>(&["fees"]).unwrap(), Some(1));
+        assert_eq!(second.changed_vars(), BTreeSet::from(["fees"]));
+        assert_eq!(
+            second.display_changes().to_string(),
+            "Action taken: Charge\nNondet picks: <none>\nChanged variables:\n+ fees: 2"
+        );
+    }
 }
@@ -0,0 +1,687 @@
+//! Serde helpers for Quint's encodings of values in ITF traces.
+//!
+//! Quint values don't map one-to-one to Rust types: sets and tuples are objects
+//! such as `{ "#set": [...] }`, integers are big integers, and sum types are
+//! records with `tag` and `value` fields. The [`serde_with`] adapters of this
+//! module decode them into idiomatic Rust types, and encode Rust types back into
+//! the same shapes, e.g. as ITF JSON with `serde_json`:
+//!
+//! | Adapter             | Quint value          | Rust types                                     |
+//! |---------------------|----------------------|------------------------------------------------|
+//! | [`Set<A>`]          | `Set[a]`             | `Vec`, `VecDeque`, `BTreeSet`, `HashSet`       |
+//! | [`Map<K, V>`]       | `k -> v`             | `BTreeMap`, `HashMap`, `Vec<(K, V)>`           |
+//! | [`BigInt`]          | `int`                | any integer, including `u128` and `i128`       |
+//! | [`Tuple`]           | `(a, b, ...)`        | tuples, arrays and sequences                   |
+//! | [`Sum`]             | `A \| B(x) \| ...`   | enums deriving serde's traits                  |
+//! | [`QuintOption<A>`]  | `Option[a]`          | `Option`                                       |
+//!
+//! Adapters nest, e.g. `Map<Same, Set<Sum>>` for a `BTreeMap<String,
+//! Vec<Message>>` where `Message` is a plain serde enum. Each adapter also has a
+//! module for serde's `with` attribute, such as [`set`] and [`bigint`].
+//!
+//! State fields deriving [`QuintState`](crate::QuintState) or
+//! [`QuintAction`](crate::QuintAction) take an adapter with
+//! `#[quint(as = "...")]`, while nondet picks read with
+//! [`Step::pick`](crate::Step::pick) are wrapped in [`Quint`].
+//!
+//! The module isn't exported as `quint_connect::serde`, since `use
+//! quint_connect::*` would then make `use serde::Deserialize` ambiguous.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use quint_connect::encoding::{Quint, Sum, bigint, sum};
+//! use quint_connect::*;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Deserialize, Serialize, PartialEq, Debug)]
+//! enum Stage {
+//!     Working,
+//!     Committed { round: u64 },
+//! }
+//!
+//! #[derive(Deserialize, Serialize)]
+//! struct Account {
+//!     #[serde(with = "bigint")]
+//!     balance: u128,
+//!     #[serde(with = "sum")]
+//!     stage: Stage,
+//! }
+//!
+//! # fn main() -> Result {
+//! let account = Account {
+//!     balance: u128::MAX,
+//!     stage: Stage::Working,
+//! };
+//! let step = Step::builder("Commit")
+//!     .pick("stage", Quint::<_, Sum>::new(Stage::Committed { round: 2 }))
+//!     .var("account", account)
+//!     .build()?;
+//!
+//! let stage = step.pick::<Quint<Stage, Sum>>("stage")?;
+//! assert_eq!(*stage, Stage::Committed { round: 2 });
+//!
+//! let account: Account = step.spec_var(&["account"])?;
+//! assert_eq!(account.balance, u128::MAX);
+//! assert_eq!(account.stage, Stage::Working);
+//! # Ok(())
+//! # }
+//! ```
+
+mod tagged;
+
+use crate::value::{Itf, to_value, unit, variant};
+use itf::Value;
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, Visitor},
+    ser,
+};
+use serde_with::{DeserializeAs, SerializeAs, ser::SerializeAsWrap};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fmt,
+    hash::{BuildHasher, Hash},
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+pub use crate::value::QuintOption;
+pub use serde_with::Same;
+
+/// Encodes a collection as a Quint set, adapting its elements with `A`.
+pub struct Set<A = Same>(PhantomData<A>);
+
+/// Encodes a map or a list of pairs as a Quint map, adapting its keys with `K`
+/// and its values with `V`. Keys can be of any type, e.g. tuples or structs.
+pub struct Map<K = Same, V = Same>(PhantomData<(K, V)>);
+
+/// Encodes an integer as a Quint big integer. Decodes big integers into any
+/// integer type they fit in, including `u128` and `i128`.
+pub struct BigInt;
+
+/// Encodes a Rust tuple, array or sequence as a Quint tuple.
+pub struct Tuple;
+
+/// Encodes an enum as a Quint sum type, e.g. `Occupied(X)` as
+/// `{ tag: "Occupied", value: { tag: "X", value: () } }`.
+///
+/// Only the enum itself is decoded from Quint's encoding: enums nested in its
+/// variants need adapters of their own.
+pub struct Sum;
+
+/// A value encoded with the adapter `A`, e.g. to read a nondet pick with
+/// [`Step::pick`](crate::Step::pick) or to build one with
+/// [`StepBuilder::pick`](crate::StepBuilder::pick).
+///
+/// ```rust
+/// # use quint_connect::{Result, Step, encoding::{BigInt, Quint}};
+/// # fn deposit(step: &Step) -> Result<u128> {
+/// let amount = step.pick::<Quint<u128, BigInt>>("amount")?.into_inner();
+/// # Ok(amount)
+/// # }
+/// ```
+pub struct Quint<T, A = Same> {
+    value: T,
+    adapter: PhantomData<A>,
+}
+
+impl<T, A> Quint<T, A> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            adapter: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T, A> Deref for Quint<T, A> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T, A> DerefMut for Quint<T, A> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T: fmt::Debug, A> fmt::Debug for Quint<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<T: Clone, A> Clone for Quint<T, A> {
+    fn clone(&self) -> Self {
+        Self::new(self.value.clone())
+    }
+}
+
+impl<T: PartialEq, A> PartialEq for Quint<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq, A> Eq for Quint<T, A> {}
+
+impl<T, A: SerializeAs<T>> Serialize for Quint<T, A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        A::serialize_as(&self.value, serializer)
+    }
+}
+
+impl<'de, T, A: DeserializeAs<'de, T>> Deserialize<'de> for Quint<T, A> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        A::deserialize_as(deserializer).map(Self::new)
+    }
+}
+
+/// Encodes a Rust value adapted with `A` into a Quint value.
+fn encode<T, A, E>(value: &T) -> Result<Value, E>
+where
+    T: ?Sized,
+    A: SerializeAs<T> + ?Sized,
+    E: ser::Error,
+{
+    to_value(&SerializeAsWrap::<T, A>::new(value)).map_err(|err| E::custom(format!("{:#}", err)))
+}
+
+fn serialize_set<'a, T, A, S>(
+    elems: impl IntoIterator<Item = &'a T>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    T: 'a,
+    A: SerializeAs<T>,
+    S: Serializer,
+{
+    let set = elems
+        .into_iter()
+        .map(encode::<T, A, S::Error>)
+        .collect::<Result<_, _>>()?;
+    Itf(&Value::Set(set)).serialize(serializer)
+}
+
+fn serialize_map<'a, K, V, KA, VA, S>(
+    entries: impl IntoIterator<Item = (&'a K, &'a V)>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    K: 'a,
+    V: 'a,
+    KA: SerializeAs<K>,
+    VA: SerializeAs<V>,
+    S: Serializer,
+{
+    let map = entries
+        .into_iter()
+        .map(|(key, value)| {
+            Ok((
+                encode::<K, KA, S::Error>(key)?,
+                encode::<V, VA, S::Error>(value)?,
+            ))
+        })
+        .collect::<Result<_, _>>()?;
+    Itf(&Value::Map(map)).serialize(serializer)
+}
+
+impl<T, A: SerializeAs<T>> SerializeAs<Vec<T>> for Set<A> {
+    fn serialize_as<S: Serializer>(source: &Vec<T>, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_set::<T, A, S>(source, serializer)
+    }
+}
+
+impl<'de, T, A: DeserializeAs<'de, T>> DeserializeAs<'de, Vec<T>> for Set<A> {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<Vec<T>, D::Error> {
+        Vec::<A>::deserialize_as(deserializer)
+    }
+}
+
+impl<T, A: SerializeAs<T>> SerializeAs<VecDeque<T>> for Set<A> {
+    fn serialize_as<S: Serializer>(source: &VecDeque<T>, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_set::<T, A, S>(source, serializer)
+    }
+}
+
+impl<'de, T, A: DeserializeAs<'de, T>> DeserializeAs<'de, VecDeque<T>> for Set<A> {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<VecDeque<T>, D::Error> {
+        VecDeque::<A>::deserialize_as(deserializer)
+    }
+}
+
+impl<T, A: SerializeAs<T>> SerializeAs<BTreeSet<T>> for Set<A> {
+    fn serialize_as<S: Serializer>(source: &BTreeSet<T>, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_set::<T, A, S>(source, serializer)
+    }
+}
+
+impl<'de, T: Ord, A: DeserializeAs<'de, T>> DeserializeAs<'de, BTreeSet<T>> for Set<A> {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<BTreeSet<T>, D::Error> {
+        BTreeSet::<A>::deserialize_as(deserializer)
+    }
+}
+
+impl<T, H, A: SerializeAs<T>> SerializeAs<HashSet<T, H>> for Set<A> {
+    fn serialize_as<S: Serializer>(
+        source: &HashSet<T, H>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serialize_set::<T, A, S>(source, serializer)
+    }
+}
+
+impl<'de, T, H, A> DeserializeAs<'de, HashSet<T, H>> for Set<A>
+where
+    T: Eq + Hash,
+    H: BuildHasher + Default,
+    A: DeserializeAs<'de, T>,
+{
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<HashSet<T, H>, D::Error> {
+        HashSet::<A, H>::deserialize_as(deserializer)
+    }
+}
+
+impl<K, V, KA, VA> SerializeAs<BTreeMap<K, V>> for Map<KA, VA>
+where
+    KA: SerializeAs<K>,
+    VA: SerializeAs<V>,
+{
+    fn serialize_as<S: Serializer>(
+        source: &BTreeMap<K, V>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serialize_map::<K, V, KA, VA, S>(source, serializer)
+    }
+}
+
+impl<'de, K, V, KA, VA> DeserializeAs<'de, BTreeMap<K, V>> for Map<KA, VA>
+where
+    K: Ord,
+    KA: DeserializeAs<'de, K>,
+    VA: DeserializeAs<'de, V>,
+{
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<BTreeMap<K, V>, D::Error> {
+        BTreeMap::<KA, VA>::deserialize_as(deserializer)
+    }
+}
+
+impl<K, V, H, KA, VA> SerializeAs<HashMap<K, V, H>> for Map<KA, VA>
+where
+    KA: SerializeAs<K>,
+    VA: SerializeAs<V>,
+{
+    fn serialize_as<S: Serializer>(
+        source: &HashMap<K, V, H>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serialize_map::<K, V, KA, VA, S>(source, serializer)
+    }
+}
+
+impl<'de, K, V, H, KA, VA> DeserializeAs<'de, HashMap<K, V, H>> for Map<KA, VA>
+where
+    K: Eq + Hash,
+    H: BuildHasher + Default,
+    KA: DeserializeAs<'de, K>,
+    VA: DeserializeAs<'de, V>,
+{
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<K, V, H>, D::Error> {
+        HashMap::<KA, VA, H>::deserialize_as(deserializer)
+    }
+}
+
+impl<K, V, KA, VA> SerializeAs<Vec<(K, V)>> for Map<KA, VA>
+where
+    KA: SerializeAs<K>,
+    VA: SerializeAs<V>,
+{
+    fn serialize_as<S: Serializer>(source: &Vec<(K, V)>, serializer: S) -> Result<S::Ok, S::Error> {
+        let entries = source.iter().map(|(key, value)| (key, value));
+        serialize_map::<K, V, KA, VA, S>(entries, serializer)
+    }
+}
+
+impl<'de, K, V, KA, VA> DeserializeAs<'de, Vec<(K, V)>> for Map<KA, VA>
+where
+    KA: DeserializeAs<'de, K>,
+    VA: DeserializeAs<'de, V>,
+{
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<Vec<(K, V)>, D::Error> {
+        serde_with::Map::<KA, VA>::deserialize_as(deserializer)
+    }
+}
+
+/// Decodes integers from Quint's numbers and big integers, as well as from their
+/// digits, as big integers are read when buffered by serde, e.g. in the variants
+/// of sum types.
+struct BigIntVisitor<T>(PhantomData<T>);
+
+impl<T: TryFrom<i128> + TryFrom<u128>> BigIntVisitor<T> {
+    fn convert<E: de::Error>(value: impl Into<i128> + fmt::Display + Copy) -> Result<T, E> {
+        T::try_from(value.into()).map_err(|_| {
+            E::custom(format!(
+                "Integer {} is out of range for `{}`",
+                value,
+                std::any::type_name::<T>()
+            ))
+        })
+    }
+}
+
+impl<'de, T: TryFrom<i128> + TryFrom<u128>> Visitor<'de> for BigIntVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Quint integer")
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<T, E> {
+        Self::convert(value)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<T, E> {
+        Self::convert(value)
+    }
+
+    fn visit_i128<E: de::Error>(self, value: i128) -> Result<T, E> {
+        Self::convert(value)
+    }
+
+    fn visit_u128<E: de::Error>(self, value: u128) -> Result<T, E> {
+        T::try_from(value).map_err(|_| {
+            E::custom(format!(
+                "Integer {} is out of range for `{}`",
+                value,
+                std::any::type_name::<T>()
+            ))
+        })
+    }
+
+    fn visit_str<E: de::Error>(self, digits: &str) -> Result<T, E> {
+        if let Ok(value) = digits.parse::<i128>() {
+            return self.visit_i128(value);
+        }
+        match digits.parse::<u128>() {
+            Ok(value) => self.visit_u128(value),
+            Err(_) => Err(E::invalid_value(de::Unexpected::Str(digits), &self)),
+        }
+    }
+}
+
+macro_rules! impl_bigint {
+    ($($ty:ty),*) => {$(
+        impl SerializeAs<$ty> for BigInt {
+            fn serialize_as<S: Serializer>(source: &$ty, serializer: S) -> Result<S::Ok, S::Error> {
+                Itf(&Value::BigInt(itf::value::BigInt::new(*source))).serialize(serializer)
+            }
+        }
+
+        impl<'de> DeserializeAs<'de, $ty> for BigInt {
+            fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<$ty, D::Error> {
+                deserializer.deserialize_any(BigIntVisitor(PhantomData))
+            }
+        }
+    )*};
+}
+
+impl_bigint!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+impl<T: Serialize> SerializeAs<T> for Tuple {
+    fn serialize_as<S: Serializer>(source: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        let elems = match encode::<T, Same, S::Error>(source)? {
+            Value::Tuple(elems) => elems,
+            Value::List(elems) => elems.into(),
+            other => {
+                return Err(ser::Error::custom(format!(
+                    "Expected a sequence to encode as a Quint tuple, got {:?}",
+                    other
+                )));
+            }
+        };
+        Itf(&Value::Tuple(elems)).serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeAs<'de, T> for Tuple {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        T::deserialize(deserializer)
+    }
+}
+
+impl<T: Serialize> SerializeAs<T> for Sum {
+    fn serialize_as<S: Serializer>(source: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        Itf(&encode::<T, Same, S::Error>(source)?).serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeAs<'de, T> for Sum {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        tagged::deserialize(deserializer)
+    }
+}
+
+impl<T, A: SerializeAs<T>> SerializeAs<Option<T>> for QuintOption<A> {
+    fn serialize_as<S: Serializer>(source: &Option<T>, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = match source {
+            Some(value) => variant("Some", encode::<T, A, S::Error>(value)?),
+            None => variant("None", unit()),
+        };
+        Itf(&value).serialize(serializer)
+    }
+}
+
+/// Defines a module for serde's `with` attribute encoding values with an adapter.
+macro_rules! with_module {
+    ($(#[$doc:meta])* $module:ident, $adapter:ty) => {
+        $(#[$doc])*
+        pub mod $module {
+            use super::*;
+
+            pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                $adapter: SerializeAs<T>,
+                S: Serializer,
+            {
+                <$adapter>::serialize_as(value, serializer)
+            }
+
+            pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+            where
+                $adapter: DeserializeAs<'de, T>,
+                D: Deserializer<'de>,
+            {
+                <$adapter>::deserialize_as(deserializer)
+            }
+        }
+    };
+}
+
+with_module!(
+    /// Encodes a field as a Quint set, see [`Set`].
+    set,
+    Set
+);
+with_module!(
+    /// Encodes a field as a Quint map, see [`Map`].
+    map,
+    Map
+);
+with_module!(
+    /// Encodes an integer field as a Quint big integer, see [`BigInt`].
+    bigint,
+    BigInt
+);
+with_module!(
+    /// Encodes a field as a Quint tuple, see [`Tuple`].
+    tuple,
+    Tuple
+);
+with_module!(
+    /// Encodes an enum field as a Quint sum type, see [`Sum`].
+    sum,
+    Sum
+);
+with_module!(
+    /// Encodes an optional field as Quint's `Option`, see [`QuintOption`].
+    option,
+    QuintOption<Same>
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Encodes a value as ITF JSON and decodes it back from the ITF value Quint
+    /// traces are read into.
+    fn roundtrip<T, A>(value: T, expected: serde_json::Value) -> T
+    where
+        A: SerializeAs<T> + for<'de> DeserializeAs<'de, T>,
+    {
+        let json = serde_json::to_value(Quint::<T, A>::new(value)).unwrap();
+        assert_eq!(json, expected);
+        let value: Value = serde_json::from_value(json).unwrap();
+        Quint::<T, A>::deserialize(value).unwrap().into_inner()
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+    struct Account {
+        owner: String,
+        id: u64,
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    enum Msg {
+        Ping,
+        Vote(u64),
+        Pair(u64, bool),
+        Transfer { from: String, amount: u64 },
+    }
+
+    #[test]
+    fn test_sets_of_records() {
+        let accounts = vec![Account {
+            owner: "alice".to_string(),
+            id: 1,
+        }];
+        let expected = json!({ "#set": [{ "id": 1, "owner": "alice" }] });
+        let decoded = roundtrip::<_, Set>(accounts, expected);
+        assert_eq!(decoded[0].owner, "alice");
+
+        let set = HashSet::from([(1, 2)]);
+        let expected = json!({ "#set": [{ "#tup": [1, 2] }] });
+        assert_eq!(roundtrip::<_, Set>(set.clone(), expected), set);
+    }
+
+    #[test]
+    fn test_maps_with_compound_keys() {
+        let board = BTreeMap::from([((1, 2), "X".to_string())]);
+        let expected = json!({ "#map": [[{ "#tup": [1, 2] }, "X"]] });
+        assert_eq!(roundtrip::<_, Map>(board.clone(), expected), board);
+
+        let key = Account {
+            owner: "bob".to_string(),
+            id: 2,
+        };
+        let balances = HashMap::from([(key, 10u128)]);
+        let expected = json!({
+            "#map": [[{ "id": 2, "owner": "bob" }, { "#bigint": "10" }]]
+        });
+        let decoded = roundtrip::<_, Map<Same, BigInt>>(balances, expected);
+        assert_eq!(decoded.values().copied().collect::<Vec<_>>(), [10]);
+
+        let pairs = vec![("a".to_string(), 1), ("b".to_string(), 2)];
+        let expected = json!({ "#map": [["a", 1], ["b", 2]] });
+        assert_eq!(roundtrip::<_, Map>(pairs.clone(), expected), pairs);
+    }
+
+    #[test]
+    fn test_big_integers() {
+        let expected = json!({ "#bigint": u128::MAX.to_string() });
+        assert_eq!(roundtrip::<_, BigInt>(u128::MAX, expected), u128::MAX);
+        let expected = json!({ "#bigint": i128::MIN.to_string() });
+        assert_eq!(roundtrip::<_, BigInt>(i128::MIN, expected), i128::MIN);
+        assert_eq!(
+            Quint::<u8, BigInt>::deserialize(Value::Number(7))
+                .unwrap()
+                .into_inner(),
+            7
+        );
+
+        let err = Quint::<u8, BigInt>::deserialize(Value::Number(300)).unwrap_err();
+        assert_eq!(err.to_string(), "Integer 300 is out of range for `u8`");
+    }
+
+    #[test]
+    fn test_sum_types() {
+        let expected = json!({ "tag": "Ping", "value": { "#tup": [] } });
+        assert_eq!(roundtrip::<_, Sum>(Msg::Ping, expected), Msg::Ping);
+
+        let expected = json!({ "tag": "Vote", "value": 3 });
+        assert_eq!(roundtrip::<_, Sum>(Msg::Vote(3), expected), Msg::Vote(3));
+
+        let expected = json!({ "tag": "Pair", "value": { "#tup": [1, true] } });
+        let pair = Msg::Pair(1, true);
+        assert_eq!(roundtrip::<_, Sum>(pair, expected), Msg::Pair(1, true));
+
+        let transfer = Msg::Transfer {
+            from: "alice".to_string(),
+            amount: 5,
+        };
+        let expected = json!({
+            "tag": "Transfer",
+            "value": { "amount": 5, "from": "alice" }
+        });
+        let decoded = roundtrip::<_, Sum>(transfer, expected);
+        assert!(matches!(decoded, Msg::Transfer { amount: 5, .. }));
+
+        let msgs = vec![Msg::Ping, Msg::Vote(1)];
+        let expected = json!({
+            "#set": [
+                { "tag": "Ping", "value": { "#tup": [] } },
+                { "tag": "Vote", "value": 1 }
+            ]
+        });
+        assert_eq!(roundtrip::<_, Set<Sum>>(msgs, expected).len(), 2);
+    }
+
+    #[test]
+    fn test_tuples_and_options() {
+        let expected = json!({ "#tup": [1, 2, 3] });
+        assert_eq!(roundtrip::<_, Tuple>(vec![1, 2, 3], expected), [1, 2, 3]);
+        let expected = json!({ "#tup": ["a", true] });
+        let pair = ("a".to_string(), true);
+        assert_eq!(roundtrip::<_, Tuple>(pair.clone(), expected), pair);
+
+        let expected = json!({ "tag": "Some", "value": { "#bigint": "5" } });
+        let value = roundtrip::<_, QuintOption<BigInt>>(Some(5u64), expected);
+        assert_eq!(value, Some(5));
+        let expected = json!({ "tag": "None", "value": { "#tup": [] } });
+        assert_eq!(
+            roundtrip::<_, QuintOption<Same>>(None::<u64>, expected),
+            None
+        );
+    }
+
+    #[test]
+    fn test_sum_type_errors() {
+        let mut rec = itf::value::Record::new();
+        rec.insert("value".to_string(), Value::Number(1));
+        let err = Quint::<Msg, Sum>::deserialize(Value::Record(rec)).unwrap_err();
+        assert_eq!(err.to_string(), "missing field `tag`");
+
+        let err = Quint::<Msg, Sum>::deserialize(Value::Number(1)).unwrap_err();
+        assert!(err.to_string().contains("Quint sum type"), "{}", err);
+    }
+}
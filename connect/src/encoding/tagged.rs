@@ -0,0 +1,128 @@
+//! Decoding of Rust enums from Quint's sum types, records with a `tag` naming the
+//! variant and a `value` holding its content.
+//!
+//! The record is read as a struct, and its value is handed to the enum as the
+//! content of the variant named by the tag, without buffering it.
+
+use serde::{
+    Deserialize, Deserializer,
+    de::{
+        self, DeserializeSeed, EnumAccess, IgnoredAny, IntoDeserializer, MapAccess, VariantAccess,
+        Visitor,
+    },
+    forward_to_deserialize_any,
+};
+use std::{fmt, marker::PhantomData};
+
+const FIELDS: &[&str] = &["tag", "value"];
+
+pub(super) fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_struct("Sum", FIELDS, SumVisitor(PhantomData))
+}
+
+struct SumVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for SumVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Quint sum type with `tag` and `value` fields")
+    }
+
+    fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<T, M::Error> {
+        // Records are ordered by field name, and Quint writes the tag first.
+        let tag = match map.next_key::<String>()?.as_deref() {
+            Some("tag") => map.next_value::<String>()?,
+            _ => return Err(de::Error::missing_field("tag")),
+        };
+        match map.next_key::<String>()?.as_deref() {
+            Some("value") => map.next_value_seed(VariantSeed {
+                tag,
+                marker: PhantomData,
+            }),
+            _ => Err(de::Error::missing_field("value")),
+        }
+    }
+}
+
+/// Decodes the enum from the value of the variant named `tag`.
+struct VariantSeed<T> {
+    tag: String,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeSeed<'de> for VariantSeed<T> {
+    type Value = T;
+
+    fn deserialize<D: Deserializer<'de>>(self, value: D) -> Result<T, D::Error> {
+        T::deserialize(Variant {
+            tag: self.tag,
+            value,
+        })
+    }
+}
+
+/// An enum with the variant `tag`, whose content is deserialized from `value`.
+struct Variant<D> {
+    tag: String,
+    value: D,
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for Variant<D> {
+    type Error = D::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        visitor.visit_enum(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes
+        byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct map
+        struct enum identifier ignored_any
+    }
+}
+
+impl<'de, D: Deserializer<'de>> EnumAccess<'de> for Variant<D> {
+    type Error = D::Error;
+    type Variant = Content<D>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Content<D>), D::Error> {
+        let tag = seed.deserialize(self.tag.into_deserializer())?;
+        Ok((tag, Content(self.value)))
+    }
+}
+
+/// The content of a variant, the value of a sum type.
+struct Content<D>(D);
+
+impl<'de, D: Deserializer<'de>> VariantAccess<'de> for Content<D> {
+    type Error = D::Error;
+
+    fn unit_variant(self) -> Result<(), D::Error> {
+        // The empty tuple in Quint.
+        IgnoredAny::deserialize(self.0).map(|_| ())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, D::Error> {
+        seed.deserialize(self.0)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, D::Error> {
+        self.0.deserialize_tuple(len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.0.deserialize_struct("", fields, visitor)
+    }
+}
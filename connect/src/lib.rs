@@ -1,7 +1,16 @@
 #![doc = include_str!("../README.md")]
+// Build script examples show their `main` function.
+#![allow(clippy::needless_doctest_main)]
 
+pub mod build;
+#[doc(alias = "serde")]
+pub mod encoding;
+
+mod debugger;
 mod driver;
 mod logger;
+mod report;
+mod settings;
 mod trace;
 mod value;
 
@@ -9,7 +18,12 @@ mod value;
 #[doc(hidden)]
 pub mod runner;
 
-pub use driver::{Config, Driver, Path, Result, State, Step};
+pub use driver::{
+    Action, Config, Driver, Path, Projected, Projection, Result, Sequence, SequenceMessage, State,
+    StateMachine, Step, StepBuilder, TraceBuilder,
+};
+pub use runner::replay;
+pub use value::ValueDisplay;
 
 /// Generates a test that runs multiple random traces by simulating a Quint specification.
 ///
@@ -19,13 +33,25 @@ pub use driver::{Config, Driver, Path, Result, State, Step};
 ///
 /// # Attributes
 ///
-/// - **`spec`** (required): Path to the Quint specification file
+/// - **`spec`** (required): Path to the Quint specification file, relative to the
+///   crate's root
 /// - **`main`**: Name of the main module to run (defaults to Quint's default)
 /// - **`init`**: Name of the init action (defaults to Quint's default)
 /// - **`step`**: Name of the step action (defaults to Quint's default)
 /// - **`max_samples`**: Maximum number of traces to generate (defaults to 100)
 /// - **`max_steps`**: Maximum number of steps per trace (defaults to Quint's default)
 /// - **`seed`**: Random seed for reproducibility (defaults to random)
+/// - **`consts`**: Constants to instantiate a parameterized main module with, e.g.
+///   `consts(N = 3, NODES = Set("a", "b"))`, with values written as Quint expressions
+/// - **`matrix`**: Lists of values for any of the attributes above but `spec`, e.g.
+///   `matrix(max_steps = [5, 20], main = ["small", "large"])`. Each combination
+///   expands into a separate test named after its values, such as
+///   `test_simulation::max_steps_5_main_small`. Constants are given as
+///   `consts = [(N = 3), (N = 5)]` and named after their position, e.g. `consts_0`.
+///
+/// Environment variables take precedence over these attributes, except for `seed`,
+/// while settings from `quint-connect.toml` apply when attributes are omitted. See the
+/// [Project Configuration](crate#project-configuration) section in the crate docs.
 ///
 /// # Examples
 ///
@@ -40,7 +66,7 @@ pub use driver::{Config, Driver, Path, Result, State, Step};
 /// #     fn step(&mut self, _step: &Step) -> Result { Ok(()) }
 /// # }
 ///
-/// #[quint_run(spec = "spec.qnt")]
+/// #[quint_run(spec = "examples/tictactoe/spec/tictactoe.qnt")]
 /// fn test_simulation() -> impl Driver {
 ///     MyDriver::default()
 /// }
@@ -58,9 +84,10 @@ pub use driver::{Config, Driver, Path, Result, State, Step};
 /// # }
 ///
 /// #[quint_run(
-///     spec = "spec.qnt",
-///     main = "simulation",
-///     init = "myInit",
+///     spec = "examples/tictactoe/spec/tictactoe.qnt",
+///     main = "tictactoe",
+///     init = "init",
+///     step = "MoveX",
 ///     max_samples = 50,
 ///     max_steps = 100
 /// )]
@@ -78,11 +105,21 @@ pub use quint_connect_macros::quint_run;
 ///
 /// # Attributes
 ///
-/// - **`spec`** (required): Path to the Quint specification file
+/// - **`spec`** (required): Path to the Quint specification file, relative to the
+///   crate's root
 /// - **`test`** (required): Name of the Quint test to run
 /// - **`main`**: Name of the main module containing the test (defaults to Quint's default)
 /// - **`max_samples`**: Maximum number of test runs (defaults to 100)
 /// - **`seed`**: Random seed for reproducibility (defaults to random)
+/// - **`consts`**: Constants to instantiate a parameterized main module with, e.g.
+///   `consts(N = 3, NODES = Set("a", "b"))`, with values written as Quint expressions
+/// - **`matrix`**: Lists of values for any of the attributes above but `spec`, e.g.
+///   `matrix(test = ["happyPathTest", "timeoutTest"])`. Each combination expands
+///   into a separate test named after its values. See [`quint_run`] for details.
+///
+/// Environment variables take precedence over these attributes, except for `seed`,
+/// while settings from `quint-connect.toml` apply when attributes are omitted. See the
+/// [Project Configuration](crate#project-configuration) section in the crate docs.
 ///
 /// # Examples
 ///
@@ -97,7 +134,10 @@ pub use quint_connect_macros::quint_run;
 /// #     fn step(&mut self, _step: &Step) -> Result { Ok(()) }
 /// # }
 ///
-/// #[quint_test(spec = "spec.qnt", test = "myTest")]
+/// #[quint_test(
+///     spec = "examples/two_phase_commit/spec/two_phase_commit.qnt",
+///     test = "commitTest"
+/// )]
 /// fn test_my_test() -> impl Driver {
 ///     MyDriver::default()
 /// }
@@ -115,9 +155,9 @@ pub use quint_connect_macros::quint_run;
 /// # }
 ///
 /// #[quint_test(
-///     spec = "spec.qnt",
-///     test = "happyPathTest",
-///     main = "tests",
+///     spec = "examples/two_phase_commit/spec/two_phase_commit.qnt",
+///     test = "commitTest",
+///     main = "two_phase_commit",
 ///     max_samples = 10
 /// )]
 /// fn test_happy_path() -> impl Driver {
@@ -126,6 +166,201 @@ pub use quint_connect_macros::quint_run;
 /// ```
 pub use quint_connect_macros::quint_test;
 
+/// Generates one test per `run` definition in a Quint specification.
+///
+/// This macro reads the spec at compile time and expands into a [`quint_test`]-like
+/// test for every `run` in its main module, including those it imports, named after
+/// the run. Runs reached through qualified imports, such as `choreo::happyTest`, give
+/// tests named `choreo__happyTest`. Runs added to the spec are picked up on the next
+/// build, and compilation fails if the module has no runs.
+///
+/// # Attributes
+///
+/// - **`spec`** (required): Path to the Quint specification file, relative to the
+///   crate's root
+/// - **`driver`** (required): A function or closure returning the [`Driver`] to use
+/// - **`main`**: Name of the main module containing the runs (defaults to Quint's
+///   default)
+/// - **`max_samples`**: Maximum number of test runs (defaults to 100)
+/// - **`seed`**: Random seed for reproducibility (defaults to random)
+/// - **`consts`**: Constants to instantiate a parameterized main module with, e.g.
+///   `consts(N = 3, NODES = Set("a", "b"))`, with values written as Quint expressions
+///
+/// Runs are found by scanning the spec's sources, following its imports, so Quint
+/// is not needed to build the tests and the same tests are generated either way.
+/// Each test's settings in `quint-connect.toml` are looked up by run name.
+///
+/// # Examples
+///
+/// ```rust
+/// use quint_connect::*;
+/// # #[derive(Default)]
+/// # struct MyDriver;
+/// # impl Driver for MyDriver {
+/// #     type State = ();
+/// #     fn step(&mut self, _step: &Step) -> Result { Ok(()) }
+/// # }
+///
+/// quint_tests!(
+///     spec = "examples/two_phase_commit/spec/two_phase_commit.qnt",
+///     driver = MyDriver::default
+/// );
+/// ```
+pub use quint_connect_macros::quint_tests;
+
+/// Checks the [`switch!`] cases of a [`Driver`] implementation against a Quint
+/// specification at compile time.
+///
+/// This attribute macro goes on the `impl Driver` block. It reads the spec and fails
+/// compilation if a case names an action that doesn't exist, or a nondeterministic
+/// pick that the action never binds with `nondet`. It also warns about actions the
+/// spec can take that no case handles, unless the switch has a `_` catch-all case.
+///
+/// # Attributes
+///
+/// - **`spec`** (required): Path to the Quint specification file, relative to the
+///   crate's root
+/// - **`main`**: Name of the main module (defaults to the file name)
+/// - **`init`**: Name of the init action (defaults to `init`)
+/// - **`step`**: Name of the step action (defaults to `step`)
+///
+/// Actions the spec can take are the init action and the branches of `any` blocks
+/// reachable from the step action. Both they and the nondet picks are read from
+/// the spec as compiled by Quint, so only action names are checked where Quint
+/// isn't installed. The checks only apply to drivers using Quint's built-in
+/// `mbt::actionTaken` and `mbt::nondetPicks` variables, not to drivers reading
+/// them from a custom [`Config`].
+///
+/// # Examples
+///
+/// ```rust
+/// use quint_connect::*;
+/// # type Position = (i64, i64);
+/// # struct TicTacToeDriver;
+/// # impl TicTacToeDriver {
+/// #     fn init(&mut self) {}
+/// #     fn move_x(&mut self, _corner: Option<Position>, _coordinate: Option<Position>) {}
+/// #     fn move_o(&mut self, _coordinate: Position) {}
+/// # }
+///
+/// #[quint_driver(spec = "examples/tictactoe/spec/tictactoe.qnt")]
+/// impl Driver for TicTacToeDriver {
+///     type State = ();
+///
+///     fn step(&mut self, step: &Step) -> Result {
+///         switch!(step {
+///             init,
+///             MoveX(corner?, coordinate?) => self.move_x(corner, coordinate),
+///             MoveO(coordinate) => self.move_o(coordinate),
+///             _ => {}
+///         })
+///     }
+/// }
+/// ```
+pub use quint_connect_macros::quint_driver;
+
+/// Derives [`Deserialize`](serde::Deserialize) for a type following Quint's encoding,
+/// and optionally implements [`State`] for a driver.
+///
+/// The derive replaces hand-written serde attributes on state types:
+///
+/// - Field names are converted to camelCase, e.g. `next_turn` reads `nextTurn`
+/// - Enums are decoded as Quint sum types, with the `tag` and `value` fields
+/// - [`Option`] values are decoded from Quint's `Option` type, also when nested in
+///   collections, tuples, or other options
+/// - Sets decode into [`Vec`] or set types, maps into map types, and tuples into
+///   Rust tuples
+/// - Integers decode from both plain numbers and big integers, e.g. into [`i128`]
+///
+/// Nested types must also derive `QuintState`, or implement `Deserialize` by hand.
+/// Generic types are supported, while borrowed types are not.
+///
+/// # Attributes
+///
+/// - **`#[quint(rename = "name")]`**: On fields and variants, the name in the spec
+/// - **`#[quint(as = "Adapter")]`**: On fields, the [`serde_with`] adapter decoding
+///   the field instead of the computed one, e.g. one of [`encoding`] for fields of
+///   types that don't derive `QuintState`
+/// - **`#[quint(driver = Type, from_driver = expr)]`**: On the type, implements
+///   [`State<Type>`](State) with `expr` as [`State::from_driver`]. The expression
+///   is called with a reference to the driver, e.g. a function path or a closure,
+///   and must return a [`Result`] of the state.
+///
+/// # Examples
+///
+/// ```rust
+/// use quint_connect::*;
+/// use std::collections::BTreeMap;
+/// # #[derive(Default)]
+/// # struct Game { board: BTreeMap<u64, Square>, next_turn: Player, winner: Option<Player> }
+///
+/// #[derive(QuintState, Clone, Copy, Default, PartialEq, Debug)]
+/// enum Player {
+///     #[default]
+///     X,
+///     O,
+/// }
+///
+/// #[derive(QuintState, Clone, Copy, PartialEq, Debug)]
+/// enum Square {
+///     Occupied(Player),
+///     Empty,
+/// }
+///
+/// #[derive(QuintState, PartialEq, Debug)]
+/// #[quint(driver = GameDriver, from_driver = |driver| Ok(Self {
+///     board: driver.game.board.clone(),
+///     next_turn: driver.game.next_turn,
+///     winner: driver.game.winner,
+/// }))]
+/// struct GameState {
+///     board: BTreeMap<u64, Square>,
+///     next_turn: Player,    // reads `nextTurn`
+///     winner: Option<Player>,
+/// }
+///
+/// #[derive(Default)]
+/// struct GameDriver {
+///     game: Game,
+/// }
+/// ```
+pub use quint_connect_macros::QuintState;
+
+/// Derives [`Action`] for an enum with a variant per action of the specification.
+///
+/// Each variant matches the action with its name, or with its name starting with a
+/// lowercase letter, e.g. `Init` matches `init`. Named fields are decoded from the
+/// action's nondeterministic picks, with field names converted to camelCase. Fields
+/// of type [`Option`] are optional picks, while missing required picks are an
+/// error. Unit variants are actions without picks.
+///
+/// Decode steps with [`Step::decode_action`], or implement [`StateMachine`] to get a
+/// [`Driver`] that applies the decoded actions.
+///
+/// # Attributes
+///
+/// - **`#[quint(rename = "name")]`**: On variants, the action's name in the spec.
+///   On fields, the name of the nondeterministic pick.
+/// - **`#[quint(as = "Adapter")]`**: On fields, the [`serde_with`] adapter decoding
+///   the pick, or the value of an optional pick, e.g. one of [`encoding`].
+///
+/// # Examples
+///
+/// ```rust
+/// use quint_connect::*;
+/// # type Position = (usize, usize);
+///
+/// #[derive(QuintAction, Clone, Debug)]
+/// enum TicTacToeAction {
+///     Init,                                // matches `init`
+///     MoveX { corner: Option<Position> },  // optional pick
+///     MoveO { coordinate: Position },
+///     #[quint(rename = "stuttered")]
+///     Stutter,
+/// }
+/// ```
+pub use quint_connect_macros::QuintAction;
+
 /// Pattern-matches on action names and extracts nondeterministic picks from a [`Step`].
 ///
 /// This macro simplifies the implementation of [`Driver::step`] by providing a convenient
@@ -141,10 +376,20 @@ pub use quint_connect_macros::quint_test;
 ///     action_name(param?),                    // Optional parameter (Option<_>)
 ///     action_name(param: Type?),              // Optional parameter with explicit type
 ///     action_name(p1, p2: Type, p3?) => expr, // Custom handler expression
+///     action_name(param) => try expr,         // Fallible handler returning a Result
+///     action1(p) | action2(p) => expr,        // Alternatives sharing a handler
+///     action_name(param) if cond => expr,     // Guard on decoded parameters
+///     action_name(param as name),             // Parameter bound to another name
+///     action_name(param: Type @ pattern),     // Parameter value matching a pattern
+///     action_name(..args: Type),              // All parameters decoded into a struct
 ///     _ => expr,                              // Catch-all for unmatched actions
 /// })
 /// ```
 ///
+/// Cases are tried in order. A case matches when the step's action has its name, its
+/// required parameters are present and match their patterns, and its guard holds.
+/// Otherwise, the next case is tried.
+///
 /// # Parameter Extraction
 ///
 /// Parameters are extracted by name and deserialized using [`serde::Deserialize`]. The
@@ -154,13 +399,51 @@ pub use quint_connect_macros::quint_test;
 ///   is missing.
 /// - **Optional parameters** (e.g., `param?` or `param: Type?`): Produces an `Option<Type>`,
 ///   with `None` if the parameter is missing.
+/// - **Renamed parameters** (e.g., `param as name`): Binds the parameter to `name`, which
+///   allows alternatives to bind different parameters under the same name.
+/// - **Patterns** (e.g., `param: Type @ pattern`): The case only matches if the decoded
+///   value matches the pattern. Bindings in the pattern are not available to handlers.
+/// - **All parameters** (e.g., `..args` or `..args: Type`): Decodes every parameter of the
+///   action into a struct with a field per parameter. Parameters missing from the step
+///   decode as `None` into `Option` fields, and parameters the struct doesn't declare
+///   fail the step.
+///
+/// # Alternatives and Guards
+///
+/// Alternatives separated by `|` share the handler that follows them, and must bind
+/// the parameters it uses. An alternative can repeat an action with different
+/// parameters, since it only matches if they are present. Guards after `if` can use
+/// the decoded parameters to refine the match.
 ///
 /// # Handler Expressions
 ///
 /// Each case can have a handler expression after `=>`:
 ///
 /// - If no handler is provided, the macro generates a call to `self.action_name(params...)`.
-/// - With a handler, you can provide custom logic, including blocks of code.
+///   If the method returns a `Result`, its error fails the step.
+/// - With a handler, you can provide custom logic, including blocks of code. Its value
+///   is discarded.
+/// - With `try` before the handler, it must evaluate to a `Result` whose error fails the
+///   step. This allows using `?` in handler blocks.
+///
+/// Errors returned by handlers are reported along with the action being handled, e.g.
+/// ``Failed to handle action `withdraw` ``. Error types must convert into
+/// [`anyhow::Error`].
+///
+/// # Naming Conventions
+///
+/// By default, implicit handlers call methods named exactly like the action, and
+/// parameters are looked up by the name they are bound to. With `rename = snake_case`,
+/// implicit handlers call the `snake_case` version of the action's name, and
+/// `snake_case` parameters are looked up by their `camelCase` name in the spec:
+///
+/// ```text
+/// switch!(step, rename = snake_case {
+///     SpontaneouslyPrepares(node_id), // Calls self.spontaneously_prepares(node_id),
+/// })                                  // with the `nodeId` nondet pick
+/// ```
+///
+/// Action names in cases are still written as in the spec.
 ///
 /// # Catch-All Pattern
 ///
@@ -212,6 +495,61 @@ pub use quint_connect_macros::quint_test;
 /// }
 /// ```
 ///
+/// Using fallible handlers:
+///
+/// ```rust
+/// use quint_connect::*;
+/// # struct MyDriver { balance: i64 }
+///
+/// impl MyDriver {
+///     fn deposit(&mut self, amount: i64) -> Result {
+///         anyhow::ensure!(amount > 0, "Invalid amount: {}", amount);
+///         self.balance += amount;
+///         Ok(())
+///     }
+/// }
+///
+/// impl Driver for MyDriver {
+///     type State = ();
+///
+///     fn step(&mut self, step: &Step) -> Result {
+///         switch!(step {
+///             deposit(amount),                        // Fails the step on error
+///             depositTwice(amount: i64) => try {
+///                 self.deposit(amount)?;
+///                 self.deposit(amount)
+///             },
+///         })
+///     }
+/// }
+/// ```
+///
+/// Using alternatives, guards and patterns:
+///
+/// ```rust
+/// use quint_connect::*;
+/// # struct MyDriver;
+/// # impl MyDriver {
+/// #     fn decide(&mut self, node: String, commit: bool) {}
+/// #     fn move_to(&mut self, pos: (u8, u8)) {}
+/// # }
+///
+/// impl Driver for MyDriver {
+///     type State = ();
+///
+///     fn step(&mut self, step: &Step) -> Result {
+///         switch!(step {
+///             DecidesOnCommit(node) => self.decide(node, true),
+///             DecidesOnAbort(node) | Timeout(node) => self.decide(node, false),
+///             Move(corner as pos) | Move(coordinate as pos) => self.move_to(pos),
+///             Move => self.move_to((2, 2)),
+///             Jump(pos: (u8, u8) @ (1, _)) => self.move_to(pos),
+///             Jump(pos: (u8, u8)) if pos.0 == pos.1 => self.move_to(pos),
+///         })
+///     }
+/// }
+/// ```
+///
 /// Using custom handlers:
 ///
 /// ```rust
@@ -3,7 +3,20 @@ mod util;
 pub(crate) use colored::Colorize;
 pub(crate) use util::*;
 
-pub(crate) const VERBOSITY: Option<&str> = option_env!("QUINT_VERBOSE");
+use std::cell::Cell;
+
+thread_local! {
+    // Each test runs on its own thread with its own settings.
+    static VERBOSITY: Cell<u8> = const { Cell::new(0) };
+}
+
+pub(crate) fn verbosity() -> u8 {
+    VERBOSITY.get()
+}
+
+pub(crate) fn set_verbosity(level: u8) {
+    VERBOSITY.set(level);
+}
 
 macro_rules! title {
     ($fmt:literal $(, $args:expr)*) => {
@@ -24,6 +37,12 @@ macro_rules! success {
     };
 }
 
+macro_rules! warning {
+    ($fmt:literal $(, $args:expr)*) => {
+        eprintln!("{}", crate::logger::indent!(3, $fmt $(,$args)*).bold().yellow());
+    };
+}
+
 macro_rules! error {
     ($fmt:literal $(, $args:expr)*) => {
         eprintln!("{}", crate::logger::indent!(3, $fmt $(,$args)*).bold().red());
@@ -32,7 +51,7 @@ macro_rules! error {
 
 macro_rules! trace {
     ($level:literal, $fmt:literal $(, $args:expr)*) => {
-        if VERBOSITY.unwrap_or("0") >= stringify!($level) {
+        if crate::logger::verbosity() >= $level {
             eprintln!("{}", crate::logger::indent!(3, $fmt $(,$args)*).dimmed().bright_white());
         }
     };
@@ -43,3 +62,22 @@ pub(crate) use info;
 pub(crate) use success;
 pub(crate) use title;
 pub(crate) use trace;
+pub(crate) use warning;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_verbosity_is_per_thread() {
+        set_verbosity(2);
+        thread::spawn(|| {
+            assert_eq!(verbosity(), 0);
+            set_verbosity(1);
+        })
+        .join()
+        .unwrap();
+        assert_eq!(verbosity(), 2);
+    }
+}
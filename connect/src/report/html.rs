@@ -0,0 +1,354 @@
+use crate::{
+    report::{Report, StepRecord},
+    value::ValueDisplay,
+};
+use itf::value::{Record, Value};
+use std::fmt::Write;
+
+/// Values whose Quint representation is shorter than this are rendered inline
+/// rather than as a collapsible tree.
+const INLINE_WIDTH: usize = 60;
+
+/// Nested values deeper than this start collapsed.
+const OPEN_DEPTH: usize = 1;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+code, pre { font-family: monospace; }
+h1 { font-size: 1.4em; }
+table.meta td { padding: 0.2em 1em 0.2em 0; vertical-align: top; }
+section.step { border: 1px solid #ccc; border-radius: 4px; margin: 0.5em 0; padding: 0.2em 0.8em; }
+section.step.diverged { border: 2px solid #c00; background: #fff5f5; }
+section.step > details > summary { font-weight: bold; cursor: pointer; }
+.states { display: flex; gap: 2em; }
+.states > div { flex: 1; min-width: 0; }
+ul.tree { list-style: none; padding-left: 1.2em; margin: 0; }
+details > summary { cursor: pointer; }
+.key { color: #005cc5; }
+.meta { color: #888; font-size: 0.9em; }
+.error { color: #c00; white-space: pre-wrap; }
+.diff .add { color: #22863a; background: #f0fff4; }
+.diff .del { color: #cb2431; background: #ffeef0; }
+";
+
+/// Renders a self-contained HTML page describing a failed trace replay.
+pub(crate) fn render(report: &Report) -> String {
+    let mut out = String::new();
+    let title = format!("Quint Connect: {}", report.test_name);
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        escape(&title),
+        STYLE,
+        escape(&title)
+    );
+
+    let _ = write!(
+        out,
+        "<table class=\"meta\">\n\
+         <tr><td>Seed</td><td><code>{}</code></td></tr>\n\
+         <tr><td>Trace</td><td>{}</td></tr>\n\
+         <tr><td>Configuration</td><td><pre>{}</pre></td></tr>\n\
+         </table>\n",
+        escape(report.seed),
+        report.trace,
+        escape(report.config),
+    );
+
+    let _ = write!(
+        out,
+        "<h2>Error</h2>\n<pre class=\"error\">{}</pre>\n",
+        escape(&report.error)
+    );
+
+    let _ = writeln!(out, "<h2>Timeline</h2>");
+    if report.steps.is_empty() {
+        let _ = writeln!(out, "<p class=\"meta\">No steps were replayed.</p>");
+    }
+    for (index, step) in report.steps.iter().enumerate() {
+        let failed = report.failed_step == Some(index);
+        write_step(&mut out, index, step, failed, report);
+    }
+    if let Some(failed) = report.failed_step
+        && failed >= report.steps.len()
+    {
+        let _ = writeln!(
+            out,
+            "<p class=\"meta\">Step {} failed before it could be replayed.</p>",
+            failed
+        );
+    }
+
+    let _ = writeln!(out, "</body>\n</html>");
+    out
+}
+
+fn write_step(out: &mut String, index: usize, step: &StepRecord, failed: bool, report: &Report) {
+    let class = if failed { "step diverged" } else { "step" };
+    let action = if step.action.is_empty() {
+        "<anonymous>"
+    } else {
+        step.action.as_str()
+    };
+
+    let _ = write!(
+        out,
+        "<section class=\"{}\" id=\"step-{}\">\n<details{}>\n<summary>[Step {}] {}",
+        class,
+        index,
+        if failed { " open" } else { "" },
+        index,
+        escape(action),
+    );
+    if !step.nondet_picks.is_empty() {
+        let picks: Vec<_> = step
+            .nondet_picks
+            .iter()
+            .map(|(name, value)| format!("{}: {}", name, value.display()))
+            .collect();
+        let _ = write!(out, "({})", escape(&picks.join(", ")));
+    }
+    let _ = writeln!(out, "</summary>");
+
+    if failed {
+        if let Some(diff) = report.diff {
+            let _ = writeln!(out, "<h3>Divergence</h3>");
+            write_diff(out, diff);
+        } else {
+            let _ = writeln!(
+                out,
+                "<p class=\"error\">This step failed. See the error above.</p>"
+            );
+        }
+    }
+
+    if !step.nondet_picks.is_empty() {
+        let _ = writeln!(out, "<h3>Nondet picks</h3>\n<ul class=\"tree\">");
+        for (name, value) in &step.nondet_picks {
+            write_entry(out, &format!("{}:", escape(name)), value, 1);
+        }
+        let _ = writeln!(out, "</ul>");
+    }
+
+    let _ = writeln!(
+        out,
+        "<div class=\"states\">\n<div>\n<h3>Specification state</h3>"
+    );
+    write_value(out, &step.spec_state, 0);
+    let _ = writeln!(out, "</div>\n<div>\n<h3>Implementation state</h3>");
+    match &step.impl_state {
+        Some(state) => {
+            let _ = writeln!(out, "<pre>{}</pre>", escape(state));
+        }
+        None => {
+            let _ = writeln!(out, "<p class=\"meta\">Not available.</p>");
+        }
+    }
+    let _ = writeln!(out, "</div>\n</div>\n</details>\n</section>");
+}
+
+fn write_diff(out: &mut String, diff: &str) {
+    let _ = writeln!(out, "<pre class=\"diff\">");
+    for line in diff.lines() {
+        let class = if line.starts_with('+') && !line.starts_with("+++") {
+            Some("add")
+        } else if line.starts_with('-') && !line.starts_with("---") {
+            Some("del")
+        } else {
+            None
+        };
+        match class {
+            Some(class) => {
+                let _ = writeln!(out, "<span class=\"{}\">{}</span>", class, escape(line));
+            }
+            None => {
+                let _ = writeln!(out, "{}", escape(line));
+            }
+        }
+    }
+    let _ = writeln!(out, "</pre>");
+}
+
+/// Writes a value as a tree of collapsible `<details>` elements. Small values
+/// are rendered inline using their Quint representation.
+fn write_value(out: &mut String, value: &Value, depth: usize) {
+    let compact = value.display().to_string();
+    if compact.len() <= INLINE_WIDTH || !is_collection(value) {
+        let _ = write!(out, "<code>{}</code>", escape(&compact));
+        return;
+    }
+
+    if let Value::Record(rec) = value
+        && let Some((tag, payload)) = as_variant(rec)
+    {
+        let _ = write!(
+            out,
+            "<details{}><summary><code>{}(…)</code></summary>\n<ul class=\"tree\">\n<li>",
+            open_attr(depth),
+            escape(tag)
+        );
+        write_value(out, payload, depth + 1);
+        let _ = writeln!(out, "</li>\n</ul>\n</details>");
+        return;
+    }
+
+    let (label, len) = match value {
+        Value::Record(rec) => ("{ … }", rec.len()),
+        Value::Map(map) => ("Map(…)", map.len()),
+        Value::Set(set) => ("Set(…)", set.iter().count()),
+        Value::List(list) => ("List(…)", list.len()),
+        Value::Tuple(tuple) => ("(…)", tuple.len()),
+        _ => unreachable!("scalar values are rendered inline"),
+    };
+
+    let _ = writeln!(
+        out,
+        "<details{}><summary><code>{}</code> <span class=\"meta\">{} {}</span></summary>\n\
+         <ul class=\"tree\">",
+        open_attr(depth),
+        label,
+        len,
+        if len == 1 { "entry" } else { "entries" }
+    );
+    match value {
+        Value::Record(rec) => {
+            for (key, value) in rec.iter() {
+                write_entry(out, &format!("{}:", escape(key)), value, depth + 1);
+            }
+        }
+        Value::Map(map) => {
+            for (key, value) in map.iter() {
+                let key = format!("{} -&gt;", escape(&key.display().to_string()));
+                write_entry(out, &key, value, depth + 1);
+            }
+        }
+        Value::Set(set) => set.iter().for_each(|v| write_item(out, v, depth + 1)),
+        Value::List(list) => list.iter().for_each(|v| write_item(out, v, depth + 1)),
+        Value::Tuple(tuple) => tuple.iter().for_each(|v| write_item(out, v, depth + 1)),
+        _ => {}
+    }
+    let _ = writeln!(out, "</ul>\n</details>");
+}
+
+fn write_entry(out: &mut String, key: &str, value: &Value, depth: usize) {
+    let _ = write!(out, "<li><span class=\"key\">{}</span> ", key);
+    write_value(out, value, depth);
+    let _ = writeln!(out, "</li>");
+}
+
+fn write_item(out: &mut String, value: &Value, depth: usize) {
+    let _ = write!(out, "<li>");
+    write_value(out, value, depth);
+    let _ = writeln!(out, "</li>");
+}
+
+fn open_attr(depth: usize) -> &'static str {
+    if depth <= OPEN_DEPTH { " open" } else { "" }
+}
+
+fn is_collection(value: &Value) -> bool {
+    matches!(
+        value,
+        Value::Record(_) | Value::Map(_) | Value::Set(_) | Value::List(_) | Value::Tuple(_)
+    )
+}
+
+/// Returns the tag and payload of a Quint sum type variant.
+fn as_variant(rec: &Record) -> Option<(&str, &Value)> {
+    match (rec.len(), rec.get("tag"), rec.get("value")) {
+        (2, Some(Value::String(tag)), Some(value)) => Some((tag, value)),
+        _ => None,
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape("<a href=\"x\">&</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_small_values_are_inline() {
+        let mut out = String::new();
+        write_value(&mut out, &Value::String("foo".to_string()), 0);
+        assert_eq!(out, "<code>&quot;foo&quot;</code>");
+    }
+
+    #[test]
+    fn test_large_records_are_collapsible() {
+        let mut inner = Record::new();
+        for i in 0..20 {
+            inner.insert(format!("field{}", i), Value::Number(i));
+        }
+        let mut outer = Record::new();
+        outer.insert("inner".to_string(), Value::Record(inner));
+
+        let mut out = String::new();
+        write_value(&mut out, &Value::Record(outer), 0);
+        assert!(out.starts_with("<details open><summary><code>{ … }</code>"));
+        assert!(out.contains("<span class=\"key\">field19:</span> <code>19</code>"));
+        assert_eq!(out.matches("<details").count(), 2);
+    }
+
+    #[test]
+    fn test_render_marks_failed_step() {
+        let step = |action: &str| StepRecord {
+            action: action.to_string(),
+            nondet_picks: vec![("node".to_string(), Value::String("p1".to_string()))],
+            spec_state: Value::Number(1),
+            impl_state: Some("1".to_string()),
+            message: None,
+        };
+        let steps = vec![step("init"), step("Prepare")];
+        let report = Report {
+            test_name: "my_test",
+            seed: "0x42",
+            config: "RunConfig { .. }",
+            trace: 3,
+            steps: &steps,
+            error: "State invariant failed".to_string(),
+            diff: Some("-1\n+2"),
+            failed_step: Some(1),
+            sequence: None,
+            itf: None,
+        };
+
+        let html = render(&report);
+        assert!(html.contains("<code>0x42</code>"));
+        assert!(html.contains("[Step 0] init(node: &quot;p1&quot;)"));
+        assert!(html.contains("<section class=\"step diverged\" id=\"step-1\">"));
+        assert!(html.contains("<span class=\"del\">-1</span>"));
+        assert!(html.contains("<span class=\"add\">+2</span>"));
+        assert_eq!(html.matches("diverged\"").count(), 1);
+
+        let report = Report {
+            failed_step: Some(2),
+            ..report
+        };
+        let html = render(&report);
+        assert!(!html.contains("diverged\""));
+        assert!(html.contains("Step 2 failed before it could be replayed."));
+    }
+}
@@ -0,0 +1,314 @@
+mod html;
+mod sequence;
+
+use crate::{
+    driver::{Sequence, SequenceMessage, Step},
+    logger::*,
+    settings::Settings,
+    trace::Trace,
+};
+use anyhow::{Context, Result, bail};
+use itf::Value;
+use std::{
+    fmt::Debug,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Supported report formats.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Format {
+    Html,
+    Mermaid,
+    PlantUml,
+    Itf,
+}
+
+impl Format {
+    fn extension(&self) -> &'static str {
+        match self {
+            Format::Html => "html",
+            Format::Mermaid => "mmd",
+            Format::PlantUml => "puml",
+            Format::Itf => "itf.json",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "html" => Ok(Format::Html),
+            "mermaid" => Ok(Format::Mermaid),
+            "plantuml" => Ok(Format::PlantUml),
+            "itf" => Ok(Format::Itf),
+            other => bail!("Unknown report format `{}`", other),
+        }
+    }
+}
+
+fn parse_formats(formats: &[String]) -> Result<Vec<Format>> {
+    formats.iter().map(|fmt| Format::from_str(fmt)).collect()
+}
+
+/// A single replayed step as recorded for reporting.
+pub(crate) struct StepRecord {
+    pub action: String,
+    pub nondet_picks: Vec<(String, Value)>,
+    pub spec_state: Value,
+    pub impl_state: Option<String>,
+    /// The message drawn for the step in sequence diagrams, if any.
+    pub message: Option<SequenceMessage>,
+}
+
+/// Records the steps of the trace being replayed so that a report can be
+/// produced if the trace fails.
+///
+/// Recording is a no-op unless at least one report format is enabled with the
+/// `QUINT_REPORT` environment variable or the `report` setting.
+pub(crate) struct Recorder {
+    formats: Vec<Format>,
+    dir: PathBuf,
+    sequence: Option<Sequence>,
+    trace: usize,
+    steps: Vec<StepRecord>,
+    diff: Option<String>,
+    failed_step: Option<usize>,
+    failed_trace: Option<Trace>,
+}
+
+impl Recorder {
+    pub fn new(settings: &Settings, sequence: Option<Sequence>) -> Self {
+        let formats = parse_formats(settings.report_formats()).unwrap_or_else(|err| {
+            error!("Ignoring report formats: {}", err);
+            Vec::new()
+        });
+        Self {
+            formats,
+            dir: settings.report_dir().to_path_buf(),
+            sequence,
+            trace: 0,
+            steps: Vec::new(),
+            diff: None,
+            failed_step: None,
+            failed_trace: None,
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        !self.formats.is_empty()
+    }
+
+    pub fn start_trace(&mut self, trace: usize) {
+        self.trace = trace;
+        self.steps.clear();
+        self.diff = None;
+        self.failed_step = None;
+    }
+
+    pub fn record_step(&mut self, step: &Step) {
+        if !self.is_enabled() {
+            return;
+        }
+        self.steps.push(StepRecord {
+            action: step.action_taken.clone(),
+            nondet_picks: step
+                .nondet_picks
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+            spec_state: step.state.clone(),
+            impl_state: None,
+            message: self
+                .sequence
+                .as_ref()
+                .and_then(|seq| sequence::message(seq, step)),
+        });
+    }
+
+    pub fn record_impl_state(&mut self, state: &impl Debug) {
+        if let Some(step) = self.steps.last_mut() {
+            step.impl_state = Some(format!("{:#?}", state));
+        }
+    }
+
+    pub fn record_divergence(&mut self, diff: String) {
+        if self.is_enabled() {
+            self.diff = Some(diff);
+        }
+    }
+
+    /// Marks the step at the given index as the one that failed, which may not
+    /// have been recorded if it failed while being derived.
+    pub fn record_failed_step(&mut self, index: usize) {
+        self.failed_step = Some(index);
+    }
+
+    /// Keeps the trace that failed to replay.
+    pub fn record_failed_trace(&mut self, trace: Trace) {
+        self.failed_trace = Some(trace);
+    }
+
+    /// The trace that failed to replay, along with its index.
+    pub fn failed_trace(&self) -> Option<(usize, &Trace)> {
+        self.failed_trace.as_ref().map(|trace| (self.trace, trace))
+    }
+
+    /// Writes reports for the failed trace in all enabled formats.
+    pub fn write_reports(&self, test_name: &str, seed: &str, config: &str, error: &anyhow::Error) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let report = Report {
+            test_name,
+            seed,
+            config,
+            trace: self.trace,
+            steps: &self.steps,
+            error: format!("{:?}", error),
+            diff: self.diff.as_deref(),
+            failed_step: self.failed_step,
+            sequence: self.sequence.as_ref(),
+            itf: self.failed_trace.as_ref(),
+        };
+
+        for format in &self.formats {
+            let path = self
+                .dir
+                .join(format!("{}.{}", file_name(test_name), format.extension()));
+            match write_report(&report, *format, &path) {
+                Ok(()) => {
+                    info!("Report written to {}", path.display());
+                }
+                Err(err) => {
+                    error!("Failed to write report: {:?}", err);
+                }
+            }
+        }
+    }
+}
+
+/// A file name for the given test, replacing characters that are not portable,
+/// such as the `::` in matrix case names.
+fn file_name(test_name: &str) -> String {
+    test_name
+        .chars()
+        .map(|c| match c {
+            c if c.is_alphanumeric() || matches!(c, '_' | '-' | '.') => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+/// Everything known about a failed trace replay.
+pub(crate) struct Report<'a> {
+    pub test_name: &'a str,
+    pub seed: &'a str,
+    pub config: &'a str,
+    pub trace: usize,
+    pub steps: &'a [StepRecord],
+    pub error: String,
+    pub diff: Option<&'a str>,
+    pub failed_step: Option<usize>,
+    pub sequence: Option<&'a Sequence>,
+    pub itf: Option<&'a Trace>,
+}
+
+fn write_report(report: &Report, format: Format, path: &Path) -> Result<()> {
+    let dialect = match format {
+        Format::Html => return write_file(path, html::render(report)),
+        Format::Itf => {
+            let Some(trace) = report.itf else {
+                bail!("No failing trace to write")
+            };
+            let json = serde_json::to_string(trace).context("Failed to serialize trace")?;
+            return write_file(path, json);
+        }
+        Format::Mermaid => sequence::Dialect::Mermaid,
+        Format::PlantUml => sequence::Dialect::PlantUml,
+    };
+    if report.sequence.is_none() {
+        bail!(
+            "Can not render a sequence diagram for `{}`.\n\
+             Please override `Driver::sequence` to map steps onto a sequence diagram.",
+            report.test_name
+        )
+    }
+    write_file(path, sequence::render(report, dialect))
+}
+
+fn write_file(path: &Path, contents: String) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    fs::write(path, contents)
+        .with_context(|| format!("Failed to write report at: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formats(formats: &[&str]) -> Vec<String> {
+        formats.iter().map(|fmt| fmt.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_formats() {
+        assert_eq!(parse_formats(&[]).unwrap(), vec![]);
+        assert_eq!(
+            parse_formats(&formats(&["html"])).unwrap(),
+            vec![Format::Html]
+        );
+        assert_eq!(
+            parse_formats(&formats(&[" HTML"])).unwrap(),
+            vec![Format::Html]
+        );
+        assert_eq!(
+            parse_formats(&formats(&["html", "mermaid", "plantuml", "itf"])).unwrap(),
+            vec![Format::Html, Format::Mermaid, Format::PlantUml, Format::Itf]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown report format `pdf`")]
+    fn test_parse_unknown_format() {
+        parse_formats(&formats(&["html", "pdf"])).unwrap();
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(file_name("simulation"), "simulation");
+        assert_eq!(
+            file_name("simulation::max_steps_5_main_small"),
+            "simulation__max_steps_5_main_small"
+        );
+        assert_eq!(file_name("a/b c"), "a_b_c");
+    }
+
+    #[test]
+    fn test_disabled_recorder_records_nothing() {
+        let mut recorder = Recorder::new(&Settings::default(), None);
+        recorder.start_trace(1);
+        recorder.record_impl_state(&42);
+        recorder.record_divergence("diff".to_string());
+        recorder.record_failed_step(0);
+        assert!(recorder.steps.is_empty());
+        assert!(recorder.diff.is_none());
+    }
+
+    #[test]
+    fn test_failed_step_is_reset_per_trace() {
+        let mut recorder = Recorder::new(&Settings::default(), None);
+        recorder.start_trace(1);
+        recorder.record_failed_step(3);
+        assert_eq!(recorder.failed_step, Some(3));
+        recorder.start_trace(2);
+        assert_eq!(recorder.failed_step, None);
+    }
+}
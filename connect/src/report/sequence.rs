@@ -0,0 +1,370 @@
+use crate::{
+    driver::{Sequence, SequenceMessage, Step},
+    report::{Report, StepRecord},
+    value::ValueDisplay,
+};
+use itf::Value;
+use std::fmt::Write;
+
+/// Sequence diagram dialects.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Dialect {
+    Mermaid,
+    PlantUml,
+}
+
+/// A step as drawn in a sequence diagram.
+enum Event {
+    Message {
+        from: usize,
+        to: usize,
+        label: String,
+    },
+    Note {
+        label: String,
+    },
+}
+
+/// Renders the failed trace as a sequence diagram, marking the failing step.
+pub(crate) fn render(report: &Report, dialect: Dialect) -> String {
+    let mut participants = Vec::new();
+    let events: Vec<_> = report
+        .steps
+        .iter()
+        .map(|step| to_event(step, &mut participants))
+        .collect();
+    if participants.is_empty() {
+        // Notes must span a declared participant.
+        participants.push("trace".to_string());
+    }
+
+    let mut out = String::new();
+    match dialect {
+        Dialect::Mermaid => {
+            let _ = writeln!(out, "sequenceDiagram");
+            let _ = writeln!(out, "    title {}", mermaid_escape(report.test_name));
+            for (id, name) in participants.iter().enumerate() {
+                let _ = writeln!(out, "    participant p{} as {}", id, mermaid_escape(name));
+            }
+        }
+        Dialect::PlantUml => {
+            let _ = writeln!(out, "@startuml");
+            let _ = writeln!(out, "title {}", plantuml_escape(report.test_name));
+            for (id, name) in participants.iter().enumerate() {
+                let _ = writeln!(out, "participant \"{}\" as p{}", plantuml_escape(name), id);
+            }
+        }
+    }
+
+    let all = span(participants.len());
+    for (index, event) in events.iter().enumerate() {
+        let failed = report.failed_step == Some(index);
+        match dialect {
+            Dialect::Mermaid => write_mermaid(&mut out, index, event, failed, &all),
+            Dialect::PlantUml => write_plantuml(&mut out, index, event, failed, &all),
+        }
+    }
+
+    if dialect == Dialect::PlantUml {
+        let _ = writeln!(out, "@enduml");
+    }
+    out
+}
+
+fn write_mermaid(out: &mut String, index: usize, event: &Event, failed: bool, all: &str) {
+    if failed {
+        let _ = writeln!(out, "    rect rgb(255, 210, 210)");
+    }
+    match event {
+        Event::Message { from, to, label } => {
+            let label = mermaid_escape(&format!("[{}] {}", index, label));
+            let _ = writeln!(out, "    p{}->>p{}: {}", from, to, label);
+        }
+        Event::Note { label } => {
+            let label = mermaid_escape(&format!("[{}] {}", index, label));
+            let _ = writeln!(out, "    Note over {}: {}", all, label);
+        }
+    }
+    if failed {
+        let _ = writeln!(out, "    Note over {}: diverged", all);
+        let _ = writeln!(out, "    end");
+    }
+}
+
+fn write_plantuml(out: &mut String, index: usize, event: &Event, failed: bool, all: &str) {
+    let color = if failed { " #FFD2D2" } else { "" };
+    match event {
+        Event::Message { from, to, label } => {
+            let arrow = if failed { "-[#red]>" } else { "->" };
+            let label = plantuml_escape(label);
+            let _ = writeln!(out, "p{} {} p{}: [{}] {}", from, arrow, to, index, label);
+        }
+        Event::Note { label } => {
+            let label = plantuml_escape(label);
+            let _ = writeln!(out, "note over {}{}: [{}] {}", all, color, index, label);
+        }
+    }
+    if failed {
+        let _ = writeln!(out, "note over {}{}: diverged", all, color);
+    }
+}
+
+/// The message drawn for a step, if any.
+pub(crate) fn message(sequence: &Sequence, step: &Step) -> Option<SequenceMessage> {
+    match sequence {
+        Sequence::Picks {
+            participant,
+            receiver,
+        } => {
+            let pick = |name: &str| {
+                step.nondet_picks
+                    .iter()
+                    .find(|(pick, _)| *pick == name)
+                    .map(|(_, value)| participant_name(value))
+            };
+            let from = pick(participant)?;
+            let to = receiver.and_then(pick);
+            let args = step
+                .nondet_picks
+                .iter()
+                .filter(|(name, _)| *name != participant && Some(name.as_str()) != *receiver);
+            Some(SequenceMessage {
+                from,
+                to,
+                label: label(&step.action_taken, args),
+            })
+        }
+        Sequence::Map(map) => step.nondet_picks.untracked(|| map(step)),
+    }
+}
+
+fn to_event(step: &StepRecord, participants: &mut Vec<String>) -> Event {
+    match &step.message {
+        Some(message) => {
+            let from = participant_id(participants, message.from.clone());
+            let to = message
+                .to
+                .clone()
+                .map(|to| participant_id(participants, to))
+                .unwrap_or(from);
+            Event::Message {
+                from,
+                to,
+                label: message.label.clone(),
+            }
+        }
+        None => Event::Note {
+            label: label(
+                &step.action,
+                step.nondet_picks.iter().map(|(name, value)| (name, value)),
+            ),
+        },
+    }
+}
+
+/// Labels an action with its nondeterministic picks, e.g. `Send(msg: "ack")`.
+fn label<'a>(action: &str, picks: impl Iterator<Item = (&'a String, &'a Value)>) -> String {
+    let args: Vec<_> = picks
+        .map(|(name, value)| format!("{}: {}", name, value.display()))
+        .collect();
+    let action = if action.is_empty() {
+        "<anonymous>"
+    } else {
+        action
+    };
+    match args.is_empty() {
+        true => action.to_string(),
+        false => format!("{}({})", action, args.join(", ")),
+    }
+}
+
+/// Strings are used as participant names without quotes.
+fn participant_name(value: &Value) -> String {
+    match value {
+        Value::String(name) => name.clone(),
+        other => other.display().to_string(),
+    }
+}
+
+fn participant_id(participants: &mut Vec<String>, name: String) -> usize {
+    match participants.iter().position(|p| *p == name) {
+        Some(id) => id,
+        None => {
+            participants.push(name);
+            participants.len() - 1
+        }
+    }
+}
+
+/// The range of participants a note spans.
+fn span(len: usize) -> String {
+    match len {
+        1 => "p0".to_string(),
+        n => format!("p0,p{}", n - 1),
+    }
+}
+
+/// Escapes characters with special meaning in Mermaid messages.
+fn mermaid_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '#' => escaped.push_str("#35;"),
+            ';' => escaped.push_str("#59;"),
+            '"' => escaped.push_str("#quot;"),
+            '\n' => escaped.push_str("<br>"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes characters that would end PlantUML names and messages early.
+fn plantuml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("<U+0022>"),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step_with(sequence: &Sequence, action: &str, picks: &[(&str, &str)]) -> StepRecord {
+        let step = picks
+            .iter()
+            .fold(Step::builder(action), |step, (name, value)| {
+                step.pick(name, value)
+            })
+            .build()
+            .unwrap();
+        StepRecord {
+            action: action.to_string(),
+            nondet_picks: picks
+                .iter()
+                .map(|(name, value)| (name.to_string(), Value::String(value.to_string())))
+                .collect(),
+            spec_state: Value::Bool(true),
+            impl_state: None,
+            message: message(sequence, &step),
+        }
+    }
+
+    fn step(action: &str, picks: &[(&str, &str)]) -> StepRecord {
+        step_with(&SEQUENCE, action, picks)
+    }
+
+    fn report(steps: &[StepRecord]) -> Report<'_> {
+        Report {
+            test_name: "two_phase_commit",
+            seed: "0x42",
+            config: "",
+            trace: 1,
+            steps,
+            error: "State invariant failed".to_string(),
+            diff: None,
+            failed_step: steps.len().checked_sub(1),
+            sequence: None,
+            itf: None,
+        }
+    }
+
+    const SEQUENCE: Sequence = Sequence::Picks {
+        participant: "node",
+        receiver: Some("to"),
+    };
+
+    #[test]
+    fn test_render_mermaid() {
+        let steps = vec![
+            step("Init", &[]),
+            step("Prepares", &[("node", "p1")]),
+            step("Send", &[("node", "p1"), ("to", "c"), ("msg", "ack")]),
+        ];
+        let diagram = render(&report(&steps), Dialect::Mermaid);
+        assert_eq!(
+            diagram,
+            "sequenceDiagram\n    \
+             title two_phase_commit\n    \
+             participant p0 as p1\n    \
+             participant p1 as c\n    \
+             Note over p0,p1: [0] Init\n    \
+             p0->>p0: [1] Prepares\n    \
+             rect rgb(255, 210, 210)\n    \
+             p0->>p1: [2] Send(msg: #quot;ack#quot;)\n    \
+             Note over p0,p1: diverged\n    \
+             end\n"
+        );
+    }
+
+    fn reply(step: &Step) -> Option<SequenceMessage> {
+        let from: String = step.pick("to").ok()?;
+        Some(SequenceMessage {
+            from,
+            to: step.pick("node").ok(),
+            label: format!("{} reply", step.action()),
+        })
+    }
+
+    #[test]
+    fn test_render_mapped_steps() {
+        let sequence = Sequence::Map(reply);
+        let steps = vec![
+            step_with(&sequence, "Init", &[]),
+            step_with(&sequence, "Send", &[("node", "p1"), ("to", "c")]),
+        ];
+        let diagram = render(&report(&steps), Dialect::Mermaid);
+        assert!(diagram.contains("participant p0 as c\n    participant p1 as p1\n"));
+        assert!(diagram.contains("Note over p0,p1: [0] Init\n"));
+        assert!(diagram.contains("p0->>p1: [1] Send reply\n"));
+
+        let step = Step::builder("Send").pick("to", "c").build().unwrap();
+        assert!(message(&sequence, &step).is_some());
+        assert_eq!(step.nondet_picks.unconsumed(), ["to"]);
+    }
+
+    #[test]
+    fn test_mermaid_escape() {
+        assert_eq!(mermaid_escape("a;b#\"c\""), "a#59;b#35;#quot;c#quot;");
+    }
+
+    #[test]
+    fn test_render_without_participants() {
+        let steps = vec![step("Init", &[])];
+        let diagram = render(&report(&steps), Dialect::Mermaid);
+        assert!(diagram.contains("    participant p0 as trace\n    rect"));
+        assert!(diagram.contains("Note over p0: [0] Init"));
+        let diagram = render(&report(&steps), Dialect::PlantUml);
+        assert!(diagram.contains("participant \"trace\" as p0\nnote over p0 #FFD2D2: [0] Init"));
+    }
+
+    #[test]
+    fn test_plantuml_escape() {
+        let steps = vec![step("Send", &[("node", "mod::\"a b\""), ("msg", "x\ny")])];
+        let diagram = render(&report(&steps), Dialect::PlantUml);
+        assert!(diagram.contains("participant \"mod::<U+0022>a b<U+0022>\" as p0\n"));
+        assert!(diagram.contains("p0 -[#red]> p0: [0] Send(msg: <U+0022>x\\ny<U+0022>)\n"));
+    }
+
+    #[test]
+    fn test_render_plantuml() {
+        let steps = vec![step("Init", &[]), step("Prepares", &[("node", "p1")])];
+        let diagram = render(&report(&steps), Dialect::PlantUml);
+        assert_eq!(
+            diagram,
+            "@startuml\n\
+             title two_phase_commit\n\
+             participant \"p1\" as p0\n\
+             note over p0: [0] Init\n\
+             p0 -[#red]> p0: [1] Prepares\n\
+             note over p0 #FFD2D2: diverged\n\
+             @enduml\n"
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Decoding of nondet picks into the fields of `QuintAction` variants.
+//!
+//! Fields are decoded with `serde_with` adapters, as computed by the derive for
+//! Quint options nested in the field's type, or [`Same`](serde_with::Same).
+
+use crate::Step;
+use anyhow::{Context, Result, bail};
+use serde_with::DeserializeAs;
+use std::any;
+
+pub fn pick<T, A>(step: &Step, name: &str) -> Result<T>
+where
+    A: for<'de> DeserializeAs<'de, T>,
+{
+    match optional_pick::<T, A>(step, name)? {
+        Some(value) => Ok(value),
+        None => Err(step.nondet_picks.missing(name, &step.action_taken)),
+    }
+}
+
+pub fn optional_pick<T, A>(step: &Step, name: &str) -> Result<Option<T>>
+where
+    A: for<'de> DeserializeAs<'de, T>,
+{
+    step.nondet_picks
+        .get(name)
+        .map(|value| {
+            A::deserialize_as(value.clone()).with_context(|| {
+                format!(
+                    "Failed to decode nondet pick `{}` of action `{}`",
+                    name, step.action_taken
+                )
+            })
+        })
+        .transpose()
+}
+
+pub fn unknown_action<T>(step: &Step) -> Result<T> {
+    bail!(
+        "Unimplemented action `{}` in `{}`",
+        step.action_taken,
+        any::type_name::<T>()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Action, driver::Config};
+    use itf::{Value, value::Record};
+    use serde_with::Same;
+
+    /// Mirrors the expansion of `#[derive(QuintAction)]`.
+    #[derive(PartialEq, Debug)]
+    enum Bank {
+        Init,
+        Deposit { amount: u64, memo: Option<String> },
+    }
+
+    impl Action for Bank {
+        fn from_step(step: &Step) -> Result<Self> {
+            match step.action_taken.as_str() {
+                "Init" | "init" => Ok(Self::Init),
+                "Deposit" | "deposit" => Ok(Self::Deposit {
+                    amount: pick::<_, Same>(step, "amount")?,
+                    memo: optional_pick::<_, Same>(step, "memo")?,
+                }),
+                _ => unknown_action(step),
+            }
+        }
+    }
+
+    fn option(value: Option<Value>) -> Value {
+        let mut rec = Record::new();
+        let (tag, value) = match value {
+            Some(value) => ("Some", value),
+            None => ("None", Value::Tuple(vec![].into())),
+        };
+        rec.insert("tag".to_string(), Value::String(tag.to_string()));
+        rec.insert("value".to_string(), value);
+        Value::Record(rec)
+    }
+
+    fn step(action: &str, picks: &[(&str, Option<Value>)]) -> Step {
+        let mut nondets = Record::new();
+        for (name, value) in picks {
+            nondets.insert(name.to_string(), option(value.clone()));
+        }
+        let mut state = Record::new();
+        state.insert(
+            "mbt::actionTaken".to_string(),
+            Value::String(action.to_string()),
+        );
+        state.insert("mbt::nondetPicks".to_string(), Value::Record(nondets));
+        Step::new(state, &Config::default()).unwrap()
+    }
+
+    #[test]
+    fn test_decode_actions() {
+        let init = step("init", &[("amount", None), ("memo", None)]);
+        assert_eq!(init.decode_action::<Bank>().unwrap(), Bank::Init);
+
+        let deposit = step("Deposit", &[("amount", Some(Value::Number(10)))]);
+        assert_eq!(
+            deposit.decode_action::<Bank>().unwrap(),
+            Bank::Deposit {
+                amount: 10,
+                memo: None
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_action_errors() {
+        let err = step("Withdraw", &[]).decode_action::<Bank>().unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("Unimplemented action `Withdraw` in `")
+        );
+
+        let err = step("Deposit", &[("amount", None)])
+            .decode_action::<Bank>()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Nondet pick `amount` is `None` in action `Deposit`"
+        );
+
+        let err = step("Deposit", &[]).decode_action::<Bank>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Missing nondet pick `amount` in action `Deposit`"
+        );
+
+        let memo = Some(Value::Number(1));
+        let picks = [("amount", Some(Value::Number(10))), ("memo", memo)];
+        let err = step("Deposit", &picks).decode_action::<Bank>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to decode nondet pick `memo` of action `Deposit`"
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Conversion of `switch!` handler values into step results.
+//!
+//! Implicit handlers are wrapped in a [`Handler`] and converted with method-call
+//! syntax, so that results are matched by [`FallibleHandler`] on the wrapper itself
+//! while any other value falls back to [`InfallibleHandler`] through
+//! auto-referencing:
+//!
+//! ```text
+//! (&mut Handler(Some(self.action(x)))).step_result()
+//! ```
+//!
+//! Explicit handlers may diverge, e.g. with `todo!()`, leaving their type unknown
+//! at the method call. They are only treated as fallible when marked with `try`,
+//! going through [`fallible`] instead.
+
+use anyhow::Result;
+
+#[doc(hidden)] // public for macro use
+pub struct Handler<T>(pub Option<T>);
+
+#[doc(hidden)] // public for macro use
+pub trait FallibleHandler {
+    fn step_result(&mut self) -> Result<()>;
+}
+
+impl<T, E: Into<anyhow::Error>> FallibleHandler for Handler<std::result::Result<T, E>> {
+    fn step_result(&mut self) -> Result<()> {
+        match self.0.take() {
+            Some(result) => fallible(result),
+            None => Ok(()),
+        }
+    }
+}
+
+#[doc(hidden)] // public for macro use
+pub trait InfallibleHandler {
+    fn step_result(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<T> InfallibleHandler for &mut Handler<T> {}
+
+#[doc(hidden)] // public for macro use
+pub fn fallible<T, E: Into<anyhow::Error>>(result: std::result::Result<T, E>) -> Result<()> {
+    result.map(|_| ()).map_err(Into::into)
+}
+
+#[cfg(test)]
+#[allow(clippy::needless_borrow)] // mirrors the expansion of `switch!`
+mod tests {
+    use super::*;
+    use anyhow::bail;
+
+    fn fails() -> Result<usize> {
+        bail!("boom")
+    }
+
+    #[test]
+    fn test_fallible_handlers() {
+        let err = (&mut Handler(Some(fails()))).step_result().unwrap_err();
+        assert_eq!(err.to_string(), "boom");
+
+        let io: std::io::Result<()> = Err(std::io::Error::other("io"));
+        let err = (&mut Handler(Some(io))).step_result().unwrap_err();
+        assert_eq!(err.to_string(), "io");
+
+        let ok: Result<usize> = Ok(1);
+        assert!((&mut Handler(Some(ok))).step_result().is_ok());
+    }
+
+    #[test]
+    fn test_infallible_handlers() {
+        assert!((&mut Handler(Some(()))).step_result().is_ok());
+        assert!((&mut Handler(Some(42))).step_result().is_ok());
+        assert!((&mut Handler(Some(Some("x")))).step_result().is_ok());
+    }
+}
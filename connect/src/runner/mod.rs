@@ -1,20 +1,32 @@
+mod action;
+mod handler;
 mod seed;
 
 pub use crate::trace::generator::{RunConfig, TestConfig};
-pub use seed::gen_random_seed;
+pub use crate::value::QuintOption;
+pub use action::{optional_pick, pick, unknown_action};
+pub use handler::{FallibleHandler, Handler, InfallibleHandler, fallible};
+pub(crate) use seed::gen_random_seed;
+pub use serde;
+pub use serde_with::{As, Same};
 
 use crate::{
-    Driver, State, Step,
+    Driver, State, Step, debugger,
+    driver::Config as DriverConfig,
     logger::*,
+    report::Recorder,
+    settings::{self, Settings, Strictness},
     trace::{
+        Trace,
         generator::{Config as GenConfig, generate_traces},
-        iter::Traces,
+        iter::trace_from_file,
     },
-    value::ValueDisplay,
+    value::{Change, ValueDisplay, diff, render, side_by_side},
 };
 use anyhow::{Result, bail, ensure};
 use itf::Value;
 use similar::TextDiff;
+use std::{collections::BTreeSet, iter, path::PathBuf};
 
 /// Configuration for running model-based tests, combining test metadata with
 /// trace generation settings.
@@ -23,32 +35,140 @@ pub struct Config<C: GenConfig> {
     pub gen_config: C,
 }
 
-/// Run the test configuration using the given test driver.
-pub fn run_test<C: GenConfig>(driver: impl Driver, config: Config<C>) -> Result<()> {
+/// Run the test configuration using drivers built by the given function.
+///
+/// A single driver replays all generated traces. Additional drivers are only built
+/// when debugging a failing trace.
+pub fn run_test<D, F, C>(new_driver: F, mut config: Config<C>) -> Result<()>
+where
+    D: Driver,
+    F: Fn() -> D,
+    C: GenConfig,
+{
+    let settings = Settings::load(&config.test_name)?;
+    set_verbosity(settings.verbosity());
+    config.gen_config.apply(&settings);
+
     title!("Running model based tests for {}", config.test_name);
-    info!(
-        "Generating {} traces using `{}` as random seed ...",
-        config.gen_config.n_traces(),
-        config.gen_config.seed()
-    );
 
-    let traces = generate_traces(&config.gen_config)?;
-    let result = replay_traces(driver, traces);
+    if let Some(path) = debugger::trace_file() {
+        info!("Debugging trace from {} ...", path.display());
+        let trace = trace_from_file(&path)?;
+        return debugger::open(&new_driver, &trace, 1);
+    }
+
+    let mut recorder = Recorder::new(&settings, D::sequence());
+    let mut picks = PickChecker::new(settings.strict());
+    let replay = settings::var("QUINT_REPLAY").map(PathBuf::from);
+    let result = match &replay {
+        Some(path) => {
+            info!("Replaying trace from {} ...", path.display());
+            let traces = iter::once(trace_from_file(path));
+            replay_traces(new_driver(), traces, &mut recorder, &mut picks)
+        }
+        None => {
+            info!(
+                "Generating {} traces using `{}` as random seed ...",
+                config.gen_config.n_traces(),
+                config.gen_config.seed()
+            );
+            let traces = generate_traces(&config.gen_config, &settings)?;
+            replay_traces(new_driver(), traces, &mut recorder, &mut picks)
+        }
+    };
+
+    match &result {
+        Ok(()) => {
+            success!("[OK] {}", config.test_name);
+        }
+        Err(err) => {
+            error!("[FAIL] {} ", config.test_name);
+            if replay.is_none() {
+                error!(
+                    "Reproduce this error with `QUINT_SEED={}`\n",
+                    config.gen_config.seed()
+                );
+            }
+            recorder.write_reports(
+                &config.test_name,
+                config.gen_config.seed(),
+                &format!("{:#?}", config.gen_config),
+                err,
+            );
 
-    if result.is_ok() {
-        success!("[OK] {}", config.test_name);
-    } else {
-        error!("[FAIL] {} ", config.test_name);
-        error!(
-            "Reproduce this error with `QUINT_SEED={}`\n",
-            config.gen_config.seed()
-        );
+            if debugger::on_failure()
+                && let Some((t, trace)) = recorder.failed_trace()
+                && let Err(err) = debugger::open(&new_driver, trace, t)
+            {
+                error!("Failed to open debugger: {:?}", err);
+            }
+        }
     }
 
     result
 }
 
-fn replay_traces<D: Driver>(mut driver: D, traces: Traces) -> Result<()> {
+/// Replays a trace against the driver without running Quint, e.g. one built with
+/// [`TraceBuilder`](crate::TraceBuilder) or read from an ITF file.
+///
+/// Each step is executed and its state checked as in tests generated by
+/// [`quint_run`](crate::quint_run) and [`quint_test`](crate::quint_test), with the
+/// verbosity and strictness settings taken from the environment and the
+/// `[defaults]` of `quint-connect.toml`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use quint_connect::*;
+/// # use serde::Deserialize;
+/// # #[derive(Default)]
+/// # struct Counter { count: i64 }
+/// # #[derive(Deserialize, PartialEq, Debug)]
+/// # struct CounterState { count: i64 }
+/// # impl State<Counter> for CounterState {
+/// #     fn from_driver(driver: &Counter) -> Result<Self> { Ok(Self { count: driver.count }) }
+/// # }
+/// # impl Driver for Counter {
+/// #     type State = CounterState;
+/// #     fn step(&mut self, step: &Step) -> Result {
+/// #         switch!(step {
+/// #             init => self.count = 0,
+/// #             add(amount: i64) => self.count += amount,
+/// #         })
+/// #     }
+/// # }
+/// # fn main() -> Result {
+/// let trace = TraceBuilder::new()
+///     .step(Step::builder("init").var("count", 0))
+///     .step(Step::builder("add").pick("amount", 2).var("count", 2))
+///     .build()?;
+/// replay(Counter::default(), trace)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the driver fails a step, or if its state diverges from the
+/// spec's.
+pub fn replay<D: Driver>(driver: D, trace: itf::Trace<Value>) -> Result<()> {
+    let settings = Settings::load_defaults()?;
+    set_verbosity(settings.verbosity());
+    let mut recorder = Recorder::new(&Settings::default(), D::sequence());
+    let mut picks = PickChecker::new(settings.strict());
+    replay_traces(driver, iter::once(Ok(trace)), &mut recorder, &mut picks)
+}
+
+fn replay_traces<D, I>(
+    mut driver: D,
+    traces: I,
+    recorder: &mut Recorder,
+    picks: &mut PickChecker,
+) -> Result<()>
+where
+    D: Driver,
+    I: Iterator<Item = Result<Trace>>,
+{
     info!("Replaying traces ...");
 
     let ann = D::config();
@@ -61,52 +181,337 @@ fn replay_traces<D: Driver>(mut driver: D, traces: Traces) -> Result<()> {
 
     for (trace, t) in iter.zip(1usize..) {
         trace!(1, "[Trace {}]", t);
+        recorder.start_trace(t);
 
-        for (s, state) in trace?.states.into_iter().enumerate() {
-            trace!(2, "Deriving step from:\n{}\n", state.value.display());
-            let Value::Record(state) = state.value else {
-                bail!("Expected current state to be a Record")
-            };
-
-            let step = Step::new(state, &ann)?;
-            trace!(1, "[Step {}]\n{}\n", s, step);
-            ensure!(
-                !step.action_taken.is_empty(),
-                "An anonymous action was found!\n\
-                 Please make sure all actions in the specification are properly named.\n\
-                 Check the crate docs for tips and tricks on nondeterminism."
-            );
-
-            driver.step(&step)?;
-            check_state(&driver, step)?;
+        let trace = trace?;
+        if let Err(err) = replay_trace(&mut driver, &trace, t, &ann, recorder, picks) {
+            recorder.record_failed_trace(trace);
+            return Err(err);
         }
     }
 
     Ok(())
 }
 
-fn check_state<D: Driver>(driver: &D, step: Step) -> Result<()> {
+fn replay_trace<D: Driver>(
+    driver: &mut D,
+    trace: &Trace,
+    t: usize,
+    ann: &DriverConfig,
+    recorder: &mut Recorder,
+    picks: &mut PickChecker,
+) -> Result<()> {
+    for (s, step) in derive_steps(trace, t, ann).enumerate() {
+        if let Err(err) = replay_step(driver, step, recorder, picks) {
+            recorder.record_failed_step(s);
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+fn replay_step<D: Driver>(
+    driver: &mut D,
+    step: Result<Step>,
+    recorder: &mut Recorder,
+    picks: &mut PickChecker,
+) -> Result<()> {
+    let step = step?;
+    trace!(1, "[Step {}]\n{}\n", step.index, step.display_changes());
+
+    recorder.record_step(&step);
+    driver.step(&step)?;
+    picks.check(&step)?;
+    check_state(driver, step, recorder)
+}
+
+/// Derives the steps of the `t`-th trace, each one following the last step that
+/// could be derived.
+pub(crate) fn derive_steps<'a>(
+    trace: &'a Trace,
+    t: usize,
+    ann: &'a DriverConfig,
+) -> impl Iterator<Item = Result<Step>> + 'a {
+    let mut prev_vars = None;
+    trace.states.iter().enumerate().map(move |(s, state)| {
+        trace!(2, "Deriving step from:\n{}\n", state.value.display());
+        let step = derive_step(&state.value, ann)?.at(t, s, prev_vars.clone());
+        prev_vars = Some(step.vars.clone());
+        Ok(step)
+    })
+}
+
+/// Derives a step from a trace state, making sure its action is named.
+fn derive_step(state: &Value, ann: &DriverConfig) -> Result<Step> {
+    let Value::Record(state) = state else {
+        bail!("Expected current state to be a Record")
+    };
+
+    let step = Step::new(state.clone(), ann)?;
+    ensure!(
+        !step.action_taken.is_empty(),
+        "An anonymous action was found!\n\
+         Please make sure all actions in the specification are properly named.\n\
+         Check the crate docs for tips and tricks on nondeterminism."
+    );
+    Ok(step)
+}
+
+/// Checks that drivers read all the nondet picks of each step, so that picks
+/// renamed in the spec or misspelled in the driver don't go unnoticed.
+struct PickChecker {
+    strictness: Strictness,
+    warned: BTreeSet<String>,
+}
+
+impl PickChecker {
+    fn new(strictness: Strictness) -> Self {
+        Self {
+            strictness,
+            warned: BTreeSet::new(),
+        }
+    }
+
+    fn check(&mut self, step: &Step) -> Result<()> {
+        if self.strictness == Strictness::Off {
+            return Ok(());
+        }
+        let unconsumed = step.nondet_picks.unconsumed();
+        if unconsumed.is_empty() {
+            return Ok(());
+        }
+        let picks = unconsumed
+            .iter()
+            .map(|pick| format!("`{}`", pick))
+            .collect::<Vec<_>>()
+            .join(", ");
+        match self.strictness {
+            Strictness::Deny => bail!(
+                "Action `{}` did not read nondet picks: {}",
+                step.action_taken,
+                picks
+            ),
+            _ => {
+                if self.warned.insert(step.action_taken.clone()) {
+                    warning!(
+                        "Action `{}` did not read nondet picks: {}",
+                        step.action_taken,
+                        picks
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn check_state<D: Driver>(driver: &D, step: Step, recorder: &mut Recorder) -> Result<()> {
     trace!(2, "Extracting state from:\n{}\n", step.state.display());
     let spec_state = D::State::from_spec(step.state)?;
     let driver_state = D::State::from_driver(driver)?;
+    recorder.record_impl_state(&driver_state);
 
-    if spec_state != driver_state {
-        let left = format!("{:#?}", spec_state);
-        let right = format!("{:#?}", driver_state);
-        let diff = TextDiff::from_lines(&left, &right);
-
+    if !spec_state.matches(&driver_state) {
+        let diff = diff_states::<D, _>(&spec_state, &driver_state);
         error!("Specification and implementation states diverge");
-        trace!(
-            1,
-            "{}",
-            diff.unified_diff()
-                .context_radius(256) // XXX: large enough?
-                .header("specification", "implementation")
-                .missing_newline_hint(false)
-        );
+        trace!(1, "{}", diff);
+        if let Some(changes) = state_changes::<D, _>(&spec_state, &driver_state) {
+            trace!(2, "{}\n", side_by_side(&changes));
+        }
+        recorder.record_divergence(diff);
 
         bail!("State invariant failed")
     }
 
     Ok(())
 }
+
+/// Renders the differences between the specification and implementation states,
+/// one path per line in Quint syntax, falling back to a line diff of their
+/// [`Debug`] output when they can't be compared structurally, e.g. when their
+/// `Debug` output omits fields, or when no structural difference is found. The
+/// line diff keeps every line as context, so that changed fields are shown with
+/// the structs they belong to.
+pub(crate) fn diff_states<D, S: State<D>>(spec_state: &S, driver_state: &S) -> String {
+    if let Some(changes) = state_changes(spec_state, driver_state) {
+        return render(&changes);
+    }
+    let left = format!("{:#?}", spec_state);
+    let right = format!("{:#?}", driver_state);
+    let lines = left.lines().count().max(right.lines().count());
+    TextDiff::from_lines(&left, &right)
+        .unified_diff()
+        .context_radius(lines)
+        .header("specification", "implementation")
+        .missing_newline_hint(false)
+        .to_string()
+}
+
+/// The changes from the specification state to the implementation's, if any
+/// could be found.
+fn state_changes<D, S: State<D>>(spec_state: &S, driver_state: &S) -> Option<Vec<Change>> {
+    let changes = diff(&spec_state.to_value()?, &driver_state.to_value()?);
+    (!changes.is_empty()).then_some(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        TraceBuilder,
+        encoding::{BigInt, Quint, Set},
+    };
+    use serde::{Deserialize, Serialize};
+
+    #[derive(PartialEq, Deserialize, Debug)]
+    struct Count {
+        count: u64,
+    }
+
+    impl State<Counter> for Count {
+        fn from_driver(driver: &Counter) -> Result<Self> {
+            Ok(Count {
+                count: driver.count,
+            })
+        }
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        count: u64,
+    }
+
+    impl Driver for Counter {
+        type State = Count;
+
+        fn step(&mut self, step: &Step) -> Result<()> {
+            match step.action() {
+                "init" => self.count = 0,
+                "add" => self.count += step.pick::<u64>("amount")?,
+                other => bail!("Unexpected action `{}`", other),
+            }
+            Ok(())
+        }
+    }
+
+    fn add(amount: u64, count: u64) -> crate::StepBuilder {
+        Step::builder("add")
+            .pick("amount", amount)
+            .var("count", count)
+    }
+
+    #[test]
+    fn test_replay() {
+        let trace = TraceBuilder::new()
+            .step(Step::builder("init").var("count", 0))
+            .step(add(2, 2))
+            .step(add(3, 5))
+            .build()
+            .unwrap();
+        replay(Counter::default(), trace).unwrap();
+
+        let trace = TraceBuilder::new()
+            .step(Step::builder("init").var("count", 0))
+            .step(add(2, 3))
+            .build()
+            .unwrap();
+        let err = replay(Counter::default(), trace).unwrap_err();
+        assert_eq!(err.to_string(), "State invariant failed");
+    }
+
+    #[test]
+    fn test_diff_states() {
+        let diff = diff_states::<Counter, _>(&Count { count: 5 }, &Count { count: 3 });
+        assert_eq!(diff, "  count: 5 → 3");
+    }
+
+    #[derive(PartialEq, Deserialize)]
+    struct Partial {
+        count: u64,
+        total: u64,
+    }
+
+    impl std::fmt::Debug for Partial {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.debug_struct("Partial")
+                .field("count", &self.count)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl State<Counter> for Partial {
+        fn from_driver(driver: &Counter) -> Result<Self> {
+            Ok(Partial {
+                count: driver.count,
+                total: driver.count,
+            })
+        }
+    }
+
+    #[test]
+    fn test_diff_partial_states() {
+        let spec = Partial { count: 5, total: 5 };
+        let diff = diff_states::<Counter, _>(&spec, &Partial { count: 3, total: 3 });
+        assert!(diff.starts_with("--- specification\n+++ implementation\n"));
+        assert!(diff.contains("-    count: 5,\n+    count: 3,\n"));
+    }
+
+    #[derive(Serialize)]
+    struct History {
+        seen: Vec<u64>,
+    }
+
+    impl crate::Projection<StepLog> for History {
+        fn from_driver(driver: &StepLog) -> Result<Self> {
+            Ok(History {
+                seen: driver.seen.clone(),
+            })
+        }
+    }
+
+    #[derive(Default)]
+    struct StepLog {
+        seen: Vec<u64>,
+    }
+
+    impl Driver for StepLog {
+        type State = crate::Projected<History>;
+
+        fn step(&mut self, step: &Step) -> Result<()> {
+            match step.action() {
+                "init" => self.seen.clear(),
+                "see" => self.seen.push(step.pick("value")?),
+                other => bail!("Unexpected action `{}`", other),
+            }
+            Ok(())
+        }
+    }
+
+    fn see(value: u64, seen: &[u64]) -> crate::StepBuilder {
+        let seen = Quint::<_, Set<BigInt>>::new(seen.to_vec());
+        Step::builder("see")
+            .pick("value", value)
+            .var("seen", seen)
+            .var("total", Quint::<_, BigInt>::new(value))
+    }
+
+    #[test]
+    fn test_replay_projected() {
+        let trace = TraceBuilder::new()
+            .step(Step::builder("init").var("seen", Quint::<_, Set>::new(Vec::<u64>::new())))
+            .step(see(2, &[2]))
+            .step(see(1, &[1, 2]))
+            .step(see(2, &[1, 2]))
+            .build()
+            .unwrap();
+        replay(StepLog::default(), trace).unwrap();
+
+        let trace = TraceBuilder::new()
+            .step(Step::builder("init").var("seen", Quint::<_, Set>::new(Vec::<u64>::new())))
+            .step(see(2, &[1]))
+            .build()
+            .unwrap();
+        let err = replay(StepLog::default(), trace).unwrap_err();
+        assert_eq!(err.to_string(), "State invariant failed");
+    }
+}
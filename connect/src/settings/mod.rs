@@ -0,0 +1,390 @@
+use anyhow::{Context, Result, anyhow};
+use quint_connect_spec::config::resolve_quint;
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+pub(crate) use quint_connect_spec::config::var;
+
+/// Default directory where failure reports are written to, relative to the
+/// test's working directory.
+const DEFAULT_REPORT_DIR: &str = "target/quint-connect";
+
+/// Test settings that can be set from a single source.
+#[derive(Clone, Default, PartialEq, Eq, Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Layer {
+    pub quint: Option<String>,
+    pub tmp_dir: Option<PathBuf>,
+    pub seed: Option<String>,
+    pub max_samples: Option<usize>,
+    pub max_steps: Option<usize>,
+    pub verbose: Option<u8>,
+    pub report: Option<Vec<String>>,
+    pub report_dir: Option<PathBuf>,
+    pub strict: Option<Strictness>,
+}
+
+/// How to handle nondet picks that a driver never reads.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Strictness {
+    #[default]
+    Off,
+    /// Warns once per action.
+    Warn,
+    /// Fails the step.
+    Deny,
+}
+
+impl FromStr for Strictness {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "off" => Ok(Strictness::Off),
+            "warn" => Ok(Strictness::Warn),
+            "deny" => Ok(Strictness::Deny),
+            other => Err(anyhow!(
+                "Unknown strictness `{}`, expected `off`, `warn` or `deny`",
+                other
+            )),
+        }
+    }
+}
+
+impl Layer {
+    /// Reads settings from `QUINT_*` environment variables.
+    fn from_env() -> Result<Self> {
+        Ok(Self {
+            quint: var("QUINT_BIN"),
+            tmp_dir: var("QUINT_TMP_DIR").map(PathBuf::from),
+            seed: var("QUINT_SEED"),
+            max_samples: parse_var("QUINT_MAX_SAMPLES")?,
+            max_steps: parse_var("QUINT_MAX_STEPS")?,
+            verbose: parse_var("QUINT_VERBOSE")?,
+            report: var("QUINT_REPORT").map(|formats| {
+                formats
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|fmt| !fmt.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            }),
+            report_dir: var("QUINT_REPORT_DIR").map(PathBuf::from),
+            strict: var("QUINT_STRICT")
+                .map(|strict| strict.parse().context("Invalid `QUINT_STRICT`"))
+                .transpose()?,
+        })
+    }
+
+    /// Resolves relative paths against the given directory. The Quint binary is
+    /// only resolved if it is a path rather than a command name.
+    fn resolve_paths(mut self, dir: &Path) -> Layer {
+        let resolve = |path: PathBuf| match path.is_relative() {
+            true => dir.join(path),
+            false => path,
+        };
+        self.quint = self.quint.map(|quint| resolve_quint(quint, dir));
+        self.tmp_dir = self.tmp_dir.map(resolve);
+        self.report_dir = self.report_dir.map(resolve);
+        self
+    }
+
+    /// Fills settings missing from this layer with the ones from `other`.
+    fn or(self, other: Layer) -> Layer {
+        Layer {
+            quint: self.quint.or(other.quint),
+            tmp_dir: self.tmp_dir.or(other.tmp_dir),
+            seed: self.seed.or(other.seed),
+            max_samples: self.max_samples.or(other.max_samples),
+            max_steps: self.max_steps.or(other.max_steps),
+            verbose: self.verbose.or(other.verbose),
+            report: self.report.or(other.report),
+            report_dir: self.report_dir.or(other.report_dir),
+            strict: self.strict.or(other.strict),
+        }
+    }
+}
+
+/// The `quint-connect.toml` file.
+#[derive(Default, Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    defaults: Layer,
+    tests: BTreeMap<String, Layer>,
+}
+
+impl ConfigFile {
+    /// Settings for the given test, falling back to the file's defaults. Matrix
+    /// cases, named `<function>::<case>`, fall back to their function's table.
+    fn layer(mut self, test_name: &str) -> Layer {
+        let test = self.tests.remove(test_name).unwrap_or_default();
+        let test = match test_name.split_once("::") {
+            Some((function, _)) => test.or(self.tests.remove(function).unwrap_or_default()),
+            None => test,
+        };
+        test.or(self.defaults)
+    }
+}
+
+/// Test settings resolved at runtime.
+///
+/// Settings are taken, in order of precedence, from environment variables,
+/// macro attributes, the test's table in `quint-connect.toml`, the file's
+/// `[defaults]` table, and finally built-in defaults.
+#[derive(Default, Debug)]
+pub struct Settings {
+    env: Layer,
+    file: Layer,
+}
+
+impl Settings {
+    /// Loads the settings for the given test.
+    pub fn load(test_name: &str) -> Result<Self> {
+        let env = Layer::from_env()?;
+        let file = match config_file()? {
+            Some(path) => read_config_file(&path)?
+                .layer(test_name)
+                .resolve_paths(config_dir(&path)),
+            None => Layer::default(),
+        };
+        Ok(Self { env, file })
+    }
+
+    /// Loads the settings shared by all tests, without any test's table.
+    pub fn load_defaults() -> Result<Self> {
+        let env = Layer::from_env()?;
+        let file = match config_file()? {
+            Some(path) => read_config_file(&path)?
+                .defaults
+                .resolve_paths(config_dir(&path)),
+            None => Layer::default(),
+        };
+        Ok(Self { env, file })
+    }
+
+    /// Unlike other settings, a seed given as an attribute wins over `QUINT_SEED`,
+    /// so that tests pinned to a seed stay reproducible.
+    pub fn seed(&self, attr: Option<String>) -> Option<String> {
+        attr.or(self.env.seed.clone()).or(self.file.seed.clone())
+    }
+
+    pub fn max_samples(&self, attr: Option<usize>) -> Option<usize> {
+        self.env.max_samples.or(attr).or(self.file.max_samples)
+    }
+
+    pub fn max_steps(&self, attr: Option<usize>) -> Option<usize> {
+        self.env.max_steps.or(attr).or(self.file.max_steps)
+    }
+
+    /// The Quint binary to run.
+    pub fn quint(&self) -> &str {
+        self.env
+            .quint
+            .as_deref()
+            .or(self.file.quint.as_deref())
+            .unwrap_or("quint")
+    }
+
+    /// Where to create temporary directories for generated traces.
+    pub fn tmp_dir(&self) -> Option<&Path> {
+        self.env.tmp_dir.as_deref().or(self.file.tmp_dir.as_deref())
+    }
+
+    pub fn verbosity(&self) -> u8 {
+        self.env.verbose.or(self.file.verbose).unwrap_or(0)
+    }
+
+    pub fn report_formats(&self) -> &[String] {
+        self.env
+            .report
+            .as_deref()
+            .or(self.file.report.as_deref())
+            .unwrap_or_default()
+    }
+
+    pub fn report_dir(&self) -> &Path {
+        self.env
+            .report_dir
+            .as_deref()
+            .or(self.file.report_dir.as_deref())
+            .unwrap_or(Path::new(DEFAULT_REPORT_DIR))
+    }
+
+    pub(crate) fn strict(&self) -> Strictness {
+        self.env.strict.or(self.file.strict).unwrap_or_default()
+    }
+}
+
+/// Finds the configuration file, either from `QUINT_CONFIG` or by looking up
+/// `quint-connect.toml` from the working directory upwards.
+pub(crate) fn config_file() -> Result<Option<PathBuf>> {
+    let cwd = env::current_dir().context("Failed to read the current directory")?;
+    Ok(quint_connect_spec::config::config_file(&cwd))
+}
+
+/// The directory relative paths in the configuration file are resolved from.
+fn config_dir(path: &Path) -> &Path {
+    path.parent().unwrap_or(Path::new(""))
+}
+
+fn read_config_file(path: &Path) -> Result<ConfigFile> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file at: {}", path.display()))?;
+    parse_config_file(&contents)
+        .with_context(|| format!("Failed to parse config file at: {}", path.display()))
+}
+
+fn parse_config_file(contents: &str) -> Result<ConfigFile> {
+    Ok(toml::from_str(contents)?)
+}
+
+fn parse_var<T: FromStr>(name: &str) -> Result<Option<T>> {
+    var(name)
+        .map(|value| {
+            value
+                .trim()
+                .parse()
+                .map_err(|_| anyhow!("Expected `{}` to be a number, found `{}`", name, value))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [defaults]
+        quint = "/opt/quint/bin/quint"
+        max_samples = 10
+        report = ["html"]
+        strict = "warn"
+
+        [tests.simulation]
+        max_samples = 50
+        max_steps = 20
+
+        [tests."simulation::main_large"]
+        max_steps = 100
+    "#;
+
+    fn settings(env: Layer, test_name: &str) -> Settings {
+        let file = parse_config_file(CONFIG).unwrap().layer(test_name);
+        Settings { env, file }
+    }
+
+    #[test]
+    fn test_file_defaults() {
+        let settings = settings(Layer::default(), "other");
+        assert_eq!(settings.quint(), "/opt/quint/bin/quint");
+        assert_eq!(settings.max_samples(None), Some(10));
+        assert_eq!(settings.max_steps(None), None);
+        assert_eq!(settings.report_formats(), ["html"]);
+        assert_eq!(settings.report_dir(), Path::new("target/quint-connect"));
+        assert_eq!(settings.strict(), Strictness::Warn);
+    }
+
+    #[test]
+    fn test_per_test_settings() {
+        let settings = settings(Layer::default(), "simulation");
+        assert_eq!(settings.max_samples(None), Some(50));
+        assert_eq!(settings.max_steps(None), Some(20));
+        assert_eq!(settings.quint(), "/opt/quint/bin/quint");
+    }
+
+    #[test]
+    fn test_matrix_case_settings() {
+        let small = settings(Layer::default(), "simulation::main_small");
+        assert_eq!(small.max_samples(None), Some(50));
+        assert_eq!(small.max_steps(None), Some(20));
+
+        let large = settings(Layer::default(), "simulation::main_large");
+        assert_eq!(large.max_samples(None), Some(50));
+        assert_eq!(large.max_steps(None), Some(100));
+    }
+
+    #[test]
+    fn test_precedence() {
+        let env = Layer {
+            max_steps: Some(5),
+            report: Some(vec![]),
+            seed: Some("0x1".to_string()),
+            ..Layer::default()
+        };
+        let settings = settings(env, "simulation");
+
+        // env > attribute > file
+        assert_eq!(settings.max_steps(Some(100)), Some(5));
+        assert_eq!(settings.max_samples(Some(100)), Some(100));
+        assert!(settings.report_formats().is_empty());
+
+        // attribute > env > file for seeds
+        assert_eq!(
+            settings.seed(Some("0x2".to_string())).as_deref(),
+            Some("0x2")
+        );
+        assert_eq!(settings.seed(None).as_deref(), Some("0x1"));
+    }
+
+    #[test]
+    fn test_relative_paths() {
+        let layer = parse_config_file(
+            r#"
+            [defaults]
+            quint = "node_modules/.bin/quint"
+            tmp_dir = "/tmp/traces"
+            report_dir = "reports"
+            "#,
+        )
+        .unwrap()
+        .defaults
+        .resolve_paths(Path::new("/workspace"));
+        assert_eq!(
+            layer.quint.as_deref(),
+            Some("/workspace/node_modules/.bin/quint")
+        );
+        assert_eq!(layer.tmp_dir.as_deref(), Some(Path::new("/tmp/traces")));
+        assert_eq!(
+            layer.report_dir.as_deref(),
+            Some(Path::new("/workspace/reports"))
+        );
+
+        let layer = Layer {
+            quint: Some("quint".to_string()),
+            ..Layer::default()
+        };
+        let layer = layer.resolve_paths(Path::new("/workspace"));
+        assert_eq!(layer.quint.as_deref(), Some("quint"));
+    }
+
+    #[test]
+    fn test_built_in_defaults() {
+        let settings = Settings::default();
+        assert_eq!(settings.quint(), "quint");
+        assert_eq!(settings.tmp_dir(), None);
+        assert_eq!(settings.verbosity(), 0);
+        assert_eq!(settings.strict(), Strictness::Off);
+    }
+
+    #[test]
+    fn test_parse_strictness() {
+        assert_eq!(" Deny ".parse::<Strictness>().unwrap(), Strictness::Deny);
+        let err = "strict".parse::<Strictness>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown strictness `strict`, expected `off`, `warn` or `deny`"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown field `max_sample`")]
+    fn test_unknown_settings() {
+        parse_config_file("[defaults]\nmax_sample = 1").unwrap();
+    }
+}
@@ -0,0 +1,109 @@
+use anyhow::{Context, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Constants to instantiate a parameterized module with, as pairs of constant
+/// names and Quint expressions.
+pub type Consts = &'static [(&'static str, &'static str)];
+
+/// Name of the generated module instantiating the spec's main module.
+const WRAPPER_MODULE: &str = "quintConnectMain";
+
+/// Returns the spec file and main module to run Quint with.
+///
+/// Without constants, these are the given spec and main module. Otherwise, a
+/// wrapper module importing the main module with the given constants is written
+/// to `dir` and used instead.
+pub(crate) fn instantiate(
+    spec: &str,
+    main: Option<&str>,
+    consts: Consts,
+    dir: &Path,
+) -> Result<(PathBuf, Option<String>)> {
+    if consts.is_empty() {
+        return Ok((PathBuf::from(spec), main.map(str::to_string)));
+    }
+
+    let spec = Path::new(spec)
+        .canonicalize()
+        .with_context(|| format!("Can't find spec file at: {}", spec))?;
+    // Quint defaults the main module to the spec's file name.
+    let module = match main {
+        Some(main) => main.to_string(),
+        None => file_stem(&spec)?,
+    };
+
+    let wrapper = dir.join(format!("{}.qnt", WRAPPER_MODULE));
+    fs::write(
+        &wrapper,
+        render_wrapper(&spec.with_extension(""), &module, consts),
+    )
+    .with_context(|| format!("Failed to write wrapper module at: {}", wrapper.display()))?;
+
+    Ok((wrapper, Some(WRAPPER_MODULE.to_string())))
+}
+
+fn file_stem(spec: &Path) -> Result<String> {
+    spec.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .with_context(|| format!("Invalid spec file name: {}", spec.display()))
+}
+
+fn render_wrapper(spec: &Path, module: &str, consts: Consts) -> String {
+    let consts: Vec<_> = consts
+        .iter()
+        .map(|(name, value)| format!("{} = {}", name, value))
+        .collect();
+    format!(
+        "module {} {{\n  import {}({}).* from \"{}\"\n}}\n",
+        WRAPPER_MODULE,
+        module,
+        consts.join(", "),
+        spec.display()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_without_consts() {
+        let (spec, main) = instantiate("foo.qnt", Some("bar"), &[], Path::new("tmp")).unwrap();
+        assert_eq!(spec, PathBuf::from("foo.qnt"));
+        assert_eq!(main.as_deref(), Some("bar"));
+    }
+
+    #[test]
+    fn test_render_wrapper() {
+        let consts = &[("N", "3"), ("NODES", "Set(\"a\", \"b\")")];
+        assert_eq!(
+            render_wrapper(Path::new("/specs/consensus"), "consensus", consts),
+            "module quintConnectMain {\n  \
+               import consensus(N = 3, NODES = Set(\"a\", \"b\")).* from \"/specs/consensus\"\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_write_wrapper() {
+        let dir = tempfile::tempdir().unwrap();
+        let spec = dir.path().join("consensus.qnt");
+        fs::write(&spec, "module consensus {}").unwrap();
+
+        let (wrapper, main) =
+            instantiate(spec.to_str().unwrap(), None, &[("N", "3")], dir.path()).unwrap();
+
+        assert_eq!(main.as_deref(), Some("quintConnectMain"));
+        let contents = fs::read_to_string(wrapper).unwrap();
+        assert!(contents.contains("import consensus(N = 3).* from"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't find spec file at: missing.qnt")]
+    fn test_missing_spec() {
+        instantiate("missing.qnt", None, &[("N", "3")], Path::new("tmp")).unwrap();
+    }
+}
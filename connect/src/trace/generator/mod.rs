@@ -1,3 +1,4 @@
+mod instance;
 mod run;
 mod test;
 mod utils;
@@ -5,24 +6,32 @@ mod utils;
 pub use run::RunConfig;
 pub use test::TestConfig;
 
-use crate::trace::iter::Traces;
+use crate::{settings::Settings, trace::iter::Traces};
 use anyhow::{Context, Result, anyhow};
-use std::{path::Path, process::Command};
+use std::{fmt::Debug, path::Path, process::Command};
 use tempfile::TempDir;
 
 /// Default number of traces to generate when not specified.
 const DEFAULT_TRACES: usize = 100;
 
 /// Internal trait for configuring trace generation.
-pub trait Config {
+pub trait Config: Debug {
+    /// Resolves the configuration against runtime settings, picking a random
+    /// seed if none was given.
+    fn apply(&mut self, settings: &Settings);
     fn seed(&self) -> &str;
     fn n_traces(&self) -> usize;
-    fn to_command(&self, tmpdir: &Path) -> Command;
+    fn to_command(&self, tmpdir: &Path) -> Result<Command>;
 }
 
-pub(crate) fn generate_traces<C: Config>(config: &C) -> Result<Traces> {
-    let tmpdir = TempDir::with_prefix("quint-connect-")?;
-    let mut cmd = config.to_command(tmpdir.path());
+pub(crate) fn generate_traces<C: Config>(config: &C, settings: &Settings) -> Result<Traces> {
+    let tmpdir = match settings.tmp_dir() {
+        Some(dir) => TempDir::with_prefix_in("quint-connect-", dir).with_context(|| {
+            format!("Failed to create temporary directory in: {}", dir.display())
+        })?,
+        None => TempDir::with_prefix("quint-connect-")?,
+    };
+    let mut cmd = config.to_command(tmpdir.path())?;
     let output = cmd.output().context("Failed to execute Quint command")?;
 
     if !output.status.success() {
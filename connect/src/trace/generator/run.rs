@@ -1,7 +1,17 @@
-use crate::trace::generator::{Config, DEFAULT_TRACES, utils::opt_arg};
+use crate::{
+    runner::gen_random_seed,
+    settings::Settings,
+    trace::generator::{
+        Config, DEFAULT_TRACES,
+        instance::{Consts, instantiate},
+        utils::opt_arg,
+    },
+};
+use anyhow::Result;
 use std::{path::Path, process::Command};
 
 /// Configuration for generating traces using `quint run` in simulation mode.
+#[derive(Default, Debug)]
 pub struct RunConfig {
     pub spec: String,
     pub main: Option<String>,
@@ -9,25 +19,36 @@ pub struct RunConfig {
     pub step: Option<String>,
     pub max_samples: Option<usize>,
     pub max_steps: Option<usize>,
-    pub seed: String,
+    pub seed: Option<String>,
+    pub consts: Consts,
+    pub quint: Option<String>,
 }
 
 impl Config for RunConfig {
+    fn apply(&mut self, settings: &Settings) {
+        let seed = settings.seed(self.seed.take());
+        self.seed = Some(seed.unwrap_or_else(gen_random_seed));
+        self.max_samples = settings.max_samples(self.max_samples);
+        self.max_steps = settings.max_steps(self.max_steps);
+        self.quint = Some(settings.quint().to_string());
+    }
+
     fn seed(&self) -> &str {
-        self.seed.as_str()
+        self.seed.as_deref().unwrap_or_default()
     }
 
     fn n_traces(&self) -> usize {
         self.max_samples.unwrap_or(DEFAULT_TRACES)
     }
 
-    fn to_command(&self, tmpdir: &Path) -> Command {
+    fn to_command(&self, tmpdir: &Path) -> Result<Command> {
+        let (spec, main) = instantiate(&self.spec, self.main.as_deref(), self.consts, tmpdir)?;
         let n_traces = self.n_traces().to_string();
-        let mut cmd = Command::new("quint");
+        let mut cmd = Command::new(self.quint.as_deref().unwrap_or("quint"));
         cmd.arg("run")
-            .arg(Path::new(&self.spec))
+            .arg(spec)
             .arg("--seed")
-            .arg(&self.seed)
+            .arg(self.seed())
             .arg("--max-samples")
             .arg(&n_traces)
             .arg("--n-traces")
@@ -38,7 +59,7 @@ impl Config for RunConfig {
             .arg("--verbosity")
             .arg("0");
 
-        opt_arg(&mut cmd, "--main", self.main.as_ref());
+        opt_arg(&mut cmd, "--main", main);
         opt_arg(&mut cmd, "--init", self.init.as_ref());
         opt_arg(&mut cmd, "--step", self.step.as_ref());
         opt_arg(
@@ -46,7 +67,7 @@ impl Config for RunConfig {
             "--max-steps",
             self.max_steps.map(|n| n.to_string()),
         );
-        cmd
+        Ok(cmd)
     }
 }
 
@@ -57,12 +78,14 @@ mod tests {
     fn basic_config() -> RunConfig {
         RunConfig {
             spec: "foo.qnt".to_string(),
-            seed: "42".to_string(),
+            seed: Some("42".to_string()),
             main: None,
             init: None,
             step: None,
             max_samples: None,
             max_steps: None,
+            consts: &[],
+            quint: None,
         }
     }
 
@@ -173,7 +196,7 @@ mod tests {
 
     fn to_string(config: RunConfig) -> String {
         let dir = Path::new("tmpdir");
-        let cmd = config.to_command(dir);
+        let cmd = config.to_command(dir).unwrap();
         format!("{:?}", cmd).replace("\"", "")
     }
 }
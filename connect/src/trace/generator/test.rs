@@ -1,31 +1,51 @@
-use crate::trace::generator::{Config, DEFAULT_TRACES, utils::opt_arg};
+use crate::{
+    runner::gen_random_seed,
+    settings::Settings,
+    trace::generator::{
+        Config, DEFAULT_TRACES,
+        instance::{Consts, instantiate},
+        utils::opt_arg,
+    },
+};
+use anyhow::Result;
 use std::{path::Path, process::Command};
 
 /// Configuration for generating traces using `quint test`.
+#[derive(Default, Debug)]
 pub struct TestConfig {
     pub spec: String,
     pub main: Option<String>,
     pub test: String,
     pub max_samples: Option<usize>,
-    pub seed: String,
+    pub seed: Option<String>,
+    pub consts: Consts,
+    pub quint: Option<String>,
 }
 
 impl Config for TestConfig {
+    fn apply(&mut self, settings: &Settings) {
+        let seed = settings.seed(self.seed.take());
+        self.seed = Some(seed.unwrap_or_else(gen_random_seed));
+        self.max_samples = settings.max_samples(self.max_samples);
+        self.quint = Some(settings.quint().to_string());
+    }
+
     fn seed(&self) -> &str {
-        self.seed.as_str()
+        self.seed.as_deref().unwrap_or_default()
     }
 
     fn n_traces(&self) -> usize {
         self.max_samples.unwrap_or(DEFAULT_TRACES)
     }
 
-    fn to_command(&self, tmpdir: &Path) -> Command {
+    fn to_command(&self, tmpdir: &Path) -> Result<Command> {
+        let (spec, main) = instantiate(&self.spec, self.main.as_deref(), self.consts, tmpdir)?;
         let n_traces = self.n_traces().to_string();
-        let mut cmd = Command::new("quint");
+        let mut cmd = Command::new(self.quint.as_deref().unwrap_or("quint"));
         cmd.arg("test")
-            .arg(Path::new(&self.spec))
+            .arg(spec)
             .arg("--seed")
-            .arg(&self.seed)
+            .arg(self.seed())
             .arg("--match")
             .arg(format!("^{}$", self.test))
             .arg("--max-samples")
@@ -35,8 +55,8 @@ impl Config for TestConfig {
             .arg("--verbosity")
             .arg("0");
 
-        opt_arg(&mut cmd, "--main", self.main.as_ref());
-        cmd
+        opt_arg(&mut cmd, "--main", main);
+        Ok(cmd)
     }
 }
 
@@ -48,9 +68,11 @@ mod tests {
         TestConfig {
             spec: "foo.qnt".to_string(),
             test: "happyTest".to_string(),
-            seed: "42".to_string(),
+            seed: Some("42".to_string()),
             main: None,
             max_samples: None,
+            consts: &[],
+            quint: None,
         }
     }
 
@@ -104,7 +126,7 @@ mod tests {
 
     fn to_string(config: TestConfig) -> String {
         let dir = Path::new("tmpdir");
-        let cmd = config.to_command(dir);
+        let cmd = config.to_command(dir).unwrap();
         format!("{:?}", cmd).replace("\"", "")
     }
 }
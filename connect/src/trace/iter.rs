@@ -32,15 +32,23 @@ impl Iterator for Traces {
     type Item = Result<Trace>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.iter.next() {
-            Some(Ok(entry)) => Some(trace_from_file(&entry.path())),
-            Some(Err(err)) => Some(Err(err.into())),
-            None => None,
+        loop {
+            match self.iter.next()? {
+                // Skip other files Quint was given, such as generated wrapper modules.
+                Ok(entry) if !is_trace_file(&entry.path()) => continue,
+                Ok(entry) => return Some(trace_from_file(&entry.path())),
+                Err(err) => return Some(Err(err.into())),
+            }
         }
     }
 }
 
-fn trace_from_file(path: &Path) -> Result<Trace> {
+fn is_trace_file(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().ends_with(".itf.json"))
+}
+
+pub(crate) fn trace_from_file(path: &Path) -> Result<Trace> {
     let file = File::open(path)
         .with_context(|| format!("Can't open trace file at: {}", path.display()))?;
 
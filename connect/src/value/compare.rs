@@ -0,0 +1,191 @@
+//! Comparison of ITF values with Quint's semantics, for states projected from
+//! drivers with [`Projection`](crate::Projection).
+
+use itf::value::{Record, Value};
+
+/// Whether a value serialized from a driver matches the spec's value.
+///
+/// Sets and maps match regardless of their order, integers regardless of their
+/// encoding, and the empty tuple and record, which both stand for Quint's unit
+/// value, match each other. Since serde serializes all collections as sequences,
+/// sets also match lists with the same elements, and records match maps with
+/// string keys. Spec record fields missing from the driver's record are not
+/// compared, as when deserializing states.
+pub(crate) fn matches(spec: &Value, actual: &Value) -> bool {
+    if spec == actual {
+        return true;
+    }
+    match (spec, actual) {
+        (Value::Set(spec), Value::Set(actual)) => same_elems(spec.iter(), actual.iter()),
+        (Value::Set(spec), Value::List(actual)) => same_elems(spec.iter(), actual.iter()),
+        (Value::List(spec), Value::List(actual)) => same_seq(spec.iter(), actual.iter()),
+        (Value::Tuple(spec), Value::Tuple(actual)) => same_seq(spec.iter(), actual.iter()),
+        (Value::Tuple(spec), Value::List(actual)) => same_seq(spec.iter(), actual.iter()),
+        (Value::Map(spec), Value::Map(actual)) => same_entries(spec.iter(), actual.iter()),
+        (Value::Map(spec), Value::Record(actual)) => {
+            let actual: Vec<_> = actual
+                .iter()
+                .map(|(key, value)| (Value::String(key.clone()), value))
+                .collect();
+            same_entries(spec.iter(), actual.iter().map(|(key, value)| (key, *value)))
+        }
+        (Value::Record(spec), Value::Record(actual)) => same_fields(spec, actual.iter()),
+        (Value::Record(spec), Value::Map(actual)) => {
+            let mut fields = Vec::new();
+            for (key, value) in actual.iter() {
+                let Value::String(key) = key else {
+                    return false;
+                };
+                fields.push((key, value));
+            }
+            same_fields(spec, fields.into_iter())
+        }
+        (Value::Tuple(unit), Value::Record(rec)) | (Value::Record(rec), Value::Tuple(unit)) => {
+            unit.is_empty() && rec.is_empty()
+        }
+        _ => int(spec).is_some() && int(spec) == int(actual),
+    }
+}
+
+/// The decimal digits of an integer, whatever its encoding.
+fn int(value: &Value) -> Option<String> {
+    match value {
+        Value::Number(n) => Some(n.to_string()),
+        Value::BigInt(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn same_seq<'a>(
+    mut spec: impl Iterator<Item = &'a Value>,
+    mut actual: impl Iterator<Item = &'a Value>,
+) -> bool {
+    loop {
+        match (spec.next(), actual.next()) {
+            (Some(spec), Some(actual)) if matches(spec, actual) => continue,
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// Whether each element matches one of the other collection, regardless of order
+/// and duplicates.
+fn same_elems<'a>(
+    spec: impl Iterator<Item = &'a Value>,
+    actual: impl Iterator<Item = &'a Value>,
+) -> bool {
+    let spec: Vec<_> = spec.collect();
+    let actual: Vec<_> = actual.collect();
+    actual
+        .iter()
+        .all(|elem| spec.iter().any(|spec_elem| matches(spec_elem, elem)))
+        && spec
+            .iter()
+            .all(|spec_elem| actual.iter().any(|elem| matches(spec_elem, elem)))
+}
+
+fn same_entries<'a>(
+    spec: impl Iterator<Item = (&'a Value, &'a Value)>,
+    actual: impl Iterator<Item = (&'a Value, &'a Value)>,
+) -> bool {
+    let spec: Vec<_> = spec.collect();
+    let actual: Vec<_> = actual.collect();
+    spec.len() == actual.len()
+        && actual.iter().all(|(key, value)| {
+            spec.iter()
+                .any(|(spec_key, spec_value)| matches(spec_key, key) && matches(spec_value, value))
+        })
+}
+
+fn same_fields<'a>(
+    spec: &Record,
+    mut actual: impl Iterator<Item = (&'a String, &'a Value)>,
+) -> bool {
+    actual.all(|(field, value)| {
+        spec.get(field)
+            .is_some_and(|spec_value| matches(spec_value, value))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::{to_value, unit, variant};
+    use itf::value::BigInt;
+    use serde::Serialize;
+    use std::collections::{BTreeMap, HashMap, HashSet};
+
+    fn json(value: serde_json::Value) -> Value {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_quint_semantics() {
+        let spec = json(serde_json::json!({
+            "players": { "#set": [{ "#bigint": "1" }, { "#bigint": "2" }] },
+            "scores": { "#map": [["alice", { "#bigint": "10" }], ["bob", { "#bigint": "3" }]] },
+            "winner": { "tag": "None", "value": { "#tup": [] } },
+            "round": { "#bigint": "2" },
+        }));
+
+        #[derive(Serialize)]
+        struct Game {
+            players: HashSet<u64>,
+            scores: HashMap<String, u128>,
+            winner: Option<String>,
+        }
+        let game = Game {
+            players: HashSet::from([2, 1]),
+            scores: HashMap::from([("bob".to_string(), 3), ("alice".to_string(), 10)]),
+            winner: None,
+        };
+        assert!(matches(&spec, &to_value(&game).unwrap()));
+
+        let game = Game {
+            players: HashSet::from([1]),
+            ..game
+        };
+        assert!(!matches(&spec, &to_value(&game).unwrap()));
+    }
+
+    #[test]
+    fn test_unit_encodings() {
+        let none = variant("None", Value::Record(Record::new()));
+        assert!(matches(&none, &variant("None", unit())));
+        assert!(!matches(&none, &variant("Some", unit())));
+    }
+
+    #[test]
+    fn test_collections() {
+        let set = Value::Set([Value::Number(1), Value::Number(2)].into_iter().collect());
+        let list = to_value(&vec![2, 1, 2]).unwrap();
+        assert!(matches(&set, &list));
+        assert!(!matches(&list, &set));
+        assert!(!matches(&set, &to_value(&vec![1]).unwrap()));
+        assert!(!matches(&to_value(&vec![1, 2]).unwrap(), &list));
+
+        let tuple = Value::Tuple(vec![Value::BigInt(BigInt::new(1)), Value::Bool(true)].into());
+        assert!(matches(&tuple, &to_value(&(1, true)).unwrap()));
+        assert!(!matches(&tuple, &to_value(&[1, 1]).unwrap()));
+
+        let map = json(serde_json::json!({
+            "#map": [[{ "#tup": [1, 2] }, { "#set": ["a"] }]]
+        }));
+        let other = to_value(&BTreeMap::from([((1, 2), vec!["a", "a"])])).unwrap();
+        assert!(matches(&map, &other));
+        let other = to_value(&BTreeMap::from([((2, 1), vec!["a"])])).unwrap();
+        assert!(!matches(&map, &other));
+    }
+
+    #[test]
+    fn test_extra_spec_fields() {
+        let spec = json(serde_json::json!({ "a": 1, "b": 2 }));
+        assert!(matches(&spec, &json(serde_json::json!({ "a": 1 }))));
+        assert!(!matches(
+            &spec,
+            &json(serde_json::json!({ "a": 1, "c": 3 }))
+        ));
+        assert!(!matches(&spec, &json(serde_json::json!({ "a": 2 }))));
+    }
+}
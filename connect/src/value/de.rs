@@ -0,0 +1,73 @@
+use serde::{Deserialize, Deserializer};
+use serde_with::DeserializeAs;
+use std::marker::PhantomData;
+
+/// Encodes a Rust [`Option`] as Quint's `Option` sum type, adapting its value
+/// with `T`.
+///
+/// Unlike [`itf::de::Option`], the value itself can be adapted, e.g. with
+/// `QuintOption<Vec<QuintOption<Same>>>` for an `Option<Vec<Option<u64>>>`.
+pub struct QuintOption<T>(PhantomData<T>);
+
+#[derive(Deserialize)]
+#[serde(tag = "tag", content = "value")]
+enum Repr<T> {
+    Some(T),
+    None,
+}
+
+/// Decodes the value of a `Some` variant with the `DeserializeAs` adapter `A`.
+struct Adapted<T, A>(T, PhantomData<A>);
+
+impl<'de, T, A: DeserializeAs<'de, T>> Deserialize<'de> for Adapted<T, A> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        A::deserialize_as(deserializer).map(|value| Adapted(value, PhantomData))
+    }
+}
+
+impl<'de, T, A: DeserializeAs<'de, T>> DeserializeAs<'de, Option<T>> for QuintOption<A> {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<Option<T>, D::Error> {
+        Ok(match Repr::<Adapted<T, A>>::deserialize(deserializer)? {
+            Repr::Some(Adapted(value, _)) => Some(value),
+            Repr::None => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itf::{Value, value::Record};
+    use serde_with::{As, Same};
+
+    fn some(value: Value) -> Value {
+        let mut rec = Record::new();
+        rec.insert("tag".to_string(), Value::String("Some".to_string()));
+        rec.insert("value".to_string(), value);
+        Value::Record(rec)
+    }
+
+    fn none() -> Value {
+        let mut rec = Record::new();
+        rec.insert("tag".to_string(), Value::String("None".to_string()));
+        rec.insert("value".to_string(), Value::Record(Record::new()));
+        Value::Record(rec)
+    }
+
+    #[test]
+    fn test_quint_option() {
+        let decode = |value| As::<QuintOption<Same>>::deserialize(value);
+        let value: Option<u64> = decode(some(Value::Number(42))).unwrap();
+        assert_eq!(value, Some(42));
+        let value: Option<u64> = decode(none()).unwrap();
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    fn test_nested_quint_options() {
+        let list = Value::List(vec![some(Value::Number(1)), none()]);
+        let value: Option<Vec<Option<u64>>> =
+            As::<QuintOption<Vec<QuintOption<Same>>>>::deserialize(some(list)).unwrap();
+        assert_eq!(value, Some(vec![Some(1), None]));
+    }
+}
@@ -0,0 +1,339 @@
+//! Reads values back from their derived [`Debug`] output, so that states which
+//! only implement `Debug` can be diffed structurally.
+
+use crate::value::{unit, variant};
+use itf::value::{BigInt, Record, Value};
+use std::{iter::Peekable, str::CharIndices};
+
+/// Parses the `{:?}` output of a value into an ITF value.
+///
+/// Structs, enum variants and tuple structs become tagged values, with the
+/// fields of structs and struct variants as records, and `{...}` collections
+/// become sets or maps. Returns `None` if the output doesn't look like derived
+/// `Debug` output, e.g. for custom impls, or if it omits fields with `..`, since
+/// the value would then only be partially compared.
+pub(crate) fn from_debug(text: &str) -> Option<Value> {
+    let mut parser = Parser {
+        text,
+        chars: text.char_indices().peekable(),
+    };
+    let value = parser.value()?;
+    parser.skip_spaces();
+    parser.chars.peek().is_none().then_some(value)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl Parser<'_> {
+    fn value(&mut self) -> Option<Value> {
+        self.skip_spaces();
+        match self.peek()? {
+            '"' => self.string().map(Value::String),
+            '\'' => self.char().map(Value::String),
+            '[' => {
+                self.next();
+                self.elems(']').map(Value::List)
+            }
+            '(' => {
+                self.next();
+                self.elems(')').map(|elems| Value::Tuple(elems.into()))
+            }
+            '{' => {
+                self.next();
+                self.collection()
+            }
+            c if c == '-' || c.is_ascii_digit() => self.number(),
+            c if c.is_alphabetic() || c == '_' => self.named(),
+            _ => None,
+        }
+    }
+
+    /// A boolean, a unit variant, or a struct, tuple struct or variant with
+    /// fields.
+    fn named(&mut self) -> Option<Value> {
+        let name = self.ident();
+        match name.as_str() {
+            "true" => return Some(Value::Bool(true)),
+            "false" => return Some(Value::Bool(false)),
+            _ => {}
+        }
+        // Fields are separated from the name by a space, while tuple fields follow it.
+        match self.peek() {
+            Some('(') => {
+                self.next();
+                let mut elems = self.elems(')')?;
+                let value = match elems.len() {
+                    1 => elems.pop()?,
+                    _ => Value::Tuple(elems.into()),
+                };
+                Some(variant(&name, value))
+            }
+            Some(' ') if self.text[self.offset()..].trim_start().starts_with('{') => {
+                self.skip_spaces();
+                self.next();
+                self.fields().map(|rec| variant(&name, Value::Record(rec)))
+            }
+            _ => Some(variant(&name, unit())),
+        }
+    }
+
+    fn fields(&mut self) -> Option<Record> {
+        let mut rec = Record::new();
+        loop {
+            self.skip_spaces();
+            match self.peek()? {
+                '}' => {
+                    self.next();
+                    return Some(rec);
+                }
+                _ => {
+                    let field = self.ident();
+                    self.expect(':')?;
+                    rec.insert(field, self.value()?);
+                }
+            }
+            self.separator('}')?;
+        }
+    }
+
+    /// A set, or a map if its first element is followed by a colon.
+    fn collection(&mut self) -> Option<Value> {
+        let mut elems = Vec::new();
+        let mut entries = Vec::new();
+        loop {
+            self.skip_spaces();
+            if self.peek()? == '}' {
+                self.next();
+                break;
+            }
+            let value = self.value()?;
+            self.skip_spaces();
+            if self.peek()? == ':' && elems.is_empty() {
+                self.next();
+                entries.push((value, self.value()?));
+            } else if entries.is_empty() {
+                elems.push(value);
+            } else {
+                return None;
+            }
+            self.separator('}')?;
+        }
+        Some(if entries.is_empty() {
+            Value::Set(elems.into_iter().collect())
+        } else {
+            Value::Map(entries.into_iter().collect())
+        })
+    }
+
+    fn elems(&mut self, close: char) -> Option<Vec<Value>> {
+        let mut elems = Vec::new();
+        loop {
+            self.skip_spaces();
+            if self.peek()? == close {
+                self.next();
+                return Some(elems);
+            }
+            elems.push(self.value()?);
+            self.separator(close)?;
+        }
+    }
+
+    /// Consumes the comma after an element, unless the collection is closing.
+    fn separator(&mut self, close: char) -> Option<()> {
+        self.skip_spaces();
+        match self.peek()? {
+            ',' => {
+                self.next();
+                Some(())
+            }
+            c if c == close => Some(()),
+            _ => None,
+        }
+    }
+
+    fn number(&mut self) -> Option<Value> {
+        let start = self.offset();
+        self.next();
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '.')
+        {
+            self.next();
+        }
+        let number = &self.text[start..self.offset()];
+        if let Ok(n) = number.parse::<i64>() {
+            Some(Value::Number(n))
+        } else if let Ok(n) = number.parse::<i128>() {
+            Some(Value::BigInt(BigInt::new(n)))
+        } else if let Ok(n) = number.parse::<u128>() {
+            Some(Value::BigInt(BigInt::new(n)))
+        } else {
+            // Floats and the like are kept as written.
+            Some(Value::String(number.to_string()))
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.quoted('"')
+    }
+
+    fn char(&mut self) -> Option<String> {
+        self.quoted('\'')
+    }
+
+    fn quoted(&mut self, quote: char) -> Option<String> {
+        self.next();
+        let mut string = String::new();
+        loop {
+            match self.next()? {
+                '\\' => match self.next()? {
+                    'n' => string.push('\n'),
+                    'r' => string.push('\r'),
+                    't' => string.push('\t'),
+                    '0' => string.push('\0'),
+                    c @ ('\\' | '"' | '\'') => string.push(c),
+                    c => {
+                        // Unicode escapes are kept as written.
+                        string.push('\\');
+                        string.push(c);
+                    }
+                },
+                c if c == quote => return Some(string),
+                c => string.push(c),
+            }
+        }
+    }
+
+    fn ident(&mut self) -> String {
+        let start = self.offset();
+        loop {
+            while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+                self.next();
+            }
+            if !self.text[self.offset()..].starts_with("::") {
+                break;
+            }
+            self.next();
+            self.next();
+        }
+        self.text[start..self.offset()].to_string()
+    }
+
+    fn expect(&mut self, expected: char) -> Option<()> {
+        self.skip_spaces();
+        (self.next()? == expected).then_some(())
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.next();
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|(_, c)| *c)
+    }
+
+    fn next(&mut self) -> Option<char> {
+        self.chars.next().map(|(_, c)| c)
+    }
+
+    fn offset(&mut self) -> usize {
+        self.chars.peek().map_or(self.text.len(), |(i, _)| *i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::ValueDisplay;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+    #[allow(dead_code)]
+    enum Stage {
+        Working,
+        Prepared,
+        Voted(bool),
+        Moved { to: (u8, char) },
+    }
+
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    struct Participant {
+        stage: Stage,
+        name: Option<String>,
+        votes: Vec<i64>,
+    }
+
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    struct System {
+        participants: BTreeMap<String, Participant>,
+        messages: BTreeSet<Stage>,
+        id: Id,
+        total: u128,
+        ratio: f64,
+    }
+
+    #[derive(Debug)]
+    struct Id(#[allow(dead_code)] u8);
+
+    fn parse(value: impl std::fmt::Debug) -> String {
+        let compact = from_debug(&format!("{:?}", value)).unwrap();
+        let pretty = from_debug(&format!("{:#?}", value)).unwrap();
+        assert_eq!(compact, pretty);
+        compact.display().to_string()
+    }
+
+    #[test]
+    fn test_scalars() {
+        assert_eq!(parse(true), "true");
+        assert_eq!(parse(-42), "-42");
+        assert_eq!(parse(u128::MAX), u128::MAX.to_string());
+        assert_eq!(parse(1.5), "\"1.5\"");
+        assert_eq!(parse("a \"quoted\"\nline"), "\"a \"quoted\"\nline\"");
+        assert_eq!(parse('x'), "\"x\"");
+        assert_eq!(parse(()), "()");
+    }
+
+    #[test]
+    fn test_structs_and_variants() {
+        let system = System {
+            participants: BTreeMap::from([(
+                "p1".to_string(),
+                Participant {
+                    stage: Stage::Moved { to: (1, 'a') },
+                    name: Some("Alice".to_string()),
+                    votes: vec![1, -2],
+                },
+            )]),
+            messages: BTreeSet::from([Stage::Voted(true)]),
+            id: Id(7),
+            total: 0,
+            ratio: 0.5,
+        };
+        assert_eq!(
+            parse(system),
+            "System({ id: Id(7), messages: Set(Voted(true)), \
+             participants: Map(\"p1\" -> Participant({ name: Some(\"Alice\"), \
+             stage: Moved({ to: (1, \"a\") }), votes: List(1, -2) })), ratio: \"0.5\", \
+             total: 0 })"
+        );
+        assert_eq!(parse(Stage::Prepared), "Prepared");
+        assert_eq!(parse(Option::<u8>::None), "None");
+        assert_eq!(parse(BTreeMap::<u8, u8>::new()), "Set()");
+    }
+
+    #[test]
+    fn test_custom_debug() {
+        assert_eq!(from_debug("Counter(n = 2)"), None);
+        assert_eq!(from_debug("{1, 2: 3}"), None);
+        assert_eq!(from_debug("[1, 2"), None);
+        assert_eq!(from_debug("1 2"), None);
+        assert_eq!(from_debug("Counter { n: 2, .. }"), None);
+    }
+}
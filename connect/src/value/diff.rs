@@ -0,0 +1,384 @@
+//! Structural diff between the specification's and the implementation's states.
+
+use crate::{
+    logger::Colorize,
+    value::{ValueDisplay, matches},
+};
+use itf::value::{Record, Value};
+use std::fmt::{self, Display, Formatter};
+
+/// A difference between two values, at the path where they diverge.
+#[derive(PartialEq, Debug)]
+pub(crate) enum Change {
+    Changed {
+        path: String,
+        spec: Value,
+        actual: Value,
+    },
+    Added {
+        path: String,
+        value: Value,
+    },
+    Removed {
+        path: String,
+        value: Value,
+    },
+}
+
+impl Change {
+    fn path(&self) -> &str {
+        match self {
+            Change::Changed { path, .. }
+            | Change::Added { path, .. }
+            | Change::Removed { path, .. } => path,
+        }
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Change::Changed { path, spec, actual } => {
+                write!(f, "  {}: {} → {}", path, spec.display(), actual.display())
+            }
+            Change::Added { path, value } => write!(f, "+ {}: {}", path, value.display()),
+            Change::Removed { path, value } => write!(f, "- {}: {}", path, value.display()),
+        }
+    }
+}
+
+/// Computes the changes from the specification's value to the implementation's.
+///
+/// Records and tagged values are compared field by field, maps by key and
+/// lists and tuples by position, while sets report the elements added and
+/// removed. Paths are written in Quint syntax, e.g. `system.votes.get("p1")._1`.
+/// Fields of the specification that the implementation doesn't have are not
+/// compared, as in [`matches`].
+pub(crate) fn diff(spec: &Value, actual: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    walk("", spec, actual, &mut changes);
+    changes
+}
+
+fn walk(path: &str, spec: &Value, actual: &Value, changes: &mut Vec<Change>) {
+    if matches(spec, actual) {
+        return;
+    }
+    match (spec, actual) {
+        (Value::Record(spec), Value::Record(actual)) => match (tagged(spec), tagged(actual)) {
+            (Some((spec_tag, spec)), Some((tag, actual))) if spec_tag == tag => {
+                walk(path, spec, actual, changes)
+            }
+            (None, None) => {
+                for (field, value) in actual.iter() {
+                    let path = field_path(path, field);
+                    match spec.get(field) {
+                        Some(spec) => walk(&path, spec, value, changes),
+                        None => changes.push(Change::Added {
+                            path,
+                            value: value.clone(),
+                        }),
+                    }
+                }
+            }
+            _ => changed(
+                path,
+                &Value::Record(spec.clone()),
+                &Value::Record(actual.clone()),
+                changes,
+            ),
+        },
+        (Value::Map(spec), Value::Map(actual)) => {
+            for (key, value) in actual.iter() {
+                let path = join(path, &format!(".get({})", key.display()));
+                match spec.iter().find(|(spec_key, _)| matches(spec_key, key)) {
+                    Some((_, spec)) => walk(&path, spec, value, changes),
+                    None => changes.push(Change::Added {
+                        path,
+                        value: value.clone(),
+                    }),
+                }
+            }
+            for (key, value) in spec.iter() {
+                if !actual
+                    .iter()
+                    .any(|(actual_key, _)| matches(key, actual_key))
+                {
+                    changes.push(Change::Removed {
+                        path: join(path, &format!(".get({})", key.display())),
+                        value: value.clone(),
+                    });
+                }
+            }
+        }
+        (Value::Set(spec), Value::Set(actual)) => elems(
+            path,
+            spec.iter().collect(),
+            actual.iter().collect(),
+            changes,
+        ),
+        (Value::Set(spec), Value::List(actual)) => elems(
+            path,
+            spec.iter().collect(),
+            actual.iter().collect(),
+            changes,
+        ),
+        (Value::List(spec), Value::List(actual)) => seq(
+            path,
+            spec.iter().collect(),
+            actual.iter().collect(),
+            |i| format!("[{}]", i),
+            changes,
+        ),
+        (Value::Tuple(spec), Value::Tuple(actual)) => seq(
+            path,
+            spec.iter().collect(),
+            actual.iter().collect(),
+            |i| format!("._{}", i + 1),
+            changes,
+        ),
+        (Value::Tuple(spec), Value::List(actual)) => seq(
+            path,
+            spec.iter().collect(),
+            actual.iter().collect(),
+            |i| format!("._{}", i + 1),
+            changes,
+        ),
+        _ => changed(path, spec, actual, changes),
+    }
+}
+
+/// The tag and value of a Quint sum type.
+fn tagged(rec: &Record) -> Option<(&str, &Value)> {
+    match (rec.len(), rec.get("tag"), rec.get("value")) {
+        (2, Some(Value::String(tag)), Some(value)) => Some((tag, value)),
+        _ => None,
+    }
+}
+
+fn elems(path: &str, spec: Vec<&Value>, actual: Vec<&Value>, changes: &mut Vec<Change>) {
+    for value in &spec {
+        if !actual.iter().any(|actual| matches(value, actual)) {
+            changes.push(Change::Removed {
+                path: join(path, ""),
+                value: (*value).clone(),
+            });
+        }
+    }
+    for value in &actual {
+        if !spec.iter().any(|spec| matches(spec, value)) {
+            changes.push(Change::Added {
+                path: join(path, ""),
+                value: (*value).clone(),
+            });
+        }
+    }
+}
+
+fn seq(
+    path: &str,
+    spec: Vec<&Value>,
+    actual: Vec<&Value>,
+    index: impl Fn(usize) -> String,
+    changes: &mut Vec<Change>,
+) {
+    for i in 0..spec.len().max(actual.len()) {
+        let path = join(path, &index(i));
+        match (spec.get(i), actual.get(i)) {
+            (Some(spec), Some(actual)) => walk(&path, spec, actual, changes),
+            (Some(spec), None) => changes.push(Change::Removed {
+                path,
+                value: (*spec).clone(),
+            }),
+            (None, Some(actual)) => changes.push(Change::Added {
+                path,
+                value: (*actual).clone(),
+            }),
+            (None, None) => unreachable!(),
+        }
+    }
+}
+
+fn changed(path: &str, spec: &Value, actual: &Value, changes: &mut Vec<Change>) {
+    changes.push(Change::Changed {
+        path: join(path, ""),
+        spec: spec.clone(),
+        actual: actual.clone(),
+    });
+}
+
+fn field_path(path: &str, field: &str) -> String {
+    match path {
+        "" => field.to_string(),
+        _ => format!("{}.{}", path, field),
+    }
+}
+
+/// Appends an accessor to a path, naming the root `state`.
+fn join(path: &str, accessor: &str) -> String {
+    match path {
+        "" => format!("state{}", accessor),
+        _ => format!("{}{}", path, accessor),
+    }
+}
+
+/// Renders the changes one per line.
+pub(crate) fn render(changes: &[Change]) -> String {
+    changes
+        .iter()
+        .map(|change| change.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders the changes as a colored table, with the specification's values on
+/// the left and the implementation's on the right.
+pub(crate) fn side_by_side(changes: &[Change]) -> String {
+    let rows: Vec<(&str, String, String)> = changes
+        .iter()
+        .map(|change| match change {
+            Change::Changed { path, spec, actual } => (
+                path.as_str(),
+                spec.display().to_string(),
+                actual.display().to_string(),
+            ),
+            Change::Added { value, .. } => {
+                (change.path(), String::new(), value.display().to_string())
+            }
+            Change::Removed { value, .. } => {
+                (change.path(), value.display().to_string(), String::new())
+            }
+        })
+        .collect();
+
+    let header = ("path", "specification", "implementation");
+    let path_width = width(header.0, rows.iter().map(|row| row.0));
+    let spec_width = width(header.1, rows.iter().map(|row| row.1.as_str()));
+
+    let mut table = format!(
+        "{}",
+        format!(
+            "{:path_width$}  {:spec_width$}  {}",
+            header.0, header.1, header.2
+        )
+        .bold()
+    );
+    for (path, spec, actual) in &rows {
+        let row = match actual.is_empty() {
+            true => format!("\n{:path_width$}  {}", path, spec.red()),
+            false => format!(
+                "\n{:path_width$}  {}  {}",
+                path,
+                format!("{:spec_width$}", spec).red(),
+                actual.green()
+            ),
+        };
+        table.push_str(&row);
+    }
+    table
+}
+
+fn width<'a>(header: &str, column: impl Iterator<Item = &'a str>) -> usize {
+    column
+        .map(|cell| cell.chars().count())
+        .chain([header.len()])
+        .max()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(value: serde_json::Value) -> Value {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn stage(tag: &str) -> serde_json::Value {
+        serde_json::json!({ "tag": tag, "value": { "#tup": [] } })
+    }
+
+    #[test]
+    fn test_field_paths() {
+        let spec = json(serde_json::json!({
+            "system": {
+                "p1": { "stage": stage("Working") },
+                "p2": { "stage": stage("Prepared") },
+            },
+            "votes": { "#map": [["p1", { "#tup": [true, 1] }]] },
+            "log": ["a", "b"],
+        }));
+        let actual = json(serde_json::json!({
+            "system": {
+                "p1": { "stage": stage("Working") },
+                "p2": { "stage": stage("Aborted") },
+            },
+            "votes": { "#map": [["p1", { "#tup": [true, 2] }], ["p2", { "#tup": [false, 0] }]] },
+            "log": ["a"],
+        }));
+        assert_eq!(
+            render(&diff(&spec, &actual)),
+            "- log[1]: \"b\"\n  \
+             system.p2.stage: Prepared → Aborted\n  \
+             votes.get(\"p1\")._2: 1 → 2\n\
+             + votes.get(\"p2\"): (false, 0)"
+        );
+    }
+
+    #[test]
+    fn test_set_elements() {
+        let spec = json(serde_json::json!({
+            "messages": { "#set": [stage("Abort"), stage("Prepared")] },
+        }));
+        let actual = json(serde_json::json!({
+            "messages": { "#set": [stage("Commit"), stage("Prepared")] },
+        }));
+        assert_eq!(
+            render(&diff(&spec, &actual)),
+            "- messages: Abort\n+ messages: Commit"
+        );
+    }
+
+    #[test]
+    fn test_tagged_values() {
+        let moved = |tag: &str, x: u64| serde_json::json!({ "tag": tag, "value": { "to": x } });
+        let spec = json(serde_json::json!({ "stage": moved("Moved", 1) }));
+
+        let actual = json(serde_json::json!({ "stage": moved("Moved", 2) }));
+        assert_eq!(render(&diff(&spec, &actual)), "  stage.to: 1 → 2");
+
+        let actual = json(serde_json::json!({ "stage": moved("Placed", 1) }));
+        assert_eq!(
+            render(&diff(&spec, &actual)),
+            "  stage: Moved({ to: 1 }) → Placed({ to: 1 })"
+        );
+    }
+
+    #[test]
+    fn test_root_paths() {
+        let spec = json(serde_json::json!({ "#tup": [1, 2] }));
+        let actual = json(serde_json::json!({ "#tup": [1, 3] }));
+        assert_eq!(render(&diff(&spec, &actual)), "  state._2: 2 → 3");
+
+        let spec = json(serde_json::json!(stage("Some")));
+        assert_eq!(
+            render(&diff(&spec, &json(stage("None")))),
+            "  state: Some → None"
+        );
+        assert!(diff(&spec, &spec).is_empty());
+    }
+
+    #[test]
+    fn test_side_by_side() {
+        colored::control::set_override(false);
+        let spec = json(serde_json::json!({ "count": 1, "tags": { "#set": ["a"] } }));
+        let actual = json(serde_json::json!({ "count": 2, "tags": { "#set": ["b"] } }));
+        assert_eq!(
+            side_by_side(&diff(&spec, &actual)),
+            "path   specification  implementation\n\
+             count  1              2\n\
+             tags   \"a\"\n\
+             tags                  \"b\""
+        );
+    }
+}
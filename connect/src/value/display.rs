@@ -1,8 +1,18 @@
 use itf::value::{Map, Record, Value};
 use std::fmt::{Display, Formatter, Result};
 
-/// Internal trait for displaying ITF values in Quint-like syntax.
-pub(crate) trait ValueDisplay {
+/// Displays ITF values in Quint syntax.
+///
+/// # Examples
+///
+/// ```rust
+/// use itf::Value;
+/// use quint_connect::ValueDisplay;
+///
+/// let value = Value::List(vec![Value::Number(1), Value::String("a".to_string())]);
+/// assert_eq!(value.display().to_string(), "List(1, \"a\")");
+/// ```
+pub trait ValueDisplay {
     fn display(&self) -> impl Display;
 }
 
@@ -1,5 +1,16 @@
+mod compare;
+mod de;
+mod debug;
+mod diff;
 mod display;
 mod option;
+mod ser;
 
-pub(crate) use display::ValueDisplay;
+pub(crate) use compare::matches;
+pub use de::QuintOption;
+pub(crate) use debug::from_debug;
+pub(crate) use diff::{Change, diff, render, side_by_side};
+pub use display::ValueDisplay;
 pub(crate) use option::ValueOption;
+pub use ser::to_value;
+pub(crate) use ser::{Itf, unit, variant};
@@ -0,0 +1,642 @@
+use crate::value::ValueDisplay;
+use itf::value::{BigInt, Map, Record, Value};
+use serde::{Serialize, ser};
+use std::fmt;
+
+/// Serializes a Rust value into an [`itf::Value`] with Quint's encodings.
+///
+/// Structs become records, enums become sum types with `tag` and `value` fields,
+/// options become Quint's `Option` type, and unit becomes the empty tuple.
+/// Sequences become lists, since sets can't be told apart from them, and maps
+/// become maps. Integers outside of the range of `i64` become big integers. Enums
+/// with serde's `tag = "tag"` and `content = "value"` attributes are encoded as sum
+/// types too.
+///
+/// # Errors
+///
+/// Returns an error for floating-point numbers, which Quint doesn't have.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> anyhow::Result<Value> {
+    Ok(value.serialize(Serializer { itf: false })?)
+}
+
+/// The name of the newtype struct wrapping values already encoded as Quint's,
+/// see [`Itf`].
+const ITF_TOKEN: &str = "$quint_connect::Itf";
+
+/// Serializes an [`itf::Value`] in the ITF format of Quint's traces, so that
+/// adapters can encode values as Quint's for any serializer, e.g. `serde_json`.
+///
+/// [`to_value`] recognizes the wrapper and decodes the value back as is: records
+/// stay records, which would otherwise be taken for maps with string keys.
+pub(crate) struct Itf<'a>(pub &'a Value);
+
+impl Serialize for Itf<'_> {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(ITF_TOKEN, self.0)
+    }
+}
+
+/// Errors raised while serializing into an [`itf::Value`].
+#[derive(Debug)]
+pub struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+/// A variant of a Quint sum type.
+pub(crate) fn variant(tag: &str, value: Value) -> Value {
+    let mut rec = Record::new();
+    rec.insert("tag".to_string(), Value::String(tag.to_string()));
+    rec.insert("value".to_string(), value);
+    Value::Record(rec)
+}
+
+/// The empty tuple, the value of Quint's unit variants.
+pub(crate) fn unit() -> Value {
+    Value::Tuple(Vec::new().into())
+}
+
+fn int(value: i128) -> Value {
+    match i64::try_from(value) {
+        Ok(value) => Value::Number(value),
+        Err(_) => Value::BigInt(BigInt::new(value)),
+    }
+}
+
+/// Serializes into an [`itf::Value`]. In ITF mode, objects are records, as in the
+/// ITF format, rather than Rust maps.
+#[derive(Clone, Copy)]
+struct Serializer {
+    itf: bool,
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = SeqSerializer;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = StructSerializer;
+    type SerializeStructVariant = StructSerializer;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, Error> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, Error> {
+        Ok(int(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, Error> {
+        Ok(int(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, Error> {
+        Ok(int(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, Error> {
+        Ok(int(v.into()))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value, Error> {
+        Ok(int(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, Error> {
+        Ok(int(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, Error> {
+        Ok(int(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, Error> {
+        Ok(int(v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, Error> {
+        Ok(int(v.into()))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value, Error> {
+        Ok(match i128::try_from(v) {
+            Ok(v) => int(v),
+            Err(_) => Value::BigInt(BigInt::new(v)),
+        })
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, Error> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, Error> {
+        Err(Error(format!(
+            "Can not serialize floating-point number {} into a Quint value",
+            v
+        )))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, Error> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, Error> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, Error> {
+        Ok(Value::List(v.iter().map(|b| int((*b).into())).collect()))
+    }
+
+    fn serialize_none(self) -> Result<Value, Error> {
+        Ok(variant("None", unit()))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, Error> {
+        Ok(variant("Some", value.serialize(self)?))
+    }
+
+    fn serialize_unit(self) -> Result<Value, Error> {
+        Ok(unit())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, Error> {
+        Ok(unit())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant_name: &'static str,
+    ) -> Result<Value, Error> {
+        Ok(variant(variant_name, unit()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        if name == ITF_TOKEN {
+            return value.serialize(Serializer { itf: true });
+        }
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant_name: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        Ok(variant(variant_name, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer::new(self, Seq::List, len))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer::new(self, Seq::Tuple, Some(len)))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer::new(self, Seq::Tuple, Some(len)))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant_name: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer::new(
+            self,
+            Seq::Variant(variant_name),
+            Some(len),
+        ))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapSerializer, Error> {
+        Ok(MapSerializer {
+            ser: self,
+            entries: Vec::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<StructSerializer, Error> {
+        Ok(StructSerializer {
+            ser: self,
+            rec: Record::new(),
+            variant: None,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant_name: &'static str,
+        _len: usize,
+    ) -> Result<StructSerializer, Error> {
+        Ok(StructSerializer {
+            ser: self,
+            rec: Record::new(),
+            variant: Some(variant_name),
+        })
+    }
+}
+
+/// Fixes up the records of enums with serde's `tag` and `content` attributes, whose
+/// tag serde serializes as a unit variant, and which have no content when the
+/// variant is unit.
+fn tagged(mut rec: Record) -> Value {
+    let is_tagged =
+        rec.contains_key("tag") && rec.iter().all(|(key, _)| key == "tag" || key == "value");
+    if !is_tagged {
+        return Value::Record(rec);
+    }
+    if let Some(Value::Record(tag)) = rec.get("tag")
+        && tag.get("value") == Some(&unit())
+        && let Some(Value::String(name)) = tag.get("tag")
+    {
+        let name = name.clone();
+        rec.insert("tag".to_string(), Value::String(name));
+    }
+    if matches!(rec.get("tag"), Some(Value::String(_))) && !rec.contains_key("value") {
+        rec.insert("value".to_string(), unit());
+    }
+    Value::Record(rec)
+}
+
+/// The keys of the objects standing for the values that JSON lacks in the ITF
+/// format, e.g. `{ "#set": [1, 2] }`.
+const ITF_KEYS: &[&str] = &["#set", "#tup", "#map", "#bigint"];
+
+/// Decodes an object of the ITF format with one of [`ITF_KEYS`].
+fn itf_object(key: &str, value: Value) -> Result<Value, Error> {
+    Ok(match (key, value) {
+        ("#set", Value::List(elems)) => Value::Set(elems.into_iter().collect()),
+        ("#tup", Value::List(elems)) => Value::Tuple(elems.into()),
+        ("#map", Value::List(pairs)) => {
+            let mut map = Map::new();
+            for pair in pairs {
+                let elems: Vec<_> = match pair {
+                    Value::List(elems) => elems,
+                    Value::Tuple(elems) => elems.into_iter().collect(),
+                    other => vec![other],
+                };
+                let Ok([key, value]) = <[Value; 2]>::try_from(elems) else {
+                    return Err(Error("Expected `#map` entries to be pairs".to_string()));
+                };
+                map.insert(key, value);
+            }
+            Value::Map(map)
+        }
+        ("#bigint", Value::String(digits)) => {
+            let n = match digits.parse::<i128>() {
+                Ok(n) => BigInt::new(n),
+                Err(_) => BigInt::new(
+                    digits
+                        .parse::<u128>()
+                        .map_err(|_| Error(format!("Invalid big integer `{}`", digits)))?,
+                ),
+            };
+            Value::BigInt(n)
+        }
+        (key, value) => {
+            return Err(Error(format!(
+                "Invalid `{}` object: {}",
+                key,
+                value.display()
+            )));
+        }
+    })
+}
+
+/// The Quint value a sequence of Rust values is serialized into.
+enum Seq {
+    List,
+    Tuple,
+    /// A sum type variant whose value is a tuple.
+    Variant(&'static str),
+}
+
+struct SeqSerializer {
+    ser: Serializer,
+    seq: Seq,
+    elems: Vec<Value>,
+}
+
+impl SeqSerializer {
+    fn new(ser: Serializer, seq: Seq, len: Option<usize>) -> Self {
+        Self {
+            ser,
+            seq,
+            elems: Vec::with_capacity(len.unwrap_or_default()),
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.elems.push(value.serialize(self.ser)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value, Error> {
+        Ok(match self.seq {
+            Seq::List => Value::List(self.elems),
+            Seq::Tuple => Value::Tuple(self.elems.into()),
+            Seq::Variant(tag) => variant(tag, Value::Tuple(self.elems.into())),
+        })
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+struct MapSerializer {
+    ser: Serializer,
+    entries: Vec<(Value, Value)>,
+    key: Option<Value>,
+}
+
+impl ser::SerializeMap for MapSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.key = Some(key.serialize(self.ser)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| Error("Map value serialized before its key".to_string()))?;
+        self.entries.push((key, value.serialize(self.ser)?));
+        Ok(())
+    }
+
+    fn end(mut self) -> Result<Value, Error> {
+        if self.ser.itf
+            && let [(Value::String(key), _)] = &self.entries[..]
+            && ITF_KEYS.contains(&key.as_str())
+        {
+            let key = key.clone();
+            let (_, value) = self.entries.remove(0);
+            return itf_object(&key, value);
+        }
+        let all_strings = self
+            .entries
+            .iter()
+            .all(|(key, _)| matches!(key, Value::String(_)));
+        if self.ser.itf && all_strings {
+            let mut rec = Record::new();
+            for (key, value) in self.entries {
+                if let Value::String(key) = key {
+                    rec.insert(key, value);
+                }
+            }
+            return Ok(Value::Record(rec));
+        }
+        Ok(Value::Map(self.entries.into_iter().collect()))
+    }
+}
+
+struct StructSerializer {
+    ser: Serializer,
+    rec: Record,
+    variant: Option<&'static str>,
+}
+
+impl StructSerializer {
+    fn insert<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), Error> {
+        self.rec.insert(key.to_string(), value.serialize(self.ser)?);
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<Value, Error> {
+        if let Some(tag) = self.variant {
+            return Ok(variant(tag, Value::Record(self.rec)));
+        }
+        // Big integers serialize as structs.
+        if self.ser.itf
+            && self.rec.len() == 1
+            && let Some(value) = self.rec.remove("#bigint")
+        {
+            return itf_object("#bigint", value);
+        }
+        Ok(tagged(self.rec))
+    }
+}
+
+impl ser::SerializeStruct for StructSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for StructSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    #[serde(tag = "tag", content = "value")]
+    enum Square {
+        Occupied(String),
+        Empty,
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    #[serde(rename_all = "camelCase")]
+    struct Game {
+        board: BTreeMap<(u8, u8), Square>,
+        next_turn: String,
+        moves: Vec<(u8, u8)>,
+        total: u128,
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let game = Game {
+            board: BTreeMap::from([
+                ((1, 1), Square::Occupied("X".to_string())),
+                ((1, 2), Square::Empty),
+            ]),
+            next_turn: "O".to_string(),
+            moves: vec![(1, 1)],
+            total: u128::MAX,
+        };
+        let value = to_value(&game).unwrap();
+
+        let Value::Record(rec) = &value else {
+            panic!("expected a record, got {:?}", value)
+        };
+        assert!(matches!(rec.get("board"), Some(Value::Map(_))));
+        assert!(matches!(rec.get("total"), Some(Value::BigInt(_))));
+
+        let decoded = Game::deserialize(value).unwrap();
+        assert_eq!(decoded, game);
+    }
+
+    #[test]
+    fn test_sum_types() {
+        #[derive(Serialize)]
+        enum Msg {
+            Ping,
+            Vote { round: u64 },
+            Pair(u64, bool),
+        }
+
+        assert_eq!(to_value(&Msg::Ping).unwrap(), variant("Ping", unit()));
+
+        let mut vote = Record::new();
+        vote.insert("round".to_string(), Value::Number(3));
+        assert_eq!(
+            to_value(&Msg::Vote { round: 3 }).unwrap(),
+            variant("Vote", Value::Record(vote))
+        );
+
+        let pair = Value::Tuple(vec![Value::Number(1), Value::Bool(true)].into());
+        assert_eq!(
+            to_value(&Msg::Pair(1, true)).unwrap(),
+            variant("Pair", pair)
+        );
+        assert_eq!(to_value(&None::<u64>).unwrap(), variant("None", unit()));
+        assert_eq!(to_value(&Square::Empty).unwrap(), variant("Empty", unit()));
+        assert_eq!(
+            to_value(&Some(1)).unwrap(),
+            variant("Some", Value::Number(1))
+        );
+    }
+
+    #[test]
+    fn test_itf_values_are_kept() {
+        let mut rec = Record::new();
+        rec.insert(
+            "set".to_string(),
+            Value::Set([Value::Number(1)].into_iter().collect()),
+        );
+        rec.insert("empty".to_string(), Value::Record(Record::new()));
+        let key = Value::Tuple(vec![Value::Number(1), Value::String("a".to_string())].into());
+        let big = Value::BigInt(BigInt::new(u128::MAX));
+        let map = Value::Map([(key, big)].into_iter().collect());
+        rec.insert("map".to_string(), map);
+        let value = Value::Record(rec);
+
+        assert_eq!(to_value(&Itf(&value)).unwrap(), value);
+        let json = serde_json::to_value(Itf(&value)).unwrap();
+        assert_eq!(serde_json::from_value::<Value>(json).unwrap(), value);
+    }
+
+    #[test]
+    fn test_floats_are_rejected() {
+        let err = to_value(&1.5).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Can not serialize floating-point number 1.5 into a Quint value"
+        );
+    }
+}
@@ -0,0 +1,230 @@
+//! Replays ITF traces of the example specs against drivers decoding Quint values
+//! with plain serde types and the adapters of `quint_connect::encoding`.
+//!
+//! The traces in `fixtures/` were written by hand in the format of Quint's traces,
+//! following the moves the specs allow, e.g. X setting up a win with a `pattern`
+//! once O took the center. Their metadata only names the spec. To replace them
+//! with traces recorded by Quint, run from the `connect` directory:
+//!
+//! ```bash
+//! quint run --mbt --max-samples=1 --max-steps=3 --seed=0x2a \
+//!     --out-itf=tests/fixtures/tictactoe.itf.json examples/tictactoe/spec/tictactoe.qnt
+//! quint run --mbt --max-samples=1 --max-steps=2 --seed=0x2a \
+//!     --out-itf=tests/fixtures/two_phase_commit.itf.json \
+//!     examples/two_phase_commit/spec/two_phase_commit.qnt
+//! ```
+
+use itf::{Trace, Value};
+use quint_connect::encoding::{BigInt, Map, Quint, Same, Set, Sum, Tuple, sum};
+use quint_connect::*;
+use serde::Deserialize;
+use serde_with::As;
+use std::collections::{BTreeMap, BTreeSet};
+
+fn load(json: &str) -> Trace<Value> {
+    serde_json::from_str(json).unwrap()
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+enum Player {
+    X,
+    O,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+enum Square {
+    Occupied(#[serde(with = "sum")] Player),
+    Empty,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Game {
+    #[serde(with = "As::<Map<BigInt, Map<BigInt, Sum>>>")]
+    board: BTreeMap<u8, BTreeMap<u8, Square>>,
+    #[serde(with = "sum")]
+    next_turn: Player,
+}
+
+struct TicTacToeDriver {
+    board: BTreeMap<u8, BTreeMap<u8, Square>>,
+    next_turn: Player,
+}
+
+impl State<TicTacToeDriver> for Game {
+    fn from_driver(driver: &TicTacToeDriver) -> Result<Self> {
+        Ok(Self {
+            board: driver.board.clone(),
+            next_turn: driver.next_turn,
+        })
+    }
+}
+
+impl TicTacToeDriver {
+    fn new() -> Self {
+        Self {
+            board: BTreeMap::new(),
+            next_turn: Player::X,
+        }
+    }
+
+    fn play(&mut self, (x, y): (u8, u8)) {
+        self.board
+            .get_mut(&x)
+            .unwrap()
+            .insert(y, Square::Occupied(self.next_turn));
+        self.next_turn = match self.next_turn {
+            Player::X => Player::O,
+            Player::O => Player::X,
+        };
+    }
+}
+
+impl Driver for TicTacToeDriver {
+    type State = Game;
+
+    fn step(&mut self, step: &Step) -> Result {
+        match step.action() {
+            "init" => {
+                let row = (1..=3)
+                    .map(|y| (y, Square::Empty))
+                    .collect::<BTreeMap<_, _>>();
+                self.board = (1..=3).map(|x| (x, row.clone())).collect();
+                self.next_turn = Player::X;
+            }
+            _ => {
+                let pos = match step.try_pick::<Quint<(u8, u8), Tuple>>("corner")? {
+                    Some(corner) => corner.into_inner(),
+                    None => step.pick::<(u8, u8)>("coordinate")?,
+                };
+                // Winning patterns are sets of coordinates.
+                if let Some(pattern) =
+                    step.try_pick::<Quint<BTreeSet<(u8, u8)>, Set<Tuple>>>("pattern")?
+                {
+                    anyhow::ensure!(pattern.contains(&pos), "{:?} is not in {:?}", pos, pattern);
+                }
+                self.play(pos);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_tictactoe_trace() {
+    let trace = load(include_str!("fixtures/tictactoe.itf.json"));
+    replay(TicTacToeDriver::new(), trace).unwrap();
+}
+
+type Node = String;
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+enum Stage {
+    Working,
+    Prepared,
+    Committed,
+    Aborted,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum Message {
+    CoordinatorAbort,
+    CoordinatorCommit,
+    ParticipantPrepared(Node),
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+enum ActionTaken {
+    Init,
+    SpontaneouslyPrepares { node: Node },
+    DecidesOnAbort { node: Node },
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+struct LocalState {
+    #[serde(with = "sum")]
+    stage: Stage,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Extensions {
+    #[serde(with = "sum")]
+    action_taken: ActionTaken,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+struct GlobalContext {
+    system: BTreeMap<Node, LocalState>,
+    #[serde(with = "As::<Map<Same, Set<Sum>>>")]
+    messages: BTreeMap<Node, BTreeSet<Message>>,
+    extensions: Extensions,
+}
+
+#[derive(Default)]
+struct TwoPhaseCommitDriver {
+    system: BTreeMap<Node, LocalState>,
+    messages: BTreeMap<Node, BTreeSet<Message>>,
+    actions: Vec<ActionTaken>,
+}
+
+impl State<TwoPhaseCommitDriver> for GlobalContext {
+    fn from_driver(driver: &TwoPhaseCommitDriver) -> Result<Self> {
+        Ok(Self {
+            system: driver.system.clone(),
+            messages: driver.messages.clone(),
+            extensions: Extensions {
+                action_taken: driver.actions.last().unwrap().clone(),
+            },
+        })
+    }
+}
+
+impl Driver for TwoPhaseCommitDriver {
+    type State = GlobalContext;
+
+    fn config() -> Config {
+        Config {
+            state: &["two_phase_commit::choreo::s"],
+            nondet: &["two_phase_commit::choreo::s", "extensions", "actionTaken"],
+        }
+    }
+
+    fn step(&mut self, step: &Step) -> Result {
+        let action = match step.action() {
+            "Init" => {
+                for node in ["c", "p1", "p2", "p3"] {
+                    let stage = LocalState {
+                        stage: Stage::Working,
+                    };
+                    self.system.insert(node.to_string(), stage);
+                    self.messages.insert(node.to_string(), BTreeSet::new());
+                }
+                ActionTaken::Init
+            }
+            "SpontaneouslyPrepares" => {
+                let node: Node = step.pick("node")?;
+                self.system.get_mut(&node).unwrap().stage = Stage::Prepared;
+                let msg = Message::ParticipantPrepared(node.clone());
+                self.messages.get_mut(&node).unwrap().insert(msg);
+                ActionTaken::SpontaneouslyPrepares { node }
+            }
+            "DecidesOnAbort" => {
+                let node: Node = step.pick("node")?;
+                self.system.get_mut(&node).unwrap().stage = Stage::Aborted;
+                let msg = Message::CoordinatorAbort;
+                self.messages.get_mut(&node).unwrap().insert(msg);
+                ActionTaken::DecidesOnAbort { node }
+            }
+            other => anyhow::bail!("Unexpected action `{}`", other),
+        };
+        self.actions.push(action);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_two_phase_commit_trace() {
+    let trace = load(include_str!("fixtures/two_phase_commit.itf.json"));
+    replay(TwoPhaseCommitDriver::default(), trace).unwrap();
+}
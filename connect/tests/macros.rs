@@ -1,3 +1,9 @@
+//! Spec paths in the fixtures are relative to the fixture files, since trybuild
+//! compiles them from a crate of its own.
+
+use quint_connect_spec::config::quint_bin;
+use std::{path::Path, process::Command};
+
 #[test]
 fn test_macros() {
     let t = trybuild::TestCases::new();
@@ -18,3 +24,20 @@ fn test_macros_nightly() {
     let t = trybuild::TestCases::new();
     t.compile_fail("tests/macros/*/fail/nightly/*.rs");
 }
+
+/// Checks that depend on the spec as compiled by Quint, which the macros skip
+/// where Quint isn't installed.
+#[test]
+fn test_macros_with_quint() {
+    let quint = quint_bin(Path::new(env!("CARGO_MANIFEST_DIR"))).unwrap();
+    if !Command::new(quint)
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success())
+    {
+        eprintln!("Quint is not installed, skipping");
+        return;
+    }
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/macros/*/fail/quint/*.rs");
+}
@@ -0,0 +1,8 @@
+use quint_connect::*;
+
+#[derive(QuintAction)]
+struct Move {
+    position: u64,
+}
+
+fn main() {}
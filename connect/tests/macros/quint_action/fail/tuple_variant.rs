@@ -0,0 +1,9 @@
+use quint_connect::*;
+
+#[derive(QuintAction)]
+enum Action {
+    Init,
+    Move(u64, u64),
+}
+
+fn main() {}
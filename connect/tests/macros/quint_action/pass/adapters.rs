@@ -0,0 +1,39 @@
+use quint_connect::encoding::{BigInt, Sum};
+use quint_connect::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+enum Asset {
+    Coin,
+    Token { id: u64 },
+}
+
+#[derive(QuintAction, Clone, PartialEq, Debug)]
+enum Action {
+    Transfer {
+        #[quint(as = "BigInt")]
+        amount: u128,
+        #[quint(as = "Sum")]
+        asset: Asset,
+        #[quint(rename = "fee", as = "BigInt")]
+        max_fee: Option<u128>,
+    },
+}
+
+fn main() {
+    let step = Step::builder("Transfer")
+        .pick("amount", u128::MAX)
+        .pick_itf("asset", serde_json::json!({"tag": "Token", "value": {"id": 7}}))
+        .pick("fee", None::<u64>)
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        step.decode_action::<Action>().unwrap(),
+        Action::Transfer {
+            amount: u128::MAX,
+            asset: Asset::Token { id: 7 },
+            max_fee: None,
+        }
+    );
+}
@@ -0,0 +1,62 @@
+use quint_connect::*;
+use std::collections::BTreeMap;
+
+type Position = (u64, u64);
+
+#[derive(QuintAction, Clone, PartialEq, Debug)]
+enum Action {
+    Init,
+    MoveX {
+        corner: Option<Position>,
+    },
+    MoveO {
+        coordinate: Position,
+    },
+    Chat {
+        #[quint(rename = "msg")]
+        message: String,
+        reactions: BTreeMap<String, Option<u64>>,
+    },
+    #[quint(rename = "stuttered")]
+    Stutter,
+}
+
+#[derive(Default)]
+struct Game {
+    moves: Vec<Position>,
+}
+
+impl StateMachine for Game {
+    type State = ();
+    type Action = Action;
+
+    fn apply(&mut self, action: Action) -> Result {
+        match action {
+            Action::Init => self.moves.clear(),
+            Action::MoveX { corner } => self.moves.push(corner.unwrap_or((2, 2))),
+            Action::MoveO { coordinate } => self.moves.push(coordinate),
+            Action::Chat { .. } | Action::Stutter => {}
+        }
+        Ok(())
+    }
+}
+
+struct ManualDriver;
+
+impl Driver for ManualDriver {
+    type State = ();
+
+    fn step(&mut self, step: &Step) -> Result {
+        match step.decode_action::<Action>()? {
+            Action::Stutter => Ok(()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[quint_run(spec = "../../specs/spec.qnt")]
+fn test_game() -> impl Driver {
+    Game::default()
+}
+
+fn main() {}
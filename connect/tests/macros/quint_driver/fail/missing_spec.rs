@@ -0,0 +1,22 @@
+use quint_connect::*;
+
+struct TestDriver;
+
+impl TestDriver {
+    fn init(&mut self) {}
+    fn deposit(&mut self, _amount: i64) {}
+    fn withdraw(&mut self, _amount: i64, _fee: Option<i64>) {}
+}
+
+#[quint_driver]
+impl Driver for TestDriver {
+    type State = ();
+
+    fn step(&mut self, step: &Step) -> Result {
+        switch!(step {
+            init,
+        })
+    }
+}
+
+fn main() {}
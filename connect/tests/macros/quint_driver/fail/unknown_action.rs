@@ -0,0 +1,26 @@
+use quint_connect::*;
+
+struct TestDriver;
+
+impl TestDriver {
+    fn init(&mut self) {}
+    fn deposit(&mut self, _amount: i64) {}
+    fn withdraw(&mut self, _amount: i64, _fee: Option<i64>) {}
+}
+
+#[quint_driver(spec = "../../specs/bank.qnt")]
+impl Driver for TestDriver {
+    type State = ();
+
+    fn step(&mut self, step: &Step) -> Result {
+        switch!(step {
+            init,
+            Deposti(amount) => self.deposit(amount),
+            Withdraw(amount, fee?) => self.withdraw(amount, fee),
+            Refund => {},
+            _ => {}
+        })
+    }
+}
+
+fn main() {}
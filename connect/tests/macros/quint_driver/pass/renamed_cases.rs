@@ -0,0 +1,26 @@
+use quint_connect::*;
+
+struct TestDriver;
+
+impl TestDriver {
+    fn init(&mut self) {}
+    fn deposit(&mut self, _amount: i64) {}
+    fn withdraw(&mut self, _amount: i64, _fee: Option<i64>) {}
+    fn transfer_funds(&mut self, _to_account: String) {}
+}
+
+#[quint_driver(spec = "../../specs/bank.qnt")]
+impl Driver for TestDriver {
+    type State = ();
+
+    fn step(&mut self, step: &Step) -> Result {
+        switch!(step, rename = snake_case {
+            init,
+            Deposit(amount),
+            Withdraw(amount, fee?) | Audit(amount, fee?) => self.withdraw(amount, fee),
+            TransferFunds(to_account),
+        })
+    }
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use quint_connect::*;
+
+struct TestDriver;
+
+impl Driver for TestDriver {
+    type State = ();
+
+    fn step(&mut self, _step: &Step) -> Result {
+        todo!()
+    }
+}
+
+#[quint_run(spec = "../../specs/spec.qnt", matrix(max_steps = []))]
+fn test1() -> impl Driver {
+    TestDriver
+}
+
+fn main() {}
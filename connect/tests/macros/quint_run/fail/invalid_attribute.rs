@@ -10,7 +10,7 @@ impl Driver for TestDriver {
     }
 }
 
-#[quint_run(spec = "spec.qnt", unknown_attr = "value")]
+#[quint_run(spec = "../../specs/spec.qnt", unknown_attr = "value")]
 fn test1() -> impl Driver {
     TestDriver
 }
@@ -10,7 +10,7 @@ impl Driver for TestDriver {
     }
 }
 
-#[quint_run(spec = "spec.qnt", main = 666)]
+#[quint_run(spec = "../../specs/spec.qnt", main = 666)]
 fn test1() -> impl Driver {
     TestDriver
 }
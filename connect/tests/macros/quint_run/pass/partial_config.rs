@@ -10,12 +10,12 @@ impl Driver for TestDriver {
     }
 }
 
-#[quint_run(spec = "spec.qnt", main = "main", max_samples = 10)]
+#[quint_run(spec = "../../specs/spec.qnt", main = "main", max_samples = 10)]
 fn test1() -> impl Driver {
     TestDriver
 }
 
-#[quint_run(spec = "spec.qnt", init = "init", step = "step", seed = "0xabc")]
+#[quint_run(spec = "../../specs/spec.qnt", init = "init", step = "step", seed = "0xabc")]
 fn test2() -> impl Driver {
     TestDriver
 }
@@ -10,13 +10,13 @@ impl Driver for TestDriver {
     }
 }
 
-#[quint_run(spec = "spec.qnt")]
+#[quint_run(spec = "../../specs/spec.qnt")]
 fn test1() -> impl Driver {
     TestDriver
 }
 
 #[quint_run(
-    spec = "spec.qnt",
+    spec = "../../specs/spec.qnt",
     main = "main",
     init = "init",
     step = "step",
@@ -28,13 +28,13 @@ fn test2() -> impl Driver {
     TestDriver
 }
 
-#[quint_run(spec = "spec.qnt")]
+#[quint_run(spec = "../../specs/spec.qnt")]
 #[ignore]
 fn test3() -> impl Driver {
     TestDriver
 }
 
-#[quint_run(spec = "spec.qnt")]
+#[quint_run(spec = "../../specs/spec.qnt")]
 #[should_panic = "some error"]
 fn test4() -> impl Driver {
     TestDriver
@@ -10,7 +10,7 @@ impl Driver for TestDriver {
     }
 }
 
-#[quint_run(spec = "spec.qnt", init = "init")]
+#[quint_run(spec = "../../specs/spec.qnt", init = "init")]
 fn test1() -> impl Driver {
     TestDriver
 }
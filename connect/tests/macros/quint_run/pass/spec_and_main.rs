@@ -10,7 +10,7 @@ impl Driver for TestDriver {
     }
 }
 
-#[quint_run(spec = "spec.qnt", main = "main")]
+#[quint_run(spec = "../../specs/spec.qnt", main = "main")]
 fn test1() -> impl Driver {
     TestDriver
 }
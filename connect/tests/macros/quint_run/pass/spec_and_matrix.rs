@@ -0,0 +1,32 @@
+use quint_connect::*;
+
+struct TestDriver;
+
+impl Driver for TestDriver {
+    type State = ();
+
+    fn step(&mut self, _step: &Step) -> Result {
+        todo!()
+    }
+}
+
+#[quint_run(
+    spec = "../../specs/spec.qnt",
+    max_samples = 10,
+    matrix(
+        max_steps = [5, 20, 100],
+        main = ["small", "large"],
+        consts = [(N = 3), (N = 5, NODES = Set("a", "b"))]
+    )
+)]
+fn test1() -> impl Driver {
+    TestDriver
+}
+
+#[test]
+fn cases_exist() {
+    let _ = test1::max_steps_5_main_small_consts_0;
+    let _ = test1::max_steps_100_main_large_consts_1;
+}
+
+fn main() {}
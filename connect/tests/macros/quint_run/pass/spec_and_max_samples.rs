@@ -10,7 +10,7 @@ impl Driver for TestDriver {
     }
 }
 
-#[quint_run(spec = "spec.qnt", max_samples = 100)]
+#[quint_run(spec = "../../specs/spec.qnt", max_samples = 100)]
 fn test1() -> impl Driver {
     TestDriver
 }
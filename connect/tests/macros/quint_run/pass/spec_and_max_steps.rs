@@ -10,7 +10,7 @@ impl Driver for TestDriver {
     }
 }
 
-#[quint_run(spec = "spec.qnt", max_steps = 50)]
+#[quint_run(spec = "../../specs/spec.qnt", max_steps = 50)]
 fn test1() -> impl Driver {
     TestDriver
 }
@@ -10,7 +10,7 @@ impl Driver for TestDriver {
     }
 }
 
-#[quint_run(spec = "spec.qnt", seed = "0x123abc")]
+#[quint_run(spec = "../../specs/spec.qnt", seed = "0x123abc")]
 fn test1() -> impl Driver {
     TestDriver
 }
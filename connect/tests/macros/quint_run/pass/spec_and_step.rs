@@ -10,7 +10,7 @@ impl Driver for TestDriver {
     }
 }
 
-#[quint_run(spec = "spec.qnt", step = "step")]
+#[quint_run(spec = "../../specs/spec.qnt", step = "step")]
 fn test1() -> impl Driver {
     TestDriver
 }
@@ -0,0 +1,8 @@
+use quint_connect::*;
+
+#[derive(QuintState)]
+struct Wrapper<'a> {
+    value: &'a str,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use quint_connect::*;
+
+#[derive(QuintState)]
+struct TestState {
+    #[quint(name = "total")]
+    count: u64,
+}
+
+fn main() {}
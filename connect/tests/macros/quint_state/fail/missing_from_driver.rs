@@ -0,0 +1,11 @@
+use quint_connect::*;
+
+struct TestDriver;
+
+#[derive(QuintState)]
+#[quint(driver = TestDriver)]
+struct TestState {
+    count: u64,
+}
+
+fn main() {}
@@ -0,0 +1,60 @@
+use quint_connect::encoding::{BigInt, Map, Same, Sum};
+use quint_connect::*;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+#[derive(Deserialize, PartialEq, Debug)]
+enum Role {
+    Admin,
+    User,
+}
+
+#[derive(QuintState, PartialEq, Debug)]
+enum Event {
+    Deposit(#[quint(as = "BigInt")] u128),
+    Promote {
+        #[quint(as = "Sum")]
+        role: Role,
+    },
+}
+
+#[derive(QuintState, PartialEq, Debug)]
+struct Bank {
+    #[quint(as = "Map<Same, Sum>")]
+    roles: BTreeMap<String, Role>,
+    #[quint(rename = "lastEvent", as = "quint_connect::encoding::QuintOption<Same>")]
+    event: Option<Event>,
+}
+
+fn main() {
+    let state = json!({
+        "roles": {"#map": [
+            ["alice", {"tag": "Admin", "value": {"#tup": []}}],
+            ["bob", {"tag": "User", "value": {"#tup": []}}]
+        ]},
+        "lastEvent": {"tag": "Some", "value": {
+            "tag": "Deposit",
+            "value": {"#bigint": "340282366920938463463374607431768211455"}
+        }}
+    });
+
+    let bank: Bank = itf::from_value(state).unwrap();
+    assert_eq!(
+        bank,
+        Bank {
+            roles: BTreeMap::from([
+                ("alice".to_string(), Role::Admin),
+                ("bob".to_string(), Role::User),
+            ]),
+            event: Some(Event::Deposit(u128::MAX)),
+        }
+    );
+
+    let event = json!({
+        "tag": "Promote",
+        "value": {"role": {"tag": "Admin", "value": {"#tup": []}}}
+    });
+    let event: Event = itf::from_value(event).unwrap();
+    assert_eq!(event, Event::Promote { role: Role::Admin });
+}
@@ -0,0 +1,90 @@
+use quint_connect::*;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(QuintState, PartialEq, Debug)]
+enum Player {
+    X,
+    O,
+}
+
+#[derive(QuintState, PartialEq, Debug)]
+enum Square {
+    Occupied(Player),
+    Empty,
+}
+
+#[derive(QuintState, PartialEq, Debug)]
+enum Message {
+    Move((u64, u64)),
+    #[quint(rename = "Resign")]
+    GiveUp,
+    Chat { from_player: Player, text: Option<String> },
+}
+
+#[derive(QuintState, PartialEq, Debug)]
+struct GameState {
+    board: BTreeMap<u64, BTreeMap<u64, Square>>,
+    next_turn: Player,
+    winner: Option<Player>,
+    moves: Vec<Option<(u64, u64)>>,
+    players: BTreeSet<String>,
+    messages: Vec<Message>,
+    total_score: i128,
+    #[quint(rename = "round")]
+    current_round: u64,
+    r#type: String,
+}
+
+fn main() {
+    let state = json!({
+        "board": {"#map": [[1, {"#map": [
+            [1, {"tag": "Occupied", "value": {"tag": "X", "value": {"#tup": []}}}],
+            [2, {"tag": "Empty", "value": {"#tup": []}}]
+        ]}]]},
+        "nextTurn": {"tag": "O", "value": {"#tup": []}},
+        "winner": {"tag": "None", "value": {"#tup": []}},
+        "moves": [
+            {"tag": "Some", "value": {"#tup": [1, 1]}},
+            {"tag": "None", "value": {"#tup": []}}
+        ],
+        "players": {"#set": ["alice", "bob"]},
+        "messages": [
+            {"tag": "Move", "value": {"#tup": [{"#bigint": "1"}, 2]}},
+            {"tag": "Resign", "value": {"#tup": []}},
+            {"tag": "Chat", "value": {
+                "fromPlayer": {"tag": "X", "value": {"#tup": []}},
+                "text": {"tag": "Some", "value": "gg"}
+            }}
+        ],
+        "totalScore": {"#bigint": "170141183460469231731687303715884105727"},
+        "round": 3,
+        "type": "classic"
+    });
+
+    let state: GameState = itf::from_value(state).unwrap();
+    assert_eq!(
+        state,
+        GameState {
+            board: BTreeMap::from([(
+                1,
+                BTreeMap::from([(1, Square::Occupied(Player::X)), (2, Square::Empty)])
+            )]),
+            next_turn: Player::O,
+            winner: None,
+            moves: vec![Some((1, 1)), None],
+            players: BTreeSet::from(["alice".to_string(), "bob".to_string()]),
+            messages: vec![
+                Message::Move((1, 2)),
+                Message::GiveUp,
+                Message::Chat {
+                    from_player: Player::X,
+                    text: Some("gg".to_string())
+                },
+            ],
+            total_score: i128::MAX,
+            current_round: 3,
+            r#type: "classic".to_string(),
+        }
+    );
+}
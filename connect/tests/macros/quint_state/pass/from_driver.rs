@@ -0,0 +1,56 @@
+use quint_connect::*;
+
+#[derive(Default)]
+struct CounterDriver {
+    count: u64,
+    last: Option<u64>,
+}
+
+#[derive(QuintState, PartialEq, Debug)]
+#[quint(driver = CounterDriver, from_driver = |driver| Ok(Self {
+    count: driver.count,
+    last_added: driver.last,
+}))]
+struct CounterState {
+    count: u64,
+    last_added: Option<u64>,
+}
+
+#[derive(QuintState, PartialEq, Debug)]
+#[quint(driver = CounterDriver, from_driver = Total::project)]
+struct Total(u64);
+
+impl Total {
+    fn project(driver: &CounterDriver) -> Result<Self> {
+        Ok(Self(driver.count))
+    }
+}
+
+impl Driver for CounterDriver {
+    type State = CounterState;
+
+    fn step(&mut self, step: &Step) -> Result {
+        switch!(step {
+            init => self.count = 0,
+            add(amount: u64) => {
+                self.count += amount;
+                self.last = Some(amount);
+            }
+        })
+    }
+}
+
+fn main() {
+    let driver = CounterDriver {
+        count: 3,
+        last: Some(2),
+    };
+    assert_eq!(
+        <CounterState as State<_>>::from_driver(&driver).unwrap(),
+        CounterState {
+            count: 3,
+            last_added: Some(2)
+        }
+    );
+    assert_eq!(Total::from_driver(&driver).unwrap(), Total(3));
+}
@@ -0,0 +1,48 @@
+// Types as generated by `quint_connect::build::generate_types`.
+
+pub type Position = (i64, i64);
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, quint_connect::QuintState)]
+pub enum Player {
+    X,
+    O,
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, quint_connect::QuintState)]
+pub enum Outcome<A> {
+    Won(A),
+    #[quint(rename = "draw")]
+    Draw,
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, quint_connect::QuintState)]
+pub struct Scores {
+    pub wins: i64,
+    pub r#type: ::std::string::String,
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, quint_connect::QuintState)]
+pub struct SpecState {
+    pub board: ::std::collections::BTreeMap<Position, Player>,
+    pub next_turn: Player,
+    pub winner: ::std::option::Option<Player>,
+    pub outcome: ::std::option::Option<Outcome<Player>>,
+    pub scores: ::std::collections::BTreeMap<::std::string::String, Scores>,
+    #[quint(rename = "HTTPPort")]
+    pub http_port: i64,
+}
+
+fn main() {
+    let state = serde_json::json!({
+        "board": {"#map": [[{"#tup": [1, 1]}, {"tag": "X", "value": {"#tup": []}}]]},
+        "nextTurn": {"tag": "O", "value": {"#tup": []}},
+        "winner": {"tag": "None", "value": {"#tup": []}},
+        "outcome": {"tag": "Some", "value": {"tag": "draw", "value": {"#tup": []}}},
+        "scores": {"#map": [["alice", {"wins": 1, "type": "ranked"}]]},
+        "HTTPPort": 8080
+    });
+    let state: SpecState = itf::from_value(state).unwrap();
+    assert_eq!(state.outcome, Some(Outcome::Draw));
+    assert_eq!(state.scores["alice"].r#type, "ranked");
+    assert_eq!(state.http_port, 8080);
+}
@@ -0,0 +1,38 @@
+use quint_connect::*;
+use serde_json::json;
+
+#[derive(QuintState, PartialEq, Debug)]
+enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+#[derive(QuintState, PartialEq, Debug)]
+struct Entry<T>
+where
+    T: Clone,
+{
+    value: T,
+    previous_value: Option<T>,
+    history: Vec<Either<T, String>>,
+}
+
+fn main() {
+    let entry = json!({
+        "value": 2,
+        "previousValue": {"tag": "Some", "value": 1},
+        "history": [
+            {"tag": "Left", "value": 1},
+            {"tag": "Right", "value": "reset"}
+        ]
+    });
+    let entry: Entry<u64> = itf::from_value(entry).unwrap();
+    assert_eq!(
+        entry,
+        Entry {
+            value: 2,
+            previous_value: Some(1),
+            history: vec![Either::Left(1), Either::Right("reset".to_string())],
+        }
+    );
+}
@@ -10,7 +10,7 @@ impl Driver for TestDriver {
     }
 }
 
-#[quint_test(spec = "spec.qnt", unknown_attr = "value")]
+#[quint_test(spec = "../../specs/spec.qnt", unknown_attr = "value")]
 fn test1() -> impl Driver {
     TestDriver
 }
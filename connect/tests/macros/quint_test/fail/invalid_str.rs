@@ -10,7 +10,7 @@ impl Driver for TestDriver {
     }
 }
 
-#[quint_test(spec = "spec.qnt", main = 666)]
+#[quint_test(spec = "../../specs/spec.qnt", main = 666)]
 fn test1() -> impl Driver {
     TestDriver
 }
@@ -10,7 +10,7 @@ impl Driver for TestDriver {
     }
 }
 
-#[quint_test(spec = "spec.qnt")]
+#[quint_test(spec = "../../specs/spec.qnt")]
 fn test1() -> impl Driver {
     TestDriver
 }
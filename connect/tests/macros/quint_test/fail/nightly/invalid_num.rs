@@ -10,12 +10,12 @@ impl Driver for TestDriver {
     }
 }
 
-#[quint_test(spec = "spec.qnt", max_samples = -666)]
+#[quint_test(spec = "../../../specs/spec.qnt", max_samples = -666)]
 fn test1() -> impl Driver {
     TestDriver
 }
 
-#[quint_test(spec = "spec.qnt", max_samples = "666")]
+#[quint_test(spec = "../../../specs/spec.qnt", max_samples = "666")]
 fn test1() -> impl Driver {
     TestDriver
 }
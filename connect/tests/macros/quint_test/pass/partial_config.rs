@@ -10,12 +10,12 @@ impl Driver for TestDriver {
     }
 }
 
-#[quint_test(spec = "spec.qnt", test = "test1", main = "main", max_samples = 10)]
+#[quint_test(spec = "../../specs/spec.qnt", test = "test1", main = "main", max_samples = 10)]
 fn test1() -> impl Driver {
     TestDriver
 }
 
-#[quint_test(spec = "spec.qnt", test = "test2", main = "main", seed = "0xabc")]
+#[quint_test(spec = "../../specs/spec.qnt", test = "test2", main = "main", seed = "0xabc")]
 fn test2() -> impl Driver {
     TestDriver
 }
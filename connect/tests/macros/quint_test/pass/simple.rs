@@ -10,13 +10,13 @@ impl Driver for TestDriver {
     }
 }
 
-#[quint_test(spec = "spec.qnt", test = "test1Test")]
+#[quint_test(spec = "../../specs/spec.qnt", test = "test1Test")]
 fn test1() -> impl Driver {
     TestDriver
 }
 
 #[quint_test(
-    spec = "spec.qnt",
+    spec = "../../specs/spec.qnt",
     main = "main",
     test = "test2Test",
     max_samples = 32,
@@ -26,13 +26,13 @@ fn test2() -> impl Driver {
     TestDriver
 }
 
-#[quint_test(spec = "spec.qnt", test = "test1Test")]
+#[quint_test(spec = "../../specs/spec.qnt", test = "test1Test")]
 #[ignore]
 fn test3() -> impl Driver {
     TestDriver
 }
 
-#[quint_test(spec = "spec.qnt", test = "test1Test")]
+#[quint_test(spec = "../../specs/spec.qnt", test = "test1Test")]
 #[should_panic = "some error"]
 fn test4() -> impl Driver {
     TestDriver
@@ -0,0 +1,23 @@
+use quint_connect::*;
+
+struct TestDriver;
+
+impl Driver for TestDriver {
+    type State = ();
+
+    fn step(&mut self, _step: &Step) -> Result {
+        todo!()
+    }
+}
+
+#[quint_test(spec = "../../specs/spec.qnt", matrix(test = ["firstTest", "secondTest"], seed = ["0x1"]))]
+fn test1() -> impl Driver {
+    TestDriver
+}
+
+#[quint_test(spec = "../../specs/spec.qnt", test = "someTest", matrix(max_samples = [1, 10]))]
+fn test2() -> impl Driver {
+    TestDriver
+}
+
+fn main() {}
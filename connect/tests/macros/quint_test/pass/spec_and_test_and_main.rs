@@ -10,7 +10,7 @@ impl Driver for TestDriver {
     }
 }
 
-#[quint_test(spec = "spec.qnt", test = "testPredicate", main = "main")]
+#[quint_test(spec = "../../specs/spec.qnt", test = "testPredicate", main = "main")]
 fn test1() -> impl Driver {
     TestDriver
 }
@@ -10,7 +10,7 @@ impl Driver for TestDriver {
     }
 }
 
-#[quint_test(spec = "spec.qnt", test = "testPredicate", max_samples = 100)]
+#[quint_test(spec = "../../specs/spec.qnt", test = "testPredicate", max_samples = 100)]
 fn test1() -> impl Driver {
     TestDriver
 }
@@ -10,7 +10,7 @@ impl Driver for TestDriver {
     }
 }
 
-#[quint_test(spec = "spec.qnt", test = "testPredicate", seed = "0x123abc")]
+#[quint_test(spec = "../../specs/spec.qnt", test = "testPredicate", seed = "0x123abc")]
 fn test1() -> impl Driver {
     TestDriver
 }
@@ -0,0 +1,16 @@
+use quint_connect::*;
+
+#[derive(Default)]
+struct TestDriver;
+
+impl Driver for TestDriver {
+    type State = ();
+
+    fn step(&mut self, _step: &Step) -> Result {
+        todo!()
+    }
+}
+
+quint_tests!(driver = TestDriver::default);
+
+fn main() {}
@@ -0,0 +1,27 @@
+use quint_connect::*;
+
+#[derive(Default)]
+struct TestDriver;
+
+impl Driver for TestDriver {
+    type State = ();
+
+    fn step(&mut self, _step: &Step) -> Result {
+        todo!()
+    }
+}
+
+mod counter {
+    use super::*;
+
+    quint_tests!(
+        spec = "../../specs/counter.qnt",
+        main = "counter",
+        driver = || TestDriver,
+        max_samples = 10,
+        seed = "0x42",
+        consts(N = 3),
+    );
+}
+
+fn main() {}
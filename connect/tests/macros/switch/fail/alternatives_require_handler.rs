@@ -0,0 +1,15 @@
+use quint_connect::*;
+
+struct TestDriver;
+
+impl Driver for TestDriver {
+    type State = ();
+
+    fn step(&mut self, step: &Step) -> Result {
+        switch!(step {
+            commit | abort,
+        })
+    }
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use quint_connect::*;
+
+struct TestDriver;
+
+impl Driver for TestDriver {
+    type State = ();
+
+    fn step(&mut self, step: &Step) -> Result {
+        switch!(step, case = snake_case {
+            _ => todo!(),
+        })
+    }
+}
+
+fn main() {}
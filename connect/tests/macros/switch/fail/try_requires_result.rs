@@ -0,0 +1,21 @@
+use quint_connect::*;
+
+struct TestDriver;
+
+impl TestDriver {
+    fn audit(&mut self) -> bool {
+        true
+    }
+}
+
+impl Driver for TestDriver {
+    type State = ();
+
+    fn step(&mut self, step: &Step) -> Result {
+        switch!(step {
+            audit => try self.audit(),
+        })
+    }
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+use quint_connect::*;
+
+struct TestDriver;
+
+impl TestDriver {
+    fn decide(&mut self, _node: String, _commit: bool) {}
+    fn move_to(&mut self, _pos: (u8, u8)) {}
+}
+
+impl Driver for TestDriver {
+    type State = ();
+
+    fn step(&mut self, step: &Step) -> Result {
+        switch!(step {
+            DecidesOnCommit(node) => self.decide(node, true),
+            DecidesOnAbort(node) | Timeout(node) => self.decide(node, false),
+            MoveX(corner as pos) | MoveX(coordinate as pos) => self.move_to(pos),
+            MoveX => self.move_to((1, 1)),
+        })
+    }
+}
+
+fn main() {}
@@ -0,0 +1,50 @@
+use quint_connect::*;
+use std::io;
+
+struct TestDriver;
+
+impl TestDriver {
+    fn init(&mut self) -> Result {
+        Ok(())
+    }
+
+    fn deposit(&mut self, amount: u8) -> Result<u8> {
+        anyhow::ensure!(amount > 0, "Invalid amount");
+        Ok(amount)
+    }
+
+    fn withdraw(&mut self, _amount: u8) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn audit(&mut self) -> bool {
+        true
+    }
+}
+
+impl Driver for TestDriver {
+    type State = ();
+
+    fn step(&mut self, step: &Step) -> Result {
+        switch!(step {
+            init,
+            deposit(amount),
+            withdraw(amount: u8) => try self.withdraw(amount),
+            audit,
+            refund(amount: u8) => try {
+                let amount = self.deposit(amount)?;
+                self.withdraw(amount)
+            },
+            _ => todo!()
+        })
+    }
+}
+
+fn main() {
+    let step = Step::builder("refund").pick("amount", 0u8).build().unwrap();
+    let err = TestDriver.step(&step).unwrap_err();
+    assert_eq!(
+        format!("{:#}", err),
+        "Failed to handle action `refund`: Invalid amount"
+    );
+}
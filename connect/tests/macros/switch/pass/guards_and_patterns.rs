@@ -0,0 +1,37 @@
+use quint_connect::*;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(tag = "tag")]
+enum Message {
+    Prepare,
+    Commit,
+    Abort,
+}
+
+struct TestDriver;
+
+impl TestDriver {
+    fn move_to(&mut self, _pos: (u8, u8)) {}
+    fn receive(&mut self, _msg: Message) {}
+    fn set(&mut self, _value: Option<u8>) {}
+}
+
+impl Driver for TestDriver {
+    type State = ();
+
+    fn step(&mut self, step: &Step) -> Result {
+        switch!(step {
+            Move(pos: (u8, u8)) if pos.0 == 1 => self.move_to(pos),
+            Move(pos as p: (u8, u8) @ (2, _) | (3, 3)) => self.move_to(p),
+            Send(msg as m: Message @ Message::Prepare | Message::Commit) => self.receive(m),
+            Send(msg: Message @ Message::Abort) if true => self.receive(msg),
+            set(value: u8? @ Some(1..=9)),
+            set(value: u8?),
+            _ if step.nondet_picks.get("ignored").is_some() => (),
+            _ => todo!(),
+        })
+    }
+}
+
+fn main() {}
@@ -0,0 +1,34 @@
+use quint_connect::*;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransferArgs {
+    from: String,
+    to_account: String,
+    amount: u64,
+    memo: Option<String>,
+}
+
+struct TestDriver;
+
+impl TestDriver {
+    fn transfer(&mut self, _args: TransferArgs) {}
+}
+
+impl Driver for TestDriver {
+    type State = ();
+
+    fn step(&mut self, step: &Step) -> Result {
+        switch!(step {
+            transfer(..args),
+            TransferMax(..args: TransferArgs) if args.amount > 100 => self.transfer(args),
+            Refund(..args: TransferArgs) => {
+                let TransferArgs { from, to_account, amount, memo } = args;
+                let _ = (from, to_account, amount, memo);
+            }
+        })
+    }
+}
+
+fn main() {}
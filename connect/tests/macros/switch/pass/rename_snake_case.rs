@@ -0,0 +1,26 @@
+use quint_connect::*;
+
+struct TestDriver;
+
+impl TestDriver {
+    fn init(&mut self) {}
+    fn spontaneously_prepares(&mut self, _node: String) {}
+    fn move_x(&mut self, _next_pos: (u8, u8)) {}
+    fn r#type(&mut self, _key_code: u8) {}
+}
+
+impl Driver for TestDriver {
+    type State = ();
+
+    fn step(&mut self, step: &Step) -> Result {
+        switch!(step, rename = snake_case {
+            init,
+            SpontaneouslyPrepares(node),
+            MoveX(next_pos),
+            Type(key_code: u8),
+            Explicit(node_id as id: String) => drop(id),
+        })
+    }
+}
+
+fn main() {}